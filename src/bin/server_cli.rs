@@ -1,31 +1,133 @@
+use strum::IntoEnumIterator;
 use tokio::{self, time};
 use blackjack_solver::server::card::ECardNumber;
+use blackjack_solver::server::deck::new_seed;
+use blackjack_solver::server::history::SHandHistoryRecorder;
 use blackjack_solver::server::player::static_strategy_player::SStaticStrategyPlayer;
+use blackjack_solver::server::preset::ERulePreset;
+use blackjack_solver::server::replay::replay_file;
+use blackjack_solver::server::rule::SGameRule;
 use blackjack_solver::server::table::STable;
 
 #[tokio::main]
 async fn main() {
     println!("{:?}", ECardNumber::Seven);
 
-    let player = SStaticStrategyPlayer::new();
-    let mut table = STable::new_random_deck();
-    table.buy_chips(10000);
+    // 两个座位 每个座位一个独立的玩家
+    let players = [SStaticStrategyPlayer::new(), SStaticStrategyPlayer::new()];
+    // --load <path> 从快照恢复牌桌 --save <path> 每次操作后保存快照
+    // --history <path> 每局结算后追加写入牌局记录
+    // --replay <path> 回放牌局记录 检查每次操作的结果是否与记录一致
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(path) = take_option(&mut args, "--replay") {
+        replay(&path);
+        return;
+    }
+    let load_path = take_option(&mut args, "--load");
+    let save_path = take_option(&mut args, "--save");
+    let history_path = take_option(&mut args, "--history");
+    let mut table = match load_path {
+        Some(path) => match STable::load(&path) {
+            Ok(table) => table,
+            Err(e) => {
+                eprintln!("{e}");
+                return;
+            }
+        },
+        None => match new_table(&args) {
+            Some(table) => table,
+            None => return,
+        },
+    };
+    println!("Seed: {}", table.seed);
+    if let Some(path) = history_path {
+        match SHandHistoryRecorder::open(&path) {
+            Ok(recorder) => table.history_recorder = Some(recorder),
+            Err(e) => {
+                eprintln!("{e}");
+                return;
+            }
+        }
+    }
     let mut c = 1;
     loop {
         print_table(&table);
-        println!("Player Chips：{:?}\tRound: {c}", table.player_chips);
+        println!("Round: {c}");
         c += 1;
+        let player = &players[table.get_current_seat().unwrap_or(0)];
         let action = player.action(&table);
         println!("Player操作：{action:?}");
         let result = table.receive_player_action(action);
         println!("Table Result: {result:?}\n\n");
+        if let Some(path) = &save_path {
+            if let Err(e) = table.save(path) {
+                eprintln!("{e}");
+            }
+        }
         time::sleep(time::Duration::from_millis(1)).await;
     }
 }
 
+fn replay(path: &str) {
+    match replay_file(path) {
+        Ok(errors) if errors.is_empty() => println!("Replay OK"),
+        Ok(errors) => {
+            for error in errors.iter() {
+                println!("{error}");
+            }
+            println!("{} round(s) regressed", errors.len());
+        }
+        Err(e) => eprintln!("{e}"),
+    }
+}
+
+/// 取出选项及其参数 剩余的为位置参数
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg == name)?;
+    args.remove(index);
+    match index < args.len() {
+        true => Some(args.remove(index)),
+        false => None,
+    }
+}
+
+/// 第一个参数为预设规则名称或规则文件路径(json/toml) 未指定时使用默认规则
+/// 第二个参数为随机数种子 指定相同种子可以复现牌局
+fn new_table(args: &[String]) -> Option<STable> {
+    let seed = match args.get(1) {
+        Some(arg) => match arg.parse::<u64>() {
+            Ok(seed) => seed,
+            Err(e) => {
+                eprintln!("invalid seed {arg}: {e}");
+                return None;
+            }
+        },
+        None => new_seed(),
+    };
+    let mut table = match args.first() {
+        Some(arg) => match ERulePreset::from_name(arg) {
+            Some(preset) => STable::from_preset(preset, seed),
+            None => match SGameRule::from_file(arg) {
+                Ok(rule) => STable::new_random_deck(rule, seed),
+                Err(e) => {
+                    eprintln!("{e}");
+                    eprintln!("presets: {:?}", ERulePreset::iter().map(|p| p.to_string()).collect::<Vec<_>>());
+                    return None;
+                }
+            },
+        },
+        None => STable::new_random_deck(SGameRule::default(), seed),
+    };
+    table.buy_chips(10000);
+    table.add_seat(10000);
+    Some(table)
+}
+
 fn print_table(table: &STable) {
     println!("Table State：{:?}", table.state);
-    println!("Player Chips：{:?}", table.player_chips);
+    for (index, seat) in table.seats.iter().enumerate() {
+        println!("Seat {} Chips：{:?}", index + 1, seat.player_chips);
+    }
 
     print!("Dealer手牌-{:?}：\t", table.dealer_hand.value());
     for card in &table.dealer_hand.hand.cards {
@@ -36,8 +138,9 @@ fn print_table(table: &STable) {
     print!("Player手牌：\n");
     for (index, hand) in table.player_hands.iter().enumerate() {
         print!(
-            "{}-{:?}-{:?}: \t",
+            "{}-{}-{:?}-{:?}: \t",
             index + 1,
+            hand.seat + 1,
             hand.betting_box,
             hand.value()
         );
@@ -0,0 +1,47 @@
+use blackjack_solver::server::deck::shuffle::{EShuffleProcedure, SDealerShuffle};
+use blackjack_solver::server::preset::ERulePreset;
+use blackjack_solver::server::solver::static_solver::{
+    get_preset_action_equity_table, get_preset_side_bet_equity_table, get_preset_strategy_table,
+    get_preset_switch_gain_table,
+};
+use blackjack_solver::server::solver::strategy_analyzer::shuffle_tracking;
+use strum::IntoEnumIterator;
+use tabled::Table;
+
+/// 按预设规则名称计算各操作的期望 未指定时计算所有预设
+fn main() {
+    let presets: Vec<ERulePreset> = match std::env::args().nth(1) {
+        Some(name) => match ERulePreset::from_name(&name) {
+            Some(preset) => vec![preset],
+            None => {
+                eprintln!("unknown preset: {name:?}");
+                eprintln!("presets: {:?}", ERulePreset::iter().map(|p| p.to_string()).collect::<Vec<_>>());
+                return;
+            }
+        },
+        None => ERulePreset::iter().collect(),
+    };
+    for preset in presets {
+        println!("{preset} ({} decks)", preset.decks_num());
+        println!("{}", Table::new(get_preset_action_equity_table(preset)));
+        println!("{}", Table::new(get_preset_strategy_table(preset)));
+        if let Some(table) = get_preset_switch_gain_table(preset) {
+            println!("{}", Table::new(table));
+        }
+        println!("{}", Table::new(get_preset_side_bet_equity_table(preset)));
+    }
+
+    // 6副牌 发出3/4后洗牌 比较各洗牌方式保留的牌序
+    let procedures = [
+        ("uniform", EShuffleProcedure::Uniform),
+        ("dealer", EShuffleProcedure::Dealer(SDealerShuffle::default())),
+        (
+            "dealer without plug",
+            EShuffleProcedure::Dealer(SDealerShuffle {
+                plug: false,
+                ..SDealerShuffle::default()
+            }),
+        ),
+    ];
+    println!("{}", Table::new(shuffle_tracking::get_table(&procedures, 312, 234, 52, 1000, 0)));
+}
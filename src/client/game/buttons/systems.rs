@@ -51,7 +51,9 @@ pub fn interact_with_split_button(
 ) {
     if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
         match game_state.get() {
-            GameState::PlayerSplitOrDoubleDownOrHitOrStand(_) => match *interaction {
+            GameState::PlayerSplitOrDoubleDownOrHitOrStand(_)
+            | GameState::PlayerSplitOrHitOrStand(_)
+            | GameState::PlayerSplitOrStand(_) => match *interaction {
                 Interaction::Pressed => {
                     *background_color = PRESSED_BUTTON_COLOR.into();
                     user_split_event_writer.send(EventRequestPlayerSplit {});
@@ -111,6 +113,7 @@ pub fn interact_with_hit_button(
     if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
         match game_state.get() {
             GameState::PlayerHitOrStand(_)
+            | GameState::PlayerSplitOrHitOrStand(_)
             | GameState::PlayerDoubleDownOrHitOrStand(_)
             | GameState::PlayerSplitOrDoubleDownOrHitOrStand(_) => match *interaction {
                 Interaction::Pressed => {
@@ -142,8 +145,11 @@ pub fn interact_with_stand_button(
     if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
         match game_state.get() {
             GameState::PlayerHitOrStand(_)
+            | GameState::PlayerSplitOrHitOrStand(_)
+            | GameState::PlayerSplitOrStand(_)
             | GameState::PlayerDoubleDownOrHitOrStand(_)
-            | GameState::PlayerSplitOrDoubleDownOrHitOrStand(_) => match *interaction {
+            | GameState::PlayerSplitOrDoubleDownOrHitOrStand(_)
+            | GameState::PlayerRescueOrStand(_) => match *interaction {
                 Interaction::Pressed => {
                     *background_color = PRESSED_BUTTON_COLOR.into();
                     user_stand_event_writer.send(EventRequestPlayerStand {});
@@ -331,7 +337,9 @@ pub fn update_split_button_on_state_change(
         // SplitButton
         for mut background_color in split_button_query.iter_mut() {
             match game_state.get() {
-                GameState::PlayerSplitOrDoubleDownOrHitOrStand(_) => {
+                GameState::PlayerSplitOrDoubleDownOrHitOrStand(_)
+                | GameState::PlayerSplitOrHitOrStand(_)
+                | GameState::PlayerSplitOrStand(_) => {
                     *background_color = ACTIVE_BUTTON_COLOR.into();
                 }
                 _ => {
@@ -371,6 +379,7 @@ pub fn update_hit_button_on_state_change(
         for mut background_color in hit_button_query.iter_mut() {
             match game_state.get() {
                 GameState::PlayerHitOrStand(_)
+                | GameState::PlayerSplitOrHitOrStand(_)
                 | GameState::PlayerDoubleDownOrHitOrStand(_)
                 | GameState::PlayerSplitOrDoubleDownOrHitOrStand(_) => {
                     *background_color = ACTIVE_BUTTON_COLOR.into();
@@ -392,8 +401,11 @@ pub fn update_stand_button_on_state_change(
         for mut background_color in stand_button_query.iter_mut() {
             match game_state.get() {
                 GameState::PlayerHitOrStand(_)
+                | GameState::PlayerSplitOrHitOrStand(_)
+                | GameState::PlayerSplitOrStand(_)
                 | GameState::PlayerDoubleDownOrHitOrStand(_)
-                | GameState::PlayerSplitOrDoubleDownOrHitOrStand(_) => {
+                | GameState::PlayerSplitOrDoubleDownOrHitOrStand(_)
+                | GameState::PlayerRescueOrStand(_) => {
                     *background_color = ACTIVE_BUTTON_COLOR.into();
                 }
                 _ => {
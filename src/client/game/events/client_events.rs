@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
-use crate::{client::resources::Focus, server::card::ECard};
+use crate::{
+    client::resources::Focus,
+    server::{card::ECard, seat::SHandSettlement, value::EValue},
+};
 
 #[derive(Event)]
 pub struct EventClientPlayerSplitCards {
@@ -34,7 +37,12 @@ pub struct EventClientUpdateState {}
 pub struct EventClientGameOver {
     pub bet_chips: usize,
     pub win_chips: usize,
+    pub insurance_chips: usize,
+    pub insurance_win_chips: usize,
     pub player_chips: usize,
+    // 每手牌的结算详情
+    pub hands: Vec<SHandSettlement>,
+    pub dealer_value: EValue,
 }
 
 /// 焦点转换事件
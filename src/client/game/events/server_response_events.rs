@@ -1,12 +1,21 @@
 use crate::server::card::ECard;
+use crate::server::table::ETableOutputEvent;
+use crate::server::seat::SHandSettlement;
+use crate::server::value::EValue;
 
 use bevy::prelude::*;
 
 // --- 被动触发事件 由server回传 ---
+/// 一次操作依次产生的所有事件 按顺序分发为下面对应的事件
+#[derive(Event)]
+pub struct EventResponseTableEvents {
+    pub events: Vec<ETableOutputEvent>,
+}
+
 #[derive(Event)]
 pub struct EventResponseInitGameWithCards {
-    pub player_cards: [ECard; 2],
-    pub dealer_cards: [ECard; 2],
+    pub player_cards: Vec<[ECard; 2]>,
+    pub dealer_cards: Vec<ECard>,
 }
 
 #[derive(Event)]
@@ -33,7 +42,11 @@ pub struct EventResponsePlayerStand {
 pub struct EventResponseGameOver {
     pub bet_chips: usize,
     pub win_chips: usize,
+    pub insurance_chips: usize,
+    pub insurance_win_chips: usize,
     pub player_chips: usize,
+    pub hands: Vec<SHandSettlement>,
+    pub dealer_value: EValue,
 }
 
 #[derive(Event)]
@@ -55,6 +68,7 @@ impl Plugin for ServerResponseEventsPlugin {
     fn build(&self, app: &mut App) {
         app
             // Event
+            .add_event::<EventResponseTableEvents>()
             .add_event::<EventResponseInitGameWithCards>()
             .add_event::<EventResponseWaitPlayerBuyInsurance>()
             .add_event::<EventResponseInsuranceResult>()
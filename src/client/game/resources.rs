@@ -134,6 +134,37 @@ impl ResFrameworkHandler {
             infobar_handler.set_value(q_text, String::from("Reset"));
         }
     }
+
+    /// 读取存档后 根据牌桌重建所有手牌
+    /// is_hole_card_revealed为false时 庄家的第二张牌为暗牌
+    pub fn rebuild_hands(
+        &mut self,
+        commands: &mut Commands,
+        assert_server: &Res<AssetServer>,
+        q_text: &mut Query<(&mut Text, Entity)>,
+        table: &STable,
+        is_hole_card_revealed: bool,
+    ) {
+        self.reset_hands(commands, assert_server, q_text);
+        // 重新开始高亮
+        self.focus = Focus::None;
+        if let Some(dealer_handler) = &mut self.dealer_handler {
+            for (index, card) in table.dealer_hand.hand.cards.iter().enumerate() {
+                let is_revealed = index != 1 || is_hole_card_revealed;
+                dealer_handler.draw_new_card(commands, assert_server, q_text, 0, *card, is_revealed);
+            }
+        }
+        if let Some(player_handler) = &mut self.player_handler {
+            for (hand_index, hand) in table.player_hands.iter().enumerate() {
+                if hand_index > 0 {
+                    player_handler.hands_handler.push_blank_hand(commands, assert_server);
+                }
+                for card in &hand.hand.cards {
+                    player_handler.draw_new_card(commands, assert_server, q_text, hand_index, *card, true);
+                }
+            }
+        }
+    }
 }
 
 impl InfobarHandler {
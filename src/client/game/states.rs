@@ -17,28 +17,40 @@ pub enum SimulationState {
 pub enum GameState {
     #[default]
     PlayerBet,
+    PlayerSwitch(usize),
     DealerCheckBlackJack,
     PlayerBuyInsurance, // TODO 添加Insurance功能
+    PlayerSurrender(usize), // TODO 添加Surrender功能
     PlayerSplitOrDoubleDownOrHitOrStand(usize),
+    PlayerSplitOrHitOrStand(usize),
+    PlayerSplitOrStand(usize),
     PlayerDoubleDownOrHitOrStand(usize),
     PlayerHitOrStand(usize),
+    PlayerRescueOrStand(usize),
     DealerHitOrStand,
     CheckResultAndReset,
+    DealerShuffle,
 }
 
 impl From<ETableState> for GameState {
     fn from(value: ETableState) -> Self {
         match value {
             ETableState::PlayerBet => GameState::PlayerBet,
+            ETableState::PlayerSwitch(hand_index) => GameState::PlayerSwitch(hand_index),
             ETableState::DealerCheckBlackJack => GameState::DealerCheckBlackJack,
             ETableState::PlayerBuyInsurance => GameState::PlayerBuyInsurance,
+            ETableState::PlayerSurrender(hand_index) => GameState::PlayerSurrender(hand_index),
             ETableState::PlayerSplitOrDoubleDownOrHitOrStand(hand_index) => {
                 GameState::PlayerSplitOrDoubleDownOrHitOrStand(hand_index)
             }
+            ETableState::PlayerSplitOrHitOrStand(hand_index) => GameState::PlayerSplitOrHitOrStand(hand_index),
+            ETableState::PlayerSplitOrStand(hand_index) => GameState::PlayerSplitOrStand(hand_index),
             ETableState::PlayerDoubleDownOrHitOrStand(hand_index) => GameState::PlayerDoubleDownOrHitOrStand(hand_index),
             ETableState::PlayerHitOrStand(hand_index) => GameState::PlayerHitOrStand(hand_index),
+            ETableState::PlayerRescueOrStand(hand_index) => GameState::PlayerRescueOrStand(hand_index),
             ETableState::DealerHitOrStand => GameState::DealerHitOrStand,
             ETableState::CheckResultAndReset => GameState::CheckResultAndReset,
+            ETableState::DealerShuffle => GameState::DealerShuffle,
         }
     }
 }
@@ -147,7 +147,11 @@ pub fn handle_client_game_over(
         let EventClientGameOver {
             bet_chips,
             win_chips,
+            insurance_chips,
+            insurance_win_chips,
             player_chips,
+            hands,
+            dealer_value,
         } = event;
         info!(
             "Receive Event: EventClientGameOver\t chips:{:?}\t bet:{:?}\t win:{:?}",
@@ -155,14 +159,30 @@ pub fn handle_client_game_over(
         );
         // 避免blackjack的情况下未揭示庄家底牌
         res_framework_handler.dealer_reveal_card(&assert_server, &mut q_text, &mut q_img);
-        // 修改info bar 文案
-        event_writer.send(EventClientUpdateInfoBar {
-            new_info: format!(
-                "Game Over\nchips:{:?}  bet:{:?}  win:{:?}",
-                player_chips, bet_chips, win_chips
-            )
-            .into(),
-        });
+        // 修改info bar 文案 买了保险时单独展示保险结果
+        let mut new_info = format!(
+            "Game Over\nchips:{:?}  bet:{:?}  win:{:?}",
+            player_chips, bet_chips, win_chips
+        );
+        if *insurance_chips > 0 {
+            new_info += &format!(
+                "\ninsurance:{:?}  insurance win:{:?}",
+                insurance_chips, insurance_win_chips
+            );
+        }
+        // 每手牌的结果 split后的手牌分别展示
+        new_info += &format!("\ndealer:{dealer_value}");
+        for (index, hand) in hands.iter().enumerate() {
+            new_info += &format!(
+                "\nhand {}: {:?}  {}  wager:{}  payout:{}",
+                index + 1,
+                hand.outcome,
+                hand.value,
+                hand.wager,
+                hand.payout
+            );
+        }
+        event_writer.send(EventClientUpdateInfoBar { new_info });
     }
 }
 
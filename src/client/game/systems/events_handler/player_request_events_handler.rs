@@ -1,8 +1,8 @@
 use bevy::prelude::*;
 
 use crate::{
-    client::resources::{Focus, ResFrameworkHandler, ResGameTable},
-    server::{player::EPlayerAction, table::ETableOutputEvent},
+    client::resources::{ResFrameworkHandler, ResGameTable},
+    server::player::EPlayerAction,
 };
 
 use super::super::super::events::{player_request_events::*, server_response_events::*};
@@ -13,7 +13,7 @@ pub fn handle_request_player_bet(
     assert_server: Res<AssetServer>,
     mut res_framework_handler: ResMut<ResFrameworkHandler>,
     mut event_reader: EventReader<EventRequestPlayerBet>,
-    mut event_writer: EventWriter<EventResponseInitGameWithCards>,
+    mut event_writer: EventWriter<EventResponseTableEvents>,
     mut table: ResMut<ResGameTable>,
 ) {
     for event in event_reader.read().into_iter() {
@@ -28,17 +28,8 @@ pub fn handle_request_player_bet(
             .table
             .receive_player_action(EPlayerAction::Bet(event.value));
         match result {
-            Ok(ETableOutputEvent::InitGameWithCards {
-                player_cards,
-                dealer_cards,
-            }) => {
-                event_writer.send(EventResponseInitGameWithCards {
-                    player_cards,
-                    dealer_cards,
-                });
-            }
-            Ok(r) => {
-                error!("EPlayerAction::Bet 返回类型错误:{r:?}");
+            Ok(events) => {
+                event_writer.send(EventResponseTableEvents { events });
             }
             Err(e) => {
                 error!("{e:?}")
@@ -49,26 +40,15 @@ pub fn handle_request_player_bet(
 
 pub fn handle_request_player_hit(
     mut event_reader: EventReader<EventRequestPlayerHit>,
-    mut event_writer: EventWriter<EventResponsePlayerDrawCard>,
+    mut event_writer: EventWriter<EventResponseTableEvents>,
     mut table: ResMut<ResGameTable>,
 ) {
     for _ in event_reader.read().into_iter() {
         info!("Receive Event: RequestPlayerHit");
         let result = table.table.receive_player_action(EPlayerAction::Hit);
         match result {
-            Ok(ETableOutputEvent::PlayerDrawCard {
-                card,
-                hand_index,
-                is_player_stop,
-            }) => {
-                event_writer.send(EventResponsePlayerDrawCard {
-                    card,
-                    hand_index,
-                    is_player_stop,
-                });
-            }
-            Ok(r) => {
-                error!("EPlayerAction::Hit 返回类型错误:{r:?}");
+            Ok(events) => {
+                event_writer.send(EventResponseTableEvents { events });
             }
             Err(e) => {
                 error!("{e:?}")
@@ -79,18 +59,15 @@ pub fn handle_request_player_hit(
 
 pub fn handle_request_player_stand(
     mut event_reader: EventReader<EventRequestPlayerStand>,
-    mut event_writer: EventWriter<EventResponsePlayerStand>,
+    mut event_writer: EventWriter<EventResponseTableEvents>,
     mut table: ResMut<ResGameTable>,
 ) {
     for _ in event_reader.read().into_iter() {
         info!("Receive Event: RequestPlayerStand");
         let result = table.table.receive_player_action(EPlayerAction::Stand);
         match result {
-            Ok(ETableOutputEvent::PlayerStand { is_player_stop }) => {
-                event_writer.send(EventResponsePlayerStand { is_player_stop });
-            }
-            Ok(r) => {
-                error!("EPlayerAction::Stand 返回类型错误:{r:?}");
+            Ok(events) => {
+                event_writer.send(EventResponseTableEvents { events });
             }
             Err(e) => {
                 error!("{e:?}")
@@ -101,30 +78,15 @@ pub fn handle_request_player_stand(
 
 pub fn handle_request_player_split(
     mut event_reader: EventReader<EventRequestPlayerSplit>,
-    mut event_writer: EventWriter<EventResponsePlayerSplitCards>,
+    mut event_writer: EventWriter<EventResponseTableEvents>,
     mut table: ResMut<ResGameTable>,
-    res_framework_handler: Res<ResFrameworkHandler>,
 ) {
     for _ in event_reader.read().into_iter() {
         info!("Receive Event: RequestPlayerSplit");
         let result = table.table.receive_player_action(EPlayerAction::Split);
         match result {
-            Ok(ETableOutputEvent::PlayerSplitCards { card1, card2 }) => {
-                match res_framework_handler.focus {
-                    Focus::Player(hand_index) => {
-                        event_writer.send(EventResponsePlayerSplitCards {
-                            card1,
-                            card2,
-                            hand_index: hand_index,
-                        });
-                    }
-                    _ => {
-                        error!("focus error: not focus on player hand!")
-                    }
-                }
-            }
-            Ok(r) => {
-                error!("EPlayerAction::Split 返回类型错误:{r:?}");
+            Ok(events) => {
+                event_writer.send(EventResponseTableEvents { events });
             }
             Err(e) => {
                 error!("{e:?}")
@@ -135,26 +97,15 @@ pub fn handle_request_player_split(
 
 pub fn handle_request_player_double_down(
     mut event_reader: EventReader<EventRequestPlayerDoubleDown>,
-    mut event_writer: EventWriter<EventResponsePlayerDrawCard>,
+    mut event_writer: EventWriter<EventResponseTableEvents>,
     mut table: ResMut<ResGameTable>,
 ) {
     for _ in event_reader.read().into_iter() {
         info!("Receive Event: RequestPlayerDoubleDown");
         let result = table.table.receive_player_action(EPlayerAction::DoubleDown);
         match result {
-            Ok(ETableOutputEvent::PlayerDrawCard {
-                card,
-                hand_index,
-                is_player_stop,
-            }) => {
-                event_writer.send(EventResponsePlayerDrawCard {
-                    card,
-                    hand_index,
-                    is_player_stop,
-                });
-            }
-            Ok(r) => {
-                error!("EPlayerAction::DoubleDown 返回类型错误:{r:?}");
+            Ok(events) => {
+                event_writer.send(EventResponseTableEvents { events });
             }
             Err(e) => {
                 error!("{e:?}")
@@ -1,8 +1,11 @@
 use bevy::prelude::*;
 
-use crate::client::{
-    resources::{ResFrameworkHandler, ResGameTable},
-    states::{FocusState, GameState},
+use crate::{
+    client::{
+        resources::{Focus, ResFrameworkHandler, ResGameTable},
+        states::{FocusState, GameState},
+    },
+    server::{seat::SSeatSettlement, table::ETableOutputEvent},
 };
 
 use super::{
@@ -11,6 +14,96 @@ use super::{
 };
 use super::EventClientDealerDrawCard;
 
+/// 按顺序分发一次操作产生的所有事件 最后根据server状态更新client状态
+pub fn handle_response_table_events(
+    mut event_reader: EventReader<EventResponseTableEvents>,
+    res_framework_handler: Res<ResFrameworkHandler>,
+    mut event_writer_init_game_with_cards: EventWriter<EventResponseInitGameWithCards>,
+    mut event_writer_player_draw_card: EventWriter<EventResponsePlayerDrawCard>,
+    mut event_writer_player_split_cards: EventWriter<EventResponsePlayerSplitCards>,
+    mut event_writer_player_stand: EventWriter<EventResponsePlayerStand>,
+    mut event_writer_dealer_draw_card: EventWriter<EventResponseDealerDrawCard>,
+    mut event_writer_game_over: EventWriter<EventResponseGameOver>,
+    mut event_writer_update_state: EventWriter<EventClientUpdateState>,
+) {
+    for EventResponseTableEvents { events } in event_reader.read() {
+        info!("Receive Event: ResponseTableEvents	{events:?}");
+        for event in events.iter().cloned() {
+            match event {
+                ETableOutputEvent::InitGameWithCards {
+                    player_cards,
+                    dealer_cards,
+                    ..
+                } => {
+                    event_writer_init_game_with_cards.send(EventResponseInitGameWithCards {
+                        player_cards,
+                        dealer_cards,
+                    });
+                }
+                ETableOutputEvent::PlayerDrawCard {
+                    card,
+                    hand_index,
+                    is_player_stop,
+                } => {
+                    event_writer_player_draw_card.send(EventResponsePlayerDrawCard {
+                        card,
+                        hand_index,
+                        is_player_stop,
+                    });
+                }
+                // split的手牌为当前focus的手牌
+                ETableOutputEvent::PlayerSplitCards { card1, card2 } => match res_framework_handler.focus {
+                    Focus::Player(hand_index) => {
+                        event_writer_player_split_cards.send(EventResponsePlayerSplitCards {
+                            card1,
+                            card2,
+                            hand_index,
+                        });
+                    }
+                    _ => {
+                        error!("focus error: not focus on player hand!")
+                    }
+                },
+                ETableOutputEvent::PlayerStand { is_player_stop } => {
+                    event_writer_player_stand.send(EventResponsePlayerStand { is_player_stop });
+                }
+                ETableOutputEvent::DealerHit { card } => {
+                    event_writer_dealer_draw_card.send(EventResponseDealerDrawCard {
+                        card,
+                        is_revealed: true,
+                    });
+                }
+                ETableOutputEvent::GameOver {
+                    seats,
+                    hands,
+                    dealer_value,
+                    ..
+                } => {
+                    // 客户端只有一个座位
+                    let SSeatSettlement {
+                        player_chips,
+                        bet_chips,
+                        win_chips,
+                        insurance_chips,
+                        insurance_win_chips,
+                    } = seats[0].clone();
+                    event_writer_game_over.send(EventResponseGameOver {
+                        bet_chips,
+                        win_chips,
+                        insurance_chips,
+                        insurance_win_chips,
+                        player_chips,
+                        hands: hands.into_iter().filter(|hand| hand.seat == 0).collect(),
+                        dealer_value,
+                    });
+                }
+                _ => {}
+            }
+        }
+        event_writer_update_state.send(EventClientUpdateState {});
+    }
+}
+
 pub fn handle_response_init_game_with_cards(
     mut event_reader: EventReader<EventResponseInitGameWithCards>,
     // table: ResMut<ResGameTable>,
@@ -29,21 +122,22 @@ pub fn handle_response_init_game_with_cards(
         info!("dealer cards:{:?}", dealer_cards);
         info!("player cards:{:?}", player_cards);
 
-        event_writer_dealer_draw_card.send(EventResponseDealerDrawCard {
-            card: dealer_cards[0],
-            is_revealed: true,
-        });
-        event_writer_dealer_draw_card.send(EventResponseDealerDrawCard {
-            card: dealer_cards[1],
-            is_revealed: false,
-        });
-        for card in player_cards {
-            event_writer_player_draw_card.send(EventResponsePlayerDrawCard {
+        // 第一张为明牌 第二张为底牌(无底牌规则下庄家只有一张牌)
+        for (index, card) in dealer_cards.iter().enumerate() {
+            event_writer_dealer_draw_card.send(EventResponseDealerDrawCard {
                 card: card.clone(),
-                hand_index: 0, // todo 多路開局
-                is_player_stop: false,
+                is_revealed: index == 0,
             });
         }
+        for (hand_index, cards) in player_cards.iter().enumerate() {
+            for card in cards {
+                event_writer_player_draw_card.send(EventResponsePlayerDrawCard {
+                    card: card.clone(),
+                    hand_index,
+                    is_player_stop: false,
+                });
+            }
+        }
         // update_client_state(
         //     &table,
         //     &mut game_state_next_state,
@@ -137,16 +231,24 @@ pub fn handle_response_game_over(
         let EventResponseGameOver {
             bet_chips,
             win_chips,
+            insurance_chips,
+            insurance_win_chips,
             player_chips,
+            hands,
+            dealer_value,
         } = event;
         info!(
-            "Receive Event: ResponseGameOver\tbet:{:?}\twin:{:?}",
-            bet_chips, win_chips
+            "Receive Event: ResponseGameOver\tbet:{:?}\twin:{:?}\tinsurance:{:?}\tinsurance win:{:?}",
+            bet_chips, win_chips, insurance_chips, insurance_win_chips
         );
         event_writer.send(EventClientGameOver {
             bet_chips: *bet_chips,
             win_chips: *win_chips,
+            insurance_chips: *insurance_chips,
+            insurance_win_chips: *insurance_win_chips,
             player_chips: *player_chips,
+            hands: hands.clone(),
+            dealer_value: *dealer_value,
         });
     }
 }
@@ -202,6 +304,7 @@ impl Plugin for ServerResponseEventsHandlerPlugin {
             .add_systems(
                 Update,
                 (
+                    handle_response_table_events,
                     handle_response_init_game_with_cards,
                     handle_response_wait_player_buy_insurance,
                     handle_response_insurance_result,
@@ -1,5 +1,22 @@
 use bevy::prelude::*;
 
+use crate::{
+    client::{
+        game::{
+            client_events::EventClientFocusChange, server_response_events::EventResponseTableEvents,
+            states::GameState,
+        },
+        resources::{ResFrameworkHandler, ResGameTable},
+        states::FocusState,
+    },
+    server::{player::EPlayerAction, table::STable},
+};
+
+use super::systems::update_client_state;
+
+// 存档文件路径
+const SAVE_PATH: &str = "blackjack_save.json";
+
 pub fn keyboard_esc_exit_game(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut app_exit_event_writer: EventWriter<AppExit>,
@@ -7,4 +24,71 @@ pub fn keyboard_esc_exit_game(
     if keyboard_input.just_pressed(KeyCode::Escape) {
         app_exit_event_writer.send(AppExit::Success);
     }
-}
\ No newline at end of file
+}
+
+/// F5保存牌桌 F9读取存档
+pub fn keyboard_save_load_game(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut commands: Commands,
+    assert_server: Res<AssetServer>,
+    mut q_text: Query<(&mut Text, Entity)>,
+    mut table: ResMut<ResGameTable>,
+    mut res_framework_handler: ResMut<ResFrameworkHandler>,
+    mut game_state_next_state: ResMut<NextState<GameState>>,
+    mut res_focus_next_state: ResMut<NextState<FocusState>>,
+    mut event_writer: EventWriter<EventClientFocusChange>,
+    mut table_events_event_writer: EventWriter<EventResponseTableEvents>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        let info = match table.table.save(SAVE_PATH) {
+            Ok(()) => format!("Saved to {SAVE_PATH}"),
+            Err(e) => e.to_string(),
+        };
+        info!("{info}");
+        res_framework_handler.infobar_set_new_info(&mut q_text, info);
+    }
+    if keyboard_input.just_pressed(KeyCode::F9) {
+        match STable::load(SAVE_PATH) {
+            Ok(loaded) => {
+                table.table = loaded;
+                let game_state: GameState = table.table.get_state().into();
+                // 轮到庄家行动之前 暗牌不翻开
+                let is_hole_card_revealed = matches!(
+                    game_state,
+                    GameState::PlayerBet
+                        | GameState::DealerHitOrStand
+                        | GameState::CheckResultAndReset
+                        | GameState::DealerShuffle
+                );
+                res_framework_handler.rebuild_hands(
+                    &mut commands,
+                    &assert_server,
+                    &mut q_text,
+                    &table.table,
+                    is_hole_card_revealed,
+                );
+                res_framework_handler.infobar_set_new_info(&mut q_text, format!("Loaded {SAVE_PATH}"));
+                // 存档停在庄家行动的状态时 继续推进到需要玩家操作的状态
+                if table.table.is_dealer_state() {
+                    match table.table.receive_player_action(EPlayerAction::WaitNext) {
+                        Ok(events) => {
+                            table_events_event_writer.send(EventResponseTableEvents { events });
+                        }
+                        Err(e) => error!("{e:?}"),
+                    }
+                }
+                update_client_state(
+                    &table,
+                    &mut game_state_next_state,
+                    &mut res_focus_next_state,
+                    &mut res_framework_handler,
+                    &mut event_writer,
+                );
+            }
+            Err(e) => {
+                error!("{e}");
+                res_framework_handler.infobar_set_new_info(&mut q_text, e.to_string());
+            }
+        }
+    }
+}
@@ -28,6 +28,7 @@ impl Plugin for SystemPlugin {
                 Update,
                 (
                     keyboard_esc_exit_game,
+                    keyboard_save_load_game,
                     update_server_state,
                 ),
             )
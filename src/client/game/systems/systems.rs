@@ -6,65 +6,40 @@ use crate::{
         game::{
             client_events::EventClientFocusChange,
             components::CompCard,
-            server_response_events::{EventResponseDealerDrawCard, EventResponseGameOver},
+            server_response_events::EventResponseTableEvents,
             states::GameState,
         },
         // player::resources::ResPlayer,
         resources::{Focus, ResFrameworkHandler, ResGameTable},
         states::FocusState,
     },
-    server::{player::EPlayerAction, table::ETableOutputEvent},
+    server::player::EPlayerAction,
 };
 
 /// 向server发送请求，并更新server状态。
+/// 庄家行动的状态由server自动推进 这里只处理client暂不支持的玩家选择(保险、投降、交换)
 pub fn update_server_state(
     game_state: Res<State<GameState>>,
     mut table: ResMut<ResGameTable>,
     mut game_state_next_state: ResMut<NextState<GameState>>,
-    mut dealer_draw_card_event_writer: EventWriter<EventResponseDealerDrawCard>,
-    mut game_over_event_writer: EventWriter<EventResponseGameOver>,
+    mut table_events_event_writer: EventWriter<EventResponseTableEvents>,
     mut res_focus_next_state: ResMut<NextState<FocusState>>,
     mut res_framework_handler: ResMut<ResFrameworkHandler>,
     mut event_writer: EventWriter<EventClientFocusChange>,
 ) {
     let action: Option<EPlayerAction> = match game_state.get() {
-        GameState::DealerCheckBlackJack
-        | GameState::DealerHitOrStand
-        | GameState::CheckResultAndReset => Some(EPlayerAction::WaitNext),
         GameState::PlayerBuyInsurance => Some(EPlayerAction::BuyInsurance(0)),
+        GameState::PlayerSurrender(_) => Some(EPlayerAction::NoAction),
+        GameState::PlayerSwitch(_) => Some(EPlayerAction::NoAction),
         _ => None,
     };
 
     match action {
         Some(action) => match table.table.receive_player_action(action.clone()) {
-            Ok(r) => {
+            Ok(events) => {
                 info!("Player Request: {action:?}");
-                info!("Server Response: {r:?}");
-
-                match r {
-                    ETableOutputEvent::DealerHit { card } => {
-                        dealer_draw_card_event_writer.send(EventResponseDealerDrawCard {
-                            card,
-                            is_revealed: true,
-                        });
-                    }
-                    ETableOutputEvent::GameOver {
-                        bet_chips,
-                        win_chips,
-                        player_chips,
-                    } => {
-                        game_over_event_writer.send(EventResponseGameOver {
-                            bet_chips,
-                            win_chips,
-                            player_chips,
-                        });
-                    }
-                    _ => {}
-                }
-
-                let game_state = table.table.get_state();
-                info!("New GameState:{game_state:?}");
-                game_state_next_state.set(game_state.into());
+                info!("Server Response: {events:?}");
+                table_events_event_writer.send(EventResponseTableEvents { events });
 
                 update_client_state(
                     &table,
@@ -102,10 +77,16 @@ pub fn update_client_state(
         GameState::PlayerBet | GameState::CheckResultAndReset => (FocusState::None, Focus::None),
         GameState::DealerHitOrStand
         | GameState::DealerCheckBlackJack
-        | GameState::PlayerBuyInsurance => (FocusState::Dealer, Focus::Dealer),
-        GameState::PlayerSplitOrDoubleDownOrHitOrStand(hand_index)
+        | GameState::PlayerBuyInsurance
+        | GameState::DealerShuffle => (FocusState::Dealer, Focus::Dealer),
+        GameState::PlayerSwitch(hand_index)
+        | GameState::PlayerSurrender(hand_index)
+        | GameState::PlayerSplitOrDoubleDownOrHitOrStand(hand_index)
+        | GameState::PlayerSplitOrHitOrStand(hand_index)
+        | GameState::PlayerSplitOrStand(hand_index)
         | GameState::PlayerDoubleDownOrHitOrStand(hand_index)
-        | GameState::PlayerHitOrStand(hand_index) => {
+        | GameState::PlayerHitOrStand(hand_index)
+        | GameState::PlayerRescueOrStand(hand_index) => {
             (FocusState::Player(hand_index), Focus::Player(hand_index))
         }
     };
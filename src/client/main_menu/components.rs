@@ -6,5 +6,11 @@ pub struct MainMenu{}
 #[derive(Component)]
 pub struct PlayButton {}
 
+#[derive(Component)]
+pub struct PresetButton {}
+
+#[derive(Component)]
+pub struct PresetText {}
+
 #[derive(Component)]
 pub struct QuitButton {}
\ No newline at end of file
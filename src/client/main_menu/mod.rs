@@ -1,9 +1,11 @@
 mod components;
+mod resources;
 mod styles;
 mod systems;
 
 use bevy::prelude::*;
 use systems::layout::*;
+use resources::ResRulePreset;
 use systems::interactions::*;
 
 use super::AppState;
@@ -13,10 +15,20 @@ pub struct MainMenuPlugin;
 impl Plugin for MainMenuPlugin {
     fn build(&self, app: &mut App) {
         app
+            // Resources
+            .init_resource::<ResRulePreset>()
             // On Enter State
             .add_systems(OnEnter(AppState::MainMenu), spawn_main_menu)
             // Syetems
-            .add_systems(Update, (interact_with_play_button, interact_with_quit_button))
+            .add_systems(
+                Update,
+                (
+                    interact_with_play_button,
+                    interact_with_preset_button,
+                    interact_with_quit_button,
+                    update_preset_text,
+                ),
+            )
             // On Exit State
             .add_systems(OnExit(AppState::MainMenu), despawn_main_menu)
             ;
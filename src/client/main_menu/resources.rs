@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+use crate::server::preset::ERulePreset;
+use strum::IntoEnumIterator;
+
+/// 主菜单中选择的预设规则 None表示使用默认规则
+#[derive(Resource, Default)]
+pub struct ResRulePreset {
+    pub preset: Option<ERulePreset>,
+}
+
+impl ResRulePreset {
+    /// 切换到下一个预设 最后一个预设之后回到默认规则
+    pub fn next(&mut self) {
+        let presets: Vec<ERulePreset> = ERulePreset::iter().collect();
+        self.preset = match self.preset {
+            None => presets.first().copied(),
+            Some(preset) => {
+                let index = presets.iter().position(|p| *p == preset).unwrap();
+                presets.get(index + 1).copied()
+            }
+        };
+    }
+
+    pub fn name(&self) -> String {
+        match self.preset {
+            Some(preset) => preset.to_string(),
+            None => String::from("Default"),
+        }
+    }
+}
@@ -25,6 +25,15 @@ pub const BUTTON_STYLE: Style = {
     style
 };
 
+pub const PRESET_BUTTON_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.justify_content = JustifyContent::Center;
+    style.align_items = AlignItems::Center;
+    style.width = Val::Px(600.0);
+    style.height = Val::Px(80.0);
+    style
+};
+
 pub const IMAGE_STYLE: Style = {
     let mut style = Style::DEFAULT;
     style.width = Val::Px(64.0);
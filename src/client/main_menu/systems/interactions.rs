@@ -2,8 +2,11 @@ use bevy::app::AppExit;
 use bevy::prelude::*;
 
 use crate::client::resources::ResGameTable;
+use crate::server::deck::new_seed;
+use crate::server::table::STable;
 
 use super::super::components::*;
+use super::super::resources::ResRulePreset;
 use super::super::styles::{HOVERED_BUTTON_COLOR, NORMAL_BUTTON_COLOR, PRESSED_BUTTON_COLOR};
 use super::super::AppState;
 
@@ -14,11 +17,16 @@ pub fn interact_with_play_button(
     >,
     mut app_state_next_state: ResMut<NextState<AppState>>,
     mut res_game_table: ResMut<ResGameTable>,
+    res_rule_preset: Res<ResRulePreset>,
 ) {
     if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
         match *interaction {
             Interaction::Pressed => {
                 *background_color = PRESSED_BUTTON_COLOR.into();
+                // 使用选择的预设规则重新创建牌桌
+                if let Some(preset) = res_rule_preset.preset {
+                    res_game_table.table = STable::from_preset(preset, new_seed());
+                }
                 res_game_table.table.buy_chips(10000); // 调整购买码量
                 app_state_next_state.set(AppState::Game);
             }
@@ -32,6 +40,43 @@ pub fn interact_with_play_button(
     }
 }
 
+/// 点击切换预设规则
+pub fn interact_with_preset_button(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<PresetButton>),
+    >,
+    mut res_rule_preset: ResMut<ResRulePreset>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                res_rule_preset.next();
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+/// 预设规则变化时更新按钮文字
+pub fn update_preset_text(
+    res_rule_preset: Res<ResRulePreset>,
+    mut text_query: Query<&mut Text, With<PresetText>>,
+) {
+    if !res_rule_preset.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.sections[0].value = format!("Rule: {}", res_rule_preset.name());
+    }
+}
+
 pub fn interact_with_quit_button(
     mut app_exit_event_writer: EventWriter<AppExit>,
     mut button_query: Query<
@@ -1,8 +1,13 @@
 use super::super::{components::*, styles::*};
+use super::super::resources::ResRulePreset;
 use bevy::prelude::*;
 
-pub fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let main_menu_entity = build_main_menu(&mut commands, &asset_server);
+pub fn spawn_main_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    res_rule_preset: Res<ResRulePreset>,
+) {
+    let main_menu_entity = build_main_menu(&mut commands, &asset_server, &res_rule_preset);
 }
 
 pub fn despawn_main_menu(mut commands: Commands, main_menu_query: Query<Entity, With<MainMenu>>) {
@@ -11,7 +16,11 @@ pub fn despawn_main_menu(mut commands: Commands, main_menu_query: Query<Entity,
     }
 }
 
-pub fn build_main_menu(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+pub fn build_main_menu(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    res_rule_preset: &ResRulePreset,
+) -> Entity {
     let main_menu_entity = commands
         .spawn((
             NodeBundle {
@@ -64,6 +73,32 @@ pub fn build_main_menu(commands: &mut Commands, asset_server: &Res<AssetServer>)
                         ..default()
                     });
                 });
+            // === Preset Button ===
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: PRESET_BUTTON_STYLE,
+                        background_color: NORMAL_BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    PresetButton {},
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle {
+                            text: Text {
+                                sections: vec![TextSection::new(
+                                    format!("Rule: {}", res_rule_preset.name()),
+                                    get_button_text_style(asset_server),
+                                )],
+                                justify: JustifyText::Center,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        PresetText {},
+                    ));
+                });
             // === Quit Button ===
             parent
                 .spawn((
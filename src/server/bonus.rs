@@ -0,0 +1,120 @@
+use super::card::{ECard, ECardColor, ECardNumber};
+use fraction::Fraction;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+/// 西班牙21点的21点奖金 奖金代替1:1赔付
+#[derive(Debug, Copy, Clone, EnumIter, Hash, Display, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EBonus21 {
+    // 5张牌组成21点
+    FiveCard,
+    // 6张牌组成21点
+    SixCard,
+    // 7张及以上的牌组成21点
+    SevenPlusCard,
+    // 不同花色的6-7-8
+    Mixed678,
+    // 同花色的6-7-8
+    Suited678,
+    // 黑桃6-7-8
+    Spades678,
+    // 不同花色的7-7-7
+    Mixed777,
+    // 同花色的7-7-7
+    Suited777,
+    // 黑桃7-7-7
+    Spades777,
+}
+
+impl EBonus21 {
+    /// 根据21点手牌的张数和组成判断奖金类型
+    /// 调用方需保证手牌点数为21且不是blackjack
+    pub fn from_cards(cards: &[ECard]) -> Option<Self> {
+        match cards.len() {
+            3 => {
+                let mut values = [cards[0].value, cards[1].value, cards[2].value];
+                values.sort_by_key(|value| *value as usize);
+                let color = cards[0].color;
+                let is_suited = cards.iter().all(|card| card.color == color);
+                let is_spades = is_suited && color == ECardColor::Spades;
+                match values {
+                    [ECardNumber::Six, ECardNumber::Seven, ECardNumber::Eight] => {
+                        Some(match (is_spades, is_suited) {
+                            (true, _) => EBonus21::Spades678,
+                            (_, true) => EBonus21::Suited678,
+                            _ => EBonus21::Mixed678,
+                        })
+                    }
+                    [ECardNumber::Seven, ECardNumber::Seven, ECardNumber::Seven] => {
+                        Some(match (is_spades, is_suited) {
+                            (true, _) => EBonus21::Spades777,
+                            (_, true) => EBonus21::Suited777,
+                            _ => EBonus21::Mixed777,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            cards_num => EBonus21::from_cards_num(cards_num),
+        }
+    }
+
+    /// 只根据21点手牌的张数判断奖金类型 即5张、6张和7张及以上的奖金
+    pub fn from_cards_num(cards_num: usize) -> Option<Self> {
+        match cards_num {
+            5 => Some(EBonus21::FiveCard),
+            6 => Some(EBonus21::SixCard),
+            x if x >= 7 => Some(EBonus21::SevenPlusCard),
+            _ => None,
+        }
+    }
+
+    /// 标准赔率表
+    pub fn default_pay_table() -> Vec<(EBonus21, Fraction)> {
+        vec![
+            (EBonus21::FiveCard, Fraction::new(3u64, 2u64)),
+            (EBonus21::SixCard, Fraction::from(2)),
+            (EBonus21::SevenPlusCard, Fraction::from(3)),
+            (EBonus21::Mixed678, Fraction::new(3u64, 2u64)),
+            (EBonus21::Suited678, Fraction::from(2)),
+            (EBonus21::Spades678, Fraction::from(3)),
+            (EBonus21::Mixed777, Fraction::new(3u64, 2u64)),
+            (EBonus21::Suited777, Fraction::from(2)),
+            (EBonus21::Spades777, Fraction::from(3)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EBonus21;
+    use crate::server::card::{ECard, ECardColor, ECardNumber};
+
+    fn card(color: ECardColor, value: ECardNumber) -> ECard {
+        ECard { color, value }
+    }
+
+    #[test]
+    fn test_from_cards() {
+        let s6 = card(ECardColor::Spades, ECardNumber::Six);
+        let s7 = card(ECardColor::Spades, ECardNumber::Seven);
+        let s8 = card(ECardColor::Spades, ECardNumber::Eight);
+        let h7 = card(ECardColor::Hearts, ECardNumber::Seven);
+        let h8 = card(ECardColor::Hearts, ECardNumber::Eight);
+        let h6 = card(ECardColor::Hearts, ECardNumber::Six);
+        let d2 = card(ECardColor::Diamonds, ECardNumber::Two);
+        let c3 = card(ECardColor::Clubs, ECardNumber::Three);
+
+        assert_eq!(EBonus21::from_cards(&[s8, s6, s7]), Some(EBonus21::Spades678));
+        assert_eq!(EBonus21::from_cards(&[h8, h6, h7]), Some(EBonus21::Suited678));
+        assert_eq!(EBonus21::from_cards(&[h8, s6, h7]), Some(EBonus21::Mixed678));
+        assert_eq!(EBonus21::from_cards(&[s7, s7, s7]), Some(EBonus21::Spades777));
+        assert_eq!(EBonus21::from_cards(&[h7, h7, h7]), Some(EBonus21::Suited777));
+        assert_eq!(EBonus21::from_cards(&[h7, s7, h7]), Some(EBonus21::Mixed777));
+        assert_eq!(EBonus21::from_cards(&[s8, s8, card(ECardColor::Spades, ECardNumber::Five)]), None);
+        assert_eq!(EBonus21::from_cards(&[d2, c3, h6, s8, d2]), Some(EBonus21::FiveCard));
+        assert_eq!(EBonus21::from_cards(&[d2, c3, h6, c3, d2, card(ECardColor::Clubs, ECardNumber::Five)]), Some(EBonus21::SixCard));
+        assert_eq!(EBonus21::from_cards(&[d2, d2, d2, c3, c3, c3, h6]), Some(EBonus21::SevenPlusCard));
+        assert_eq!(EBonus21::from_cards(&[h8, c3, h6, s7]), None);
+    }
+}
@@ -3,16 +3,17 @@ use rand::{
     distributions::{Distribution, Standard},
     Rng,
 };
+use serde::{Deserialize, Serialize};
 use std::ops::Add;
 use strum_macros::{Display, EnumIter};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ECard {
     pub color: ECardColor,
     pub value: ECardNumber,
 }
 
-#[derive(Debug, Copy, Clone, EnumIter, Hash, Display, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, EnumIter, Hash, Display, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ECardColor {
     Hearts,
     Diamonds,
@@ -20,7 +21,7 @@ pub enum ECardColor {
     Spades,
 }
 
-#[derive(Debug, Copy, Clone, EnumIter, Hash, Eq, PartialEq, Display)]
+#[derive(Debug, Copy, Clone, EnumIter, Hash, Eq, PartialEq, Display, Serialize, Deserialize)]
 pub enum ECardNumber {
     Ace = 0,
     Two = 1,
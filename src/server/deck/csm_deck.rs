@@ -0,0 +1,225 @@
+use crate::server::card::{ECard, ECardColor};
+use fraction::Fraction;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use strum::IntoEnumIterator;
+
+use super::super::card::{ECardNumber, ECardPoint};
+use super::super::deck::TDeck;
+use super::{new_probability_map_from_cards, ECardNum, EDeckSnapshot};
+
+/// 机器内至少保留的牌数 held_out_num超过牌数减去该值时按上限处理 保证机器内的牌足够发一局
+pub const CSM_MIN_MACHINE_CARDS: usize = 13;
+
+/// 连续洗牌机卡池
+/// 每次从机器内的牌中随机抽牌，本局的弃牌在结算后放回机器。
+/// 放回的弃牌先进入held_out队列，队列中超过held_out_num张的牌才重新进入机器，模拟发牌托盘和尚未洗入的牌。
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SCsmDeck {
+    // 机器内可以抽到的牌
+    pub machine: Vec<ECard>,
+    // 不在机器内 等待放回的牌
+    pub held_out: VecDeque<ECard>,
+    pub held_out_num: usize,
+    // 本局已抽出的牌
+    pub discards: Vec<ECard>,
+    // 快照中不保存 恢复时根据牌重新计算
+    #[serde(skip)]
+    pub number_map: HashMap<ECardNumber, usize>,
+    #[serde(skip)]
+    pub point_probability_map: HashMap<ECardPoint, Fraction>,
+    cards_num: usize,
+    seed: u64,
+    rng: ChaCha12Rng,
+}
+
+impl SCsmDeck {
+    pub fn new(num_of_deck: u8, held_out_num: usize, seed: u64) -> Self {
+        SCsmDeck::new_without(num_of_deck, &[], held_out_num, seed)
+    }
+
+    /// 移除部分牌面的牌库 如西班牙21点移除所有10
+    pub fn new_without(num_of_deck: u8, removed_cards: &[ECardNumber], held_out_num: usize, seed: u64) -> Self {
+        let mut cards = Vec::new();
+
+        for _ in 0..num_of_deck {
+            for value in ECardNumber::iter().filter(|value| !removed_cards.contains(value)) {
+                for color in ECardColor::iter() {
+                    cards.push(ECard { color, value });
+                }
+            }
+        }
+
+        let cards_num = cards.len();
+        let (number_map, point_probability_map) = new_probability_map_from_cards(&cards);
+
+        let mut deck = SCsmDeck {
+            machine: cards,
+            held_out: VecDeque::new(),
+            held_out_num: held_out_num.min(SCsmDeck::max_held_out_num(cards_num)),
+            discards: Vec::new(),
+            number_map,
+            point_probability_map,
+            cards_num,
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        };
+
+        deck.shuffle();
+
+        deck
+    }
+
+    /// held_out_num的上限 牌数不足CSM_MIN_MACHINE_CARDS时不留出牌
+    pub fn max_held_out_num(cards_num: usize) -> usize {
+        cards_num.saturating_sub(CSM_MIN_MACHINE_CARDS)
+    }
+
+    /// 从快照恢复时 限制held_out_num并重新计算map
+    pub(super) fn restore(&mut self) {
+        self.held_out_num = self.held_out_num.min(SCsmDeck::max_held_out_num(self.cards_num));
+        self.update_map();
+    }
+
+    /// 根据机器内的牌重新计算map
+    pub(super) fn update_map(&mut self) {
+        (self.number_map, self.point_probability_map) = new_probability_map_from_cards(&self.machine);
+    }
+
+    fn take(&mut self, index: usize) -> ECard {
+        let card = self.machine.swap_remove(index);
+        self.discards.push(card);
+        self.update_map();
+        card
+    }
+}
+
+impl TDeck for SCsmDeck {
+    fn draw(&mut self) -> Option<ECard> {
+        // 机器内的牌用尽时 先回收已抽出的牌
+        if self.machine.is_empty() {
+            self.recycle();
+        }
+        if self.machine.is_empty() {
+            return None;
+        }
+
+        let index = self.rng.gen_range(0..self.machine.len());
+        Some(self.take(index))
+    }
+
+    fn draw_specific(&mut self, card_num: ECardNumber) -> Option<ECardNumber> {
+        let index = self.machine.iter().position(|card| card.value == card_num)?;
+        Some(self.take(index).value)
+    }
+
+    /// 所有牌放回机器 重新选出held_out的牌
+    fn shuffle(&mut self) {
+        self.machine.append(&mut self.discards);
+        self.machine.extend(self.held_out.drain(..));
+        self.machine.shuffle(&mut self.rng);
+        let held_out = self.machine.split_off(self.machine.len() - self.held_out_num);
+        self.held_out = held_out.into();
+        self.update_map();
+    }
+
+    /// 弃牌放入held_out队列 队列中最早的牌放回机器
+    fn recycle(&mut self) {
+        self.held_out.extend(self.discards.drain(..));
+        while self.held_out.len() > self.held_out_num {
+            let card = self.held_out.pop_front().unwrap();
+            self.machine.push(card);
+        }
+        self.update_map();
+    }
+
+    fn needs_cut_card(&self) -> bool {
+        false
+    }
+
+    fn get_point_probability_map(&self) -> &HashMap<ECardPoint, Fraction> {
+        &self.point_probability_map
+    }
+
+    fn remain_cards_num(&self) -> ECardNum {
+        ECardNum::Some(self.machine.len())
+    }
+
+    fn cards_num(&self) -> ECardNum {
+        ECardNum::Some(self.cards_num)
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn snapshot(&self) -> EDeckSnapshot {
+        EDeckSnapshot::Csm(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::card::{ECardNumber, ECardPoint};
+    use crate::server::deck::{csm_deck::{SCsmDeck, CSM_MIN_MACHINE_CARDS}, ECardNum, TDeck};
+    use fraction::Fraction;
+
+    #[test]
+    fn test_draw_and_recycle() {
+        let mut deck = SCsmDeck::new(1, 10, 0);
+        assert_eq!(deck.cards_num(), ECardNum::Some(52));
+        assert_eq!(deck.remain_cards_num(), ECardNum::Some(42));
+
+        for _ in 0..5 {
+            deck.draw().unwrap();
+        }
+        assert_eq!(deck.remain_cards_num(), ECardNum::Some(37));
+        assert_eq!(deck.discards.len(), 5);
+
+        // 5张弃牌进入held_out队列 挤出最早的5张牌回到机器
+        deck.recycle();
+        assert_eq!(deck.remain_cards_num(), ECardNum::Some(42));
+        assert_eq!(deck.held_out.len(), 10);
+        assert!(deck.discards.is_empty());
+
+        deck.shuffle();
+        assert_eq!(deck.remain_cards_num(), ECardNum::Some(42));
+        assert_eq!(deck.held_out.len(), 10);
+    }
+
+    #[test]
+    fn test_held_out_num_capped() {
+        // 所有牌都留在机器外时无法发牌 机器内至少保留CSM_MIN_MACHINE_CARDS张牌
+        let mut deck = SCsmDeck::new(1, 52, 0);
+        assert_eq!(deck.held_out_num, 52 - CSM_MIN_MACHINE_CARDS);
+        assert_eq!(deck.remain_cards_num(), ECardNum::Some(CSM_MIN_MACHINE_CARDS));
+        for _ in 0..52 {
+            assert!(deck.draw().is_some());
+        }
+    }
+
+    #[test]
+    fn test_probability_map() {
+        let mut deck = SCsmDeck::new(1, 0, 0);
+        assert_eq!(
+            *deck.get_point_probability_map().get(&ECardPoint::Ace).unwrap(),
+            Fraction::new(1u64, 13u64)
+        );
+        for _ in 0..4 {
+            assert_eq!(deck.draw_specific(ECardNumber::Ace), Some(ECardNumber::Ace));
+        }
+        assert_eq!(deck.draw_specific(ECardNumber::Ace), None);
+        assert_eq!(*deck.get_point_probability_map().get(&ECardPoint::Ace).unwrap(), Fraction::from(0));
+
+        // 没有held_out时 弃牌立即回到机器
+        deck.recycle();
+        assert_eq!(deck.remain_cards_num(), ECardNum::Some(52));
+        assert_eq!(
+            *deck.get_point_probability_map().get(&ECardPoint::Ace).unwrap(),
+            Fraction::new(1u64, 13u64)
+        );
+    }
+}
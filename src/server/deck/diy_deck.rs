@@ -1,26 +1,38 @@
 use crate::server::card::ECard;
 use fraction::Fraction;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::super::card::{ECardNumber, ECardPoint};
 use super::super::deck::TDeck;
-use super::{new_probability_map_from_cards, new_probability_map_from_number_map, ECardNum};
+use super::{new_probability_map_from_cards, new_probability_map_from_number_map, ECardNum, EDeckSnapshot};
 
 /// 手动构建卡池
 /// 手动指定卡池顺序
 /// 有状态：当某张牌从牌库中抽出后，影响后续抽牌的概率。
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SDiyDeck {
     pub cards: Vec<ECard>,
+    // 快照中不保存 恢复时根据牌重新计算
+    #[serde(skip)]
     pub number_map: HashMap<ECardNumber, usize>,
+    #[serde(skip)]
     pub point_probability_map: HashMap<ECardPoint, Fraction>,
     // 指针 指向下一张抽到的牌
     pub draw_ptr: usize,
+    // 洗牌时不打乱牌序 只把指针移回开头 用于按记录回放
+    #[serde(default)]
+    pub fixed_order: bool,
+    seed: u64,
+    rng: ChaCha12Rng,
 }
 
 impl SDiyDeck {
-    pub fn from(cards: Vec<ECard>) -> Self {
+    /// seed只在牌库用尽或洗牌后生效 首轮按给定顺序抽牌
+    pub fn from(cards: Vec<ECard>, seed: u64) -> Self {
         let (number_map, point_probability_map) = new_probability_map_from_cards(&cards);
 
         SDiyDeck {
@@ -28,9 +40,26 @@ impl SDiyDeck {
             number_map,
             point_probability_map,
             draw_ptr: 0,
+            fixed_order: false,
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
         }
     }
 
+    /// 回放用的牌库 从draw_ptr开始按给定顺序抽牌 洗牌后从头开始 牌序始终不变
+    pub fn from_fixed_order(cards: Vec<ECard>, draw_ptr: usize, seed: u64) -> Self {
+        let mut deck = SDiyDeck::from(cards, seed);
+        deck.draw_ptr = draw_ptr.min(deck.cards.len());
+        deck.fixed_order = true;
+        deck.update_map();
+        deck
+    }
+
+    /// 根据剩余的牌重新计算map
+    pub(super) fn update_map(&mut self) {
+        (self.number_map, self.point_probability_map) = new_probability_map_from_cards(&self.cards[self.draw_ptr..]);
+    }
+
     fn inner_remain_cards_num(&self) -> usize {
         self.cards.len() - self.draw_ptr
     }
@@ -42,12 +71,7 @@ impl SDiyDeck {
 
 impl TDeck for SDiyDeck {
     fn draw(&mut self) -> Option<ECard> {
-        // 判断是否shuffle
-        // 手牌不足时需要shuffle
-        if self.inner_remain_cards_num() <= 0 {
-            self.shuffle();
-        }
-        
+        // 牌库用尽时返回None 不在牌局中途shuffle 桌上的牌不能回到牌库
         // 抽出一张卡
         let result = match self.cards.get(self.draw_ptr) {
             None => None,
@@ -91,8 +115,9 @@ impl TDeck for SDiyDeck {
     }
 
     fn shuffle(&mut self) {
-        let mut rng = thread_rng();
-        self.cards.shuffle(&mut rng);
+        if !self.fixed_order {
+            self.cards.shuffle(&mut self.rng);
+        }
         self.draw_ptr = 0;
         (self.number_map, self.point_probability_map) = new_probability_map_from_cards(&self.cards);
     }
@@ -108,6 +133,14 @@ impl TDeck for SDiyDeck {
     fn cards_num(&self) -> super::ECardNum {
         ECardNum::Some(self.inner_cards_num())
     }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn snapshot(&self) -> EDeckSnapshot {
+        EDeckSnapshot::Diy(self.clone())
+    }
 }
 
 #[cfg(test)]
@@ -161,7 +194,7 @@ mod tests {
                 value: ECardNumber::Nine,
             },
         ];
-        let mut deck = SDiyDeck::from(v);
+        let mut deck = SDiyDeck::from(v, 0);
         let cards_num = match deck.cards_num() {
             ECardNum::Some(num) => num,
             ECardNum::Infinite => 0,
@@ -199,7 +232,7 @@ mod tests {
                 value: ECardNumber::Six,
             },
         ];
-        let mut deck = SDiyDeck::from(v);
+        let mut deck = SDiyDeck::from(v, 0);
         println!("cards:{:?}", deck.cards);
         println!("ptr:{:?}", deck.draw_ptr);
         println!("draw:{:?}", deck.draw_specific(ECardNumber::Six));
@@ -263,7 +296,7 @@ mod tests {
                 value: ECardNumber::King,
             },
         ];
-        let deck = SDiyDeck::from(v);
+        let deck = SDiyDeck::from(v, 0);
         let point_map = deck.get_point_probability_map();
         let number_map = &deck.number_map;
         for point in ECardPoint::iter() {
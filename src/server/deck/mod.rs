@@ -1,9 +1,17 @@
+pub mod csm_deck;
 pub mod diy_deck;
 pub mod queue_deck;
 pub mod random_deck;
+pub mod shuffle;
 
 use super::card::{ECard, ECardNumber, ECardPoint};
+use csm_deck::SCsmDeck;
+use diy_deck::SDiyDeck;
 use fraction::Fraction;
+use queue_deck::SQueueDeck;
+use rand::{thread_rng, Rng};
+use random_deck::SRandomDeck;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use strum::IntoEnumIterator;
 
@@ -15,12 +23,64 @@ pub trait TDeck {
 
     fn shuffle(&mut self);
 
+    /// 本局结算后回收弃牌 只有连续洗牌机需要
+    fn recycle(&mut self) {}
+
+    /// 是否使用切牌卡 连续洗牌机没有切牌卡 也不需要洗牌时烧牌
+    fn needs_cut_card(&self) -> bool {
+        true
+    }
+
     /// 获取每种点数的抽取概率
     /// 使用分数Fraction作为结果，提高精度。
     fn get_point_probability_map(&self) -> &HashMap<ECardPoint, Fraction>;
 
     fn remain_cards_num(&self) -> ECardNum;
     fn cards_num(&self) -> ECardNum;
+
+    /// 创建卡池时使用的随机数种子 相同种子抽出相同的牌序
+    fn seed(&self) -> u64;
+
+    /// 保存卡池的完整状态 包括牌序、抽牌指针和随机数生成器
+    fn snapshot(&self) -> EDeckSnapshot;
+}
+
+/// 卡池快照 按卡池类型区分
+#[derive(Clone, Serialize, Deserialize)]
+pub enum EDeckSnapshot {
+    Random(SRandomDeck),
+    Queue(SQueueDeck),
+    Diy(SDiyDeck),
+    Csm(SCsmDeck),
+}
+
+impl EDeckSnapshot {
+    /// 从快照恢复卡池 概率map根据牌重新计算
+    pub fn into_deck(self) -> Box<dyn TDeck + Sync + Send> {
+        match self {
+            EDeckSnapshot::Random(mut deck) => {
+                deck.update_map();
+                Box::new(deck)
+            }
+            EDeckSnapshot::Queue(mut deck) => {
+                deck.update_map();
+                Box::new(deck)
+            }
+            EDeckSnapshot::Diy(mut deck) => {
+                deck.update_map();
+                Box::new(deck)
+            }
+            EDeckSnapshot::Csm(mut deck) => {
+                deck.restore();
+                Box::new(deck)
+            }
+        }
+    }
+}
+
+/// 生成新的随机数种子 未指定种子时使用
+pub fn new_seed() -> u64 {
+    thread_rng().gen()
 }
 
 #[derive(PartialEq, Eq, Debug)]
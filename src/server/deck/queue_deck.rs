@@ -1,30 +1,45 @@
 use crate::server::card::{ECard, ECardColor};
 use fraction::Fraction;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use strum::IntoEnumIterator;
 
 use super::super::card::{ECardNumber, ECardPoint};
 use super::super::deck::TDeck;
-use super::{new_probability_map_from_cards, new_probability_map_from_number_map, ECardNum};
+use super::shuffle::EShuffleProcedure;
+use super::{new_probability_map_from_cards, new_probability_map_from_number_map, ECardNum, EDeckSnapshot};
 
 /// 队列卡池
 /// 有状态：当某张牌从牌库中抽出后，影响后续抽牌的概率。
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SQueueDeck {
     pub cards: Vec<ECard>,
+    // 快照中不保存 恢复时根据牌重新计算
+    #[serde(skip)]
     pub number_map: HashMap<ECardNumber, usize>,
+    #[serde(skip)]
     pub point_probability_map: HashMap<ECardPoint, Fraction>,
     // 指针 指向下一张抽到的牌
     draw_ptr: usize,
+    // 洗牌方式 默认完全随机
+    pub shuffle_procedure: EShuffleProcedure,
+    seed: u64,
+    rng: ChaCha12Rng,
 }
 
 impl SQueueDeck {
-    pub fn new(num_of_deck: u8) -> Self {
+    pub fn new(num_of_deck: u8, seed: u64) -> Self {
+        SQueueDeck::new_without(num_of_deck, &[], seed)
+    }
+
+    /// 移除部分牌面的牌库 如西班牙21点移除所有10
+    pub fn new_without(num_of_deck: u8, removed_cards: &[ECardNumber], seed: u64) -> Self {
         let mut cards = Vec::new();
 
         for _ in 0..num_of_deck {
-            for value in ECardNumber::iter() {
+            for value in ECardNumber::iter().filter(|value| !removed_cards.contains(value)) {
                 for color in ECardColor::iter() {
                     cards.push(ECard { color, value });
                 }
@@ -38,6 +53,9 @@ impl SQueueDeck {
             number_map,
             point_probability_map,
             draw_ptr: 0,
+            shuffle_procedure: EShuffleProcedure::Uniform,
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
         };
 
         deck.shuffle();
@@ -45,6 +63,26 @@ impl SQueueDeck {
         deck
     }
 
+    /// 使用指定的洗牌方式 从下一次洗牌开始生效
+    pub fn with_shuffle_procedure(mut self, shuffle_procedure: EShuffleProcedure) -> Self {
+        self.shuffle_procedure = shuffle_procedure;
+        self
+    }
+
+    /// 剩余未发出的每张牌(点数和花色)的数量
+    pub fn get_card_map(&self) -> HashMap<ECard, usize> {
+        let mut card_map = HashMap::new();
+        for card in self.cards[self.draw_ptr..].iter() {
+            *card_map.entry(*card).or_insert(0) += 1;
+        }
+        card_map
+    }
+
+    /// 根据剩余的牌重新计算map
+    pub(super) fn update_map(&mut self) {
+        (self.number_map, self.point_probability_map) = new_probability_map_from_cards(&self.cards[self.draw_ptr..]);
+    }
+
     fn inner_remain_cards_num(&self) -> usize {
         self.cards.len() - self.draw_ptr
     }
@@ -56,13 +94,8 @@ impl SQueueDeck {
 
 impl TDeck for SQueueDeck {
     fn draw(&mut self) -> Option<ECard> {
-        // 判断是否shuffle
-        // 手牌不足时需要shuffle
-        if self.inner_remain_cards_num() <= 0 {
-            self.shuffle();
-        }
-
-        // 抽出一张卡
+        // 牌库用尽时返回None 不在牌局中途shuffle 桌上的牌不能回到牌库
+        // 规则校验保证切牌卡后面留有一局所需的牌 正常情况下不会用尽
         let result = match self.cards.get(self.draw_ptr) {
             None => None,
             Some(x) => {
@@ -105,8 +138,7 @@ impl TDeck for SQueueDeck {
     }
 
     fn shuffle(&mut self) {
-        let mut rng = thread_rng();
-        self.cards.shuffle(&mut rng);
+        self.shuffle_procedure.apply(&mut self.cards, self.draw_ptr, &mut self.rng);
         self.draw_ptr = 0;
         (self.number_map, self.point_probability_map) = new_probability_map_from_cards(&self.cards);
     }
@@ -122,15 +154,25 @@ impl TDeck for SQueueDeck {
     fn cards_num(&self) -> super::ECardNum {
         ECardNum::Some(self.inner_cards_num())
     }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn snapshot(&self) -> EDeckSnapshot {
+        EDeckSnapshot::Queue(self.clone())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::server::deck::{queue_deck::SQueueDeck, TDeck};
+    use crate::server::card::{ECardNumber, ECardPoint};
+    use crate::server::deck::{queue_deck::SQueueDeck, ECardNum, TDeck};
+    use fraction::Fraction;
 
     #[tokio::test]
     pub async fn test_draw_and_remain() {
-        let mut deck = SQueueDeck::new(2);
+        let mut deck = SQueueDeck::new(2, 0);
         let draw_num = 10;
         for _ in 0..draw_num {
             println!("card_num:{:?}", deck.cards_num());
@@ -144,4 +186,40 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_new_without() {
+        let deck = SQueueDeck::new_without(6, &[ECardNumber::Ten], 0);
+        assert_eq!(deck.cards_num(), ECardNum::Some(6 * 48));
+        assert_eq!(deck.number_map.get(&ECardNumber::Ten), None);
+        assert_eq!(deck.number_map.get(&ECardNumber::Jack), Some(&24));
+        assert_eq!(
+            *deck.get_point_probability_map().get(&ECardPoint::Ten).unwrap(),
+            Fraction::new(1u64, 4u64)
+        );
+    }
+
+    #[test]
+    fn test_seed() {
+        let draw = |seed: u64| {
+            let mut deck = SQueueDeck::new(1, seed);
+            assert_eq!(deck.seed(), seed);
+            (0..52).map(|_| deck.draw().unwrap()).collect::<Vec<_>>()
+        };
+        assert_eq!(draw(1), draw(1));
+        assert_ne!(draw(1), draw(2));
+    }
+
+    #[test]
+    fn test_draw_exhausted() {
+        // 牌库用尽时不会自动shuffle
+        let mut deck = SQueueDeck::new(1, 0);
+        for _ in 0..52 {
+            assert!(deck.draw().is_some());
+        }
+        assert_eq!(deck.draw(), None);
+        assert_eq!(deck.remain_cards_num(), ECardNum::Some(0));
+        deck.shuffle();
+        assert!(deck.draw().is_some());
+    }
 }
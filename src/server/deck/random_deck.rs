@@ -1,22 +1,29 @@
 use crate::server::card::{ECard, ECardColor};
 use fraction::Fraction;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::super::card::{ECardNumber, ECardPoint};
 use super::super::deck::TDeck;
-use super::ECardNum;
+use super::{new_probability_map_from_cards, ECardNum, EDeckSnapshot};
 
 /// 随机卡池
 /// 根据卡牌的初始占比，按概率抽牌。
 /// 无状态：当某张牌从牌库中抽出后，不影响后续抽牌的概率。
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SRandomDeck {
     pub cards: Vec<ECard>,
+    // 快照中不保存 恢复时根据牌重新计算
+    #[serde(skip)]
     pub point_probability_map: HashMap<ECardPoint, Fraction>,
+    seed: u64,
+    rng: ChaCha12Rng,
 }
 
 impl SRandomDeck {
-    pub fn new() -> Self {
+    pub fn new(seed: u64) -> Self {
         let card_num_vec = vec![
             ECardNumber::Ace,
             ECardNumber::Two,
@@ -63,15 +70,30 @@ impl SRandomDeck {
         SRandomDeck {
             cards,
             point_probability_map,
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
         }
     }
+
+    /// 根据卡牌重新计算map
+    pub(super) fn update_map(&mut self) {
+        self.point_probability_map = new_probability_map_from_cards(&self.cards).1;
+    }
+
+    /// 移除部分牌面的随机卡池 如西班牙21点移除所有10
+    pub fn new_without(removed_cards: &[ECardNumber], seed: u64) -> Self {
+        let mut deck = SRandomDeck::new(seed);
+        deck.cards.retain(|card| !removed_cards.contains(&card.value));
+        deck.point_probability_map = new_probability_map_from_cards(&deck.cards).1;
+        deck
+    }
 }
 
 impl TDeck for SRandomDeck {
     fn draw(&mut self) -> Option<ECard> {
         match self
             .cards
-            .get(rand::thread_rng().gen_range(0..self.cards.len()))
+            .get(self.rng.gen_range(0..self.cards.len()))
         {
             None => None,
             Some(x) => Some(*x),
@@ -97,6 +119,14 @@ impl TDeck for SRandomDeck {
     fn cards_num(&self) -> ECardNum {
         ECardNum::Infinite
     }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn snapshot(&self) -> EDeckSnapshot {
+        EDeckSnapshot::Random(self.clone())
+    }
 }
 
 #[cfg(test)]
@@ -107,14 +137,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_draw() {
-        let mut deck = SRandomDeck::new();
+        let mut deck = SRandomDeck::new(0);
         for _ in 0..10 {
             println!("{:?}", deck.draw())
         }
     }
     #[tokio::test]
     async fn test_draw_specific() {
-        let mut deck = SRandomDeck::new();
+        let mut deck = SRandomDeck::new(0);
         println!("{:?}", deck.draw_specific(ECardNumber::Eight));
         println!("{:?}", deck.draw_specific(ECardNumber::Ace));
         println!("{:?}", deck.draw_specific(ECardNumber::Jack));
@@ -123,7 +153,7 @@ mod tests {
     }
     #[tokio::test]
     async fn test_get_probability_map() {
-        let deck = SRandomDeck::new();
+        let deck = SRandomDeck::new(0);
         let map = deck.get_point_probability_map();
         println!("{:?}", map);
         println!("{:?}", map.get(&ECardPoint::Ace));
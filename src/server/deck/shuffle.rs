@@ -0,0 +1,248 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// 洗牌方式
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum EShuffleProcedure {
+    // 完全均匀的随机洗牌
+    #[default]
+    Uniform,
+    // 模拟荷官手工洗牌
+    Dealer(SDealerShuffle),
+}
+
+impl EShuffleProcedure {
+    /// 洗牌 dealt_num为上一靴已发出的牌数 位于cards的开头
+    pub fn apply<T, R: Rng>(&self, cards: &mut Vec<T>, dealt_num: usize, rng: &mut R) {
+        match self {
+            EShuffleProcedure::Uniform => cards.shuffle(rng),
+            EShuffleProcedure::Dealer(shuffle) => shuffle.apply(cards, dealt_num, rng),
+        }
+    }
+}
+
+/// 荷官手工洗牌 依次执行plug、riffle和strip cut
+/// 反序列化时经过SDealerShuffle::new校验
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "SDealerShuffleParams")]
+pub struct SDealerShuffle {
+    // 是否把未发出的牌插入弃牌堆中 否则放在弃牌堆上面
+    pub plug: bool,
+    pub riffles: usize,
+    // riffle时连续从同一半落牌的概率 越大牌越成块
+    pub clumping: f64,
+    pub strip_cuts: usize,
+}
+
+impl Default for SDealerShuffle {
+    fn default() -> Self {
+        SDealerShuffle {
+            plug: true,
+            riffles: 2,
+            clumping: 0.3,
+            strip_cuts: 1,
+        }
+    }
+}
+
+/// 未经校验的荷官洗牌参数 用于反序列化
+#[derive(Deserialize)]
+struct SDealerShuffleParams {
+    plug: bool,
+    riffles: usize,
+    clumping: f64,
+    strip_cuts: usize,
+}
+
+impl TryFrom<SDealerShuffleParams> for SDealerShuffle {
+    type Error = EDealerShuffleError;
+
+    fn try_from(params: SDealerShuffleParams) -> Result<Self, Self::Error> {
+        SDealerShuffle::new(params.plug, params.riffles, params.clumping, params.strip_cuts)
+    }
+}
+
+/// 荷官洗牌参数的校验错误
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EDealerShuffleError {
+    // clumping不是0到1之间的数
+    InvalidClumping(f64),
+    // 至少需要riffle一次
+    NoRiffle,
+}
+
+impl fmt::Display for EDealerShuffleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EDealerShuffleError::InvalidClumping(clumping) => {
+                write!(f, "clumping must be between 0 and 1, got {clumping}")
+            }
+            EDealerShuffleError::NoRiffle => write!(f, "riffles must be at least 1"),
+        }
+    }
+}
+
+impl SDealerShuffle {
+    /// 校验参数并创建 clumping需要在0到1之间 riffles至少为1
+    pub fn new(
+        plug: bool,
+        riffles: usize,
+        clumping: f64,
+        strip_cuts: usize,
+    ) -> Result<Self, EDealerShuffleError> {
+        if !(0.0..=1.0).contains(&clumping) {
+            return Err(EDealerShuffleError::InvalidClumping(clumping));
+        }
+        if riffles == 0 {
+            return Err(EDealerShuffleError::NoRiffle);
+        }
+        Ok(SDealerShuffle {
+            plug,
+            riffles,
+            clumping,
+            strip_cuts,
+        })
+    }
+
+    pub fn apply<T, R: Rng>(&self, cards: &mut Vec<T>, dealt_num: usize, rng: &mut R) {
+        let dealt_num = dealt_num.min(cards.len());
+        // 弃牌堆在下 未发出的牌放在上面或插入弃牌堆
+        let mut discards: Vec<T> = cards.drain(..dealt_num).collect();
+        let position = match self.plug {
+            true => rng.gen_range(0..=discards.len()),
+            false => discards.len(),
+        };
+        let tail = discards.split_off(position);
+        discards.append(cards);
+        discards.extend(tail);
+        *cards = discards;
+
+        for _ in 0..self.riffles {
+            riffle(cards, self.clumping, rng);
+        }
+        for _ in 0..self.strip_cuts {
+            strip_cut(cards, rng);
+        }
+    }
+}
+
+/// 将牌分成两半后交错落牌
+/// 切牌位置在中间附近 每次从剩余牌数较多的一半落牌的概率更大(GSR模型)
+/// clumping为继续从上一次的一半落牌的概率 超出0到1时按边界处理 NaN按0处理
+pub fn riffle<T, R: Rng>(cards: &mut Vec<T>, clumping: f64, rng: &mut R) {
+    let clumping = match clumping.is_nan() {
+        true => 0.0,
+        false => clumping.clamp(0.0, 1.0),
+    };
+    let len = cards.len();
+    if len < 2 {
+        return;
+    }
+    let cut = (0..len).filter(|_| rng.gen_bool(0.5)).count();
+    let right: Vec<T> = cards.split_off(cut);
+    let mut left = std::mem::take(cards).into_iter();
+    let mut right = right.into_iter();
+    let mut left_num = cut;
+    let mut right_num = len - cut;
+    let mut last_left = rng.gen_bool(0.5);
+    while left_num + right_num > 0 {
+        let from_left = match (left_num, right_num) {
+            (0, _) => false,
+            (_, 0) => true,
+            _ => match rng.gen_bool(clumping) {
+                true => last_left,
+                false => rng.gen_range(0..left_num + right_num) < left_num,
+            },
+        };
+        match from_left {
+            true => {
+                cards.push(left.next().unwrap());
+                left_num -= 1;
+            }
+            false => {
+                cards.push(right.next().unwrap());
+                right_num -= 1;
+            }
+        }
+        last_left = from_left;
+    }
+}
+
+/// 从上面逐次拿出3到10张牌的小块 依次叠放 块内顺序不变 块之间顺序相反
+pub fn strip_cut<T, R: Rng>(cards: &mut Vec<T>, rng: &mut R) {
+    let mut stripped = Vec::<T>::with_capacity(cards.len());
+    while !cards.is_empty() {
+        let size = rng.gen_range(3..=10).min(cards.len());
+        let mut packet: Vec<T> = cards.drain(..size).collect();
+        packet.append(&mut stripped);
+        stripped = packet;
+    }
+    *cards = stripped;
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{riffle, strip_cut, EDealerShuffleError, SDealerShuffle};
+
+    #[test]
+    fn test_shuffle_keep_cards() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut cards: Vec<usize> = (0..52).collect();
+        riffle(&mut cards, 0.3, &mut rng);
+        strip_cut(&mut cards, &mut rng);
+        SDealerShuffle::default().apply(&mut cards, 30, &mut rng);
+        let mut sorted = cards.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..52).collect::<Vec<_>>());
+        assert_ne!(cards, sorted);
+    }
+
+    #[test]
+    fn test_riffle_clumping() {
+        // clumping为1时只会从一半连续落牌 相当于切牌
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut cards: Vec<usize> = (0..52).collect();
+        riffle(&mut cards, 1.0, &mut rng);
+        let cut = cards.iter().position(|&x| x == 0).unwrap();
+        assert!(cards.iter().cycle().skip(cut).take(52).copied().eq(0..52));
+    }
+
+    #[test]
+    fn test_dealer_shuffle_validate() {
+        assert_eq!(SDealerShuffle::new(true, 2, 0.3, 1), Ok(SDealerShuffle::default()));
+        assert_eq!(
+            SDealerShuffle::new(true, 2, 1.5, 1),
+            Err(EDealerShuffleError::InvalidClumping(1.5))
+        );
+        assert!(matches!(
+            SDealerShuffle::new(true, 2, f64::NAN, 1),
+            Err(EDealerShuffleError::InvalidClumping(_))
+        ));
+        assert_eq!(SDealerShuffle::new(true, 0, 0.3, 1), Err(EDealerShuffleError::NoRiffle));
+
+        // 反序列化时同样校验
+        let json = serde_json::to_string(&SDealerShuffle::default()).unwrap();
+        assert_eq!(serde_json::from_str::<SDealerShuffle>(&json).unwrap(), SDealerShuffle::default());
+        assert!(serde_json::from_str::<SDealerShuffle>(
+            r#"{"plug": true, "riffles": 2, "clumping": -1.0, "strip_cuts": 1}"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<SDealerShuffle>(
+            r#"{"plug": true, "riffles": 0, "clumping": 0.3, "strip_cuts": 1}"#
+        )
+        .is_err());
+
+        // 直接构造的非法参数在riffle时按边界处理 不会panic
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut cards: Vec<usize> = (0..52).collect();
+        riffle(&mut cards, f64::NAN, &mut rng);
+        riffle(&mut cards, 2.0, &mut rng);
+        cards.sort();
+        assert!(cards.into_iter().eq(0..52));
+    }
+}
@@ -1,8 +1,9 @@
 use super::super::card::ECard;
 use super::super::value::EValue;
 use super::hand::SHand;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SDealerHand {
     pub hand: SHand,
 }
@@ -32,6 +33,11 @@ impl SDealerHand {
     pub fn is_blackjack(&self) -> bool {
         self.hand.value().to_point() == 21 && self.hand.cards.len() == 2
     }
+
+    /// 是否以22点bust
+    pub fn is_bust_22(&self) -> bool {
+        self.value() == EValue::Bust && self.hand.hard_point() == 22
+    }
 }
 
 #[cfg(test)]
@@ -1,9 +1,10 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
 use super::super::card::ECard;
 use super::super::value::EValue;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SHand {
     pub cards: Vec<ECard>,
     pub value: EValue,
@@ -47,6 +48,17 @@ impl SHand {
     pub fn value(&self) -> EValue {
         self.value
     }
+
+    /// 所有A按1点计算的总点数 bust时可以得到超出21点的具体点数
+    pub fn hard_point(&self) -> u8 {
+        self.cards
+            .iter()
+            .map(|card| match Into::<EValue>::into(*card).to_point() {
+                11 => 1,
+                x => x,
+            })
+            .sum()
+    }
 }
 
 impl Display for SHand {
@@ -1,28 +1,75 @@
-use super::super::card::ECard;
+use super::super::bonus::EBonus21;
+use super::super::card::{ECard, ECardNumber};
+use fraction::Fraction;
+use super::super::rule::SGameRule;
+use super::super::side_bet::ESideBet;
 use super::super::value::EValue;
 use super::hand::SHand;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SPlayerHand {
     pub hand: SHand,
     // 本轮玩家下注
     pub betting_box: usize,
+    // double down追加的筹码 包含在betting_box中
+    pub double_down_amount: usize,
+    // 庄家出资的免费下注(Free Bet) 不包含在betting_box中
+    // 获胜时按1:1赔付 平局或失败时不退还
+    pub free_bet: usize,
+    // 是否免费double down
+    pub is_free_double: bool,
     // 保险
     pub insurance: usize,
+    // 边注 发牌后立即结算 split产生的手牌不带边注
+    pub side_bets: Vec<(ESideBet, usize)>,
+    // 是否投降
+    pub is_surrender: bool,
+    // 是否选择even money
+    pub is_even_money: bool,
+    // 是否由split产生
+    pub is_split: bool,
+    // 是否在Blackjack Switch中交换过第二张牌 交换后的A+10不算blackjack
+    #[serde(default)]
+    pub is_switched: bool,
+    // 所属的座位
+    pub seat: usize,
+    // 所属的下注spot
+    pub spot: usize,
 }
 impl SPlayerHand {
     pub fn new() -> Self {
         SPlayerHand {
             hand: SHand::new(),
             betting_box: 0,
+            double_down_amount: 0,
+            free_bet: 0,
+            is_free_double: false,
             insurance: 0,
+            side_bets: Vec::new(),
+            is_surrender: false,
+            is_even_money: false,
+            is_split: false,
+            is_switched: false,
+            seat: 0,
+            spot: 0,
         }
     }
 
     pub fn reset(&mut self) {
         self.hand.reset();
         self.betting_box = 0;
+        self.double_down_amount = 0;
+        self.free_bet = 0;
+        self.is_free_double = false;
         self.insurance = 0;
+        self.side_bets.clear();
+        self.is_surrender = false;
+        self.is_even_money = false;
+        self.is_split = false;
+        self.is_switched = false;
+        self.seat = 0;
+        self.spot = 0;
     }
 
     pub fn value(&self) -> EValue {
@@ -49,29 +96,148 @@ impl SPlayerHand {
         self.insurance = bet;
     }
 
-    pub fn double_down(&mut self, card: ECard) {
-        self.betting_box *= 2;
+    pub fn side_bet(&mut self, side_bet: ESideBet, bet: usize) {
+        self.side_bets.push((side_bet, bet));
+    }
+
+    /// double down 追加amount筹码并拿一张牌
+    pub fn double_down(&mut self, amount: usize, card: ECard) {
+        self.betting_box += amount;
+        self.double_down_amount += amount;
+        self.draw(card);
+    }
+
+    /// 免费double down 庄家出资追加与当前下注相同的免费下注并拿一张牌
+    pub fn free_double_down(&mut self, card: ECard) {
+        self.free_bet += self.get_wager();
+        self.is_free_double = true;
         self.draw(card);
     }
 
+    /// 是否已经double down(包括免费double)
+    pub fn is_double_down(&self) -> bool {
+        self.double_down_amount > 0 || self.is_free_double
+    }
+
+    /// 投降 结算时退还一半下注
+    pub fn surrender(&mut self) {
+        self.is_surrender = true;
+    }
+
+    pub fn is_surrender(&self) -> bool {
+        self.is_surrender
+    }
+
+    /// double down之后救援(rescue) 结算时只退还double追加的筹码
+    pub fn rescue(&mut self) {
+        self.is_surrender = true;
+    }
+
+    /// 投降只能在前两张牌时进行 double之后的投降即为救援
+    pub fn is_rescue(&self) -> bool {
+        self.is_surrender && self.double_down_amount > 0
+    }
+
+    /// even money 结算时按1:1赔付 不受庄家blackjack影响
+    pub fn even_money(&mut self) {
+        self.is_even_money = true;
+    }
+
+    pub fn is_even_money(&self) -> bool {
+        self.is_even_money
+    }
+
     pub fn get_bet(&self) -> usize {
         self.betting_box
     }
 
+    /// 手牌的全部下注 包括庄家出资的免费下注
+    pub fn get_wager(&self) -> usize {
+        self.betting_box + self.free_bet
+    }
+
+    /// 不包括double down追加筹码的原始下注
+    pub fn get_original_bet(&self) -> usize {
+        self.betting_box - self.double_down_amount
+    }
+
     pub fn split(&mut self) -> SPlayerHand {
         let card = self.hand.cards.pop().unwrap();
+        // 重新计算剩余手牌的value
+        let first_card = self.hand.cards.pop().unwrap();
+        self.hand.reset();
+        self.hand.draw(first_card);
+        self.is_split = true;
         let mut hand = SPlayerHand::new();
         hand.draw(card);
+        hand.is_split = true;
+        hand.seat = self.seat;
+        hand.spot = self.spot;
         hand
     }
 
+    /// Blackjack Switch 与另一手牌交换第二张牌 两手牌的value重新计算
+    pub fn switch(&mut self, other: &mut SPlayerHand) {
+        let cards = [self.hand.cards[0], other.hand.cards[1]];
+        let other_cards = [other.hand.cards[0], self.hand.cards[1]];
+        self.hand.reset();
+        other.hand.reset();
+        for (card, other_card) in cards.into_iter().zip(other_cards) {
+            self.hand.draw(card);
+            other.hand.draw(other_card);
+        }
+        self.is_switched = true;
+        other.is_switched = true;
+    }
+
     pub fn is_bust(&self) -> bool {
         self.value() == EValue::Bust
     }
 
-    pub fn should_split(&self) -> bool {
+    /// 手牌达到规则要求的张数且未bust 即Charlie 自动获胜
+    pub fn is_charlie(&self, rule: &SGameRule) -> bool {
+        !self.is_bust() && rule.check_charlie(self.hand.cards.len())
+    }
+
+    /// 判断当前手牌是否允许split
+    /// hands_num 当前player的手牌数量
+    pub fn should_split(&self, rule: &SGameRule, hands_num: usize) -> bool {
         let cards = &self.hand.cards;
-        cards.len() == 2 && cards.get(0).unwrap().value == cards.get(1).unwrap().value
+        if cards.len() != 2 || cards[0].value != cards[1].value {
+            return false;
+        }
+        if hands_num >= rule.split_max_hands {
+            return false;
+        }
+        // 再次split A
+        if self.is_split_ace() && !rule.resplit_aces {
+            return false;
+        }
+        true
+    }
+
+    /// 判断当前手牌是否允许double down
+    pub fn can_double_down(&self, rule: &SGameRule) -> bool {
+        self.hand.cards.len() == 2
+            && (!self.is_split || rule.double_after_split)
+            && rule.check_double_down(self.value())
+    }
+
+    /// 判断当前手牌是否由split A产生
+    pub fn is_split_ace(&self) -> bool {
+        self.is_split && self.hand.cards.first().unwrap().value == ECardNumber::Ace
+    }
+
+    /// 21点奖金的赔率 不符合奖金条件或规则未开放该奖金时为None
+    /// blackjack不支付奖金 double之后根据规则决定
+    pub fn get_bonus_21_pay(&self, rule: &SGameRule) -> Option<Fraction> {
+        if self.point() != 21 || self.is_blackjack() {
+            return None;
+        }
+        if self.is_double_down() && !rule.bonus_21_after_double {
+            return None;
+        }
+        EBonus21::from_cards(&self.hand.cards).and_then(|bonus| rule.get_bonus_21_pay(bonus))
     }
 
     pub fn win(&mut self, value: usize) {
@@ -86,12 +252,13 @@ impl SPlayerHand {
     /// 需要同时满足以下条件：
     /// 1.手牌value为soft 21
     /// 2.手牌数量为2张
+    /// 3.手牌不是由split产生
+    /// 4.手牌没有在Blackjack Switch中交换过
     pub fn is_blackjack(&self) -> bool {
-        if self.hand.value == EValue::S21 && self.hand.cards.len() == 2 {
-            true
-        } else {
-            false
-        }
+        self.hand.value == EValue::S21
+            && self.hand.cards.len() == 2
+            && !self.is_split
+            && !self.is_switched
     }
 }
 
@@ -100,7 +267,17 @@ impl From<SHand> for SPlayerHand {
         SPlayerHand {
             hand: value,
             betting_box: 0,
+            double_down_amount: 0,
+            free_bet: 0,
+            is_free_double: false,
             insurance: 0,
+            side_bets: Vec::new(),
+            is_surrender: false,
+            is_even_money: false,
+            is_split: false,
+            is_switched: false,
+            seat: 0,
+            spot: 0,
         }
     }
 }
@@ -0,0 +1,267 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use super::card::ECard;
+use super::deck::ECardNum;
+use super::player::EPlayerAction;
+use super::rule::SGameRule;
+use super::seat::SSeatSettlement;
+use super::table::{EPlayerActionError, ETableOutputEvent, ETableState, STable};
+
+/// 牌局记录格式的版本 格式不兼容时递增
+pub const HAND_HISTORY_VERSION: u32 = 2;
+
+/// 一局的完整记录 对应JSONL文件中的一行
+/// 一局从上一局结算后的第一个操作开始 到产生结算事件(GameOver)的操作结束 结算后的洗牌记录在该操作中
+/// 格式示例(实际写入时为一行):
+/// {"version":2,"round":1,"seed":42,"rule":{..规则文件格式..},
+///  "shoe":{"cards_num":52,"remain_cards_num":52,"cut_card_remain":10},"player_chips":[10000],"rng":{..},
+///  "steps":[{"state":"PlayerBet","action":{"Bet":2},"result":{"Ok":[{"InitGameWithCards":{..}}, ..]}}, ..],
+///  "dealer_cards":[{"color":"Hearts","value":"Ten"}, ..],"player_cards":[[..], ..],
+///  "settlement":[{"player_chips":10002,"bet_chips":2,"win_chips":4,..}]}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SHandHistory {
+    pub version: u32,
+    // 局数 从1开始 每个recorder单独计数
+    pub round: usize,
+    pub seed: u64,
+    pub rule: SGameRule,
+    // 本局开始时牌库的位置
+    pub shoe: SShoePosition,
+    // 本局开始时各座位的筹码
+    pub player_chips: Vec<usize>,
+    // 本局开始时牌桌的随机数状态 回放时恢复 使切牌卡位置相同
+    pub rng: ChaCha12Rng,
+    pub steps: Vec<SHandHistoryStep>,
+    // 庄家和每手牌的最终手牌
+    pub dealer_cards: Vec<ECard>,
+    pub player_cards: Vec<Vec<ECard>>,
+    // 各座位的结算结果
+    pub settlement: Vec<SSeatSettlement>,
+}
+
+/// 牌库位置 无限牌库时牌数为None
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SShoePosition {
+    pub cards_num: Option<usize>,
+    pub remain_cards_num: Option<usize>,
+    pub cut_card_remain: usize,
+}
+
+/// 一次操作 state为执行操作前的牌桌状态 result为操作依次产生的所有事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SHandHistoryStep {
+    pub state: ETableState,
+    pub action: EPlayerAction,
+    pub result: Result<Vec<ETableOutputEvent>, EPlayerActionError>,
+}
+
+#[derive(Debug)]
+pub enum EHandHistoryError {
+    // 读写文件失败
+    IoError(std::io::Error),
+    // 第line行(从1开始)格式错误
+    ParseError { line: usize, error: String },
+}
+
+impl fmt::Display for EHandHistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EHandHistoryError::IoError(e) => write!(f, "failed to access hand history: {e}"),
+            EHandHistoryError::ParseError { line, error } => {
+                write!(f, "failed to parse hand history at line {line}: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EHandHistoryError {}
+
+fn to_option(num: ECardNum) -> Option<usize> {
+    match num {
+        ECardNum::Some(num) => Some(num),
+        ECardNum::Infinite => None,
+    }
+}
+
+/// 牌局记录器 每局结算时追加写入一行
+pub struct SHandHistoryRecorder {
+    writer: Box<dyn Write + Sync + Send>,
+    round: usize,
+    current: Option<SHandHistory>,
+}
+
+impl SHandHistoryRecorder {
+    pub fn new(writer: Box<dyn Write + Sync + Send>) -> Self {
+        SHandHistoryRecorder {
+            writer,
+            round: 0,
+            current: None,
+        }
+    }
+
+    /// 以追加方式打开文件 文件不存在时创建
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, EHandHistoryError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(EHandHistoryError::IoError)?;
+        Ok(SHandHistoryRecorder::new(Box::new(file)))
+    }
+
+    /// 操作执行之前调用 本局的第一个操作时记录规则和牌库位置
+    pub fn before_action(&mut self, table: &STable) {
+        if self.current.is_some() {
+            return;
+        }
+        self.round += 1;
+        self.current = Some(SHandHistory {
+            version: HAND_HISTORY_VERSION,
+            round: self.round,
+            seed: table.seed,
+            rule: table.rule.clone(),
+            shoe: SShoePosition {
+                cards_num: to_option(table.deck.cards_num()),
+                remain_cards_num: to_option(table.deck.remain_cards_num()),
+                cut_card_remain: table.cut_card_remain,
+            },
+            player_chips: table.seats.iter().map(|seat| seat.player_chips).collect(),
+            rng: table.rng.clone(),
+            steps: Vec::new(),
+            dealer_cards: Vec::new(),
+            player_cards: Vec::new(),
+            settlement: Vec::new(),
+        });
+    }
+
+    /// 操作执行之后调用 记录操作和结果 结算时写入本局的记录
+    pub fn after_action(
+        &mut self,
+        table: &STable,
+        state: ETableState,
+        action: EPlayerAction,
+        result: &Result<Vec<ETableOutputEvent>, EPlayerActionError>,
+    ) -> std::io::Result<()> {
+        let Some(history) = &mut self.current else {
+            return Ok(());
+        };
+        history.steps.push(SHandHistoryStep {
+            state,
+            action,
+            result: result.clone(),
+        });
+        let game_over = result.iter().flatten().find_map(|event| match event {
+            ETableOutputEvent::GameOver {
+                seats,
+                hands,
+                dealer_cards,
+                ..
+            } => Some((seats, hands, dealer_cards)),
+            _ => None,
+        });
+        match game_over {
+            Some((seats, hands, dealer_cards)) => {
+                // 结算后手牌已经重置 从结算事件中获取最终手牌
+                history.settlement = seats.clone();
+                history.dealer_cards = dealer_cards.clone();
+                history.player_cards = hands.iter().map(|hand| hand.cards.clone()).collect();
+                let history = self.current.take().unwrap();
+                let line = serde_json::to_string(&history)?;
+                writeln!(self.writer, "{line}")?;
+                self.writer.flush()
+            }
+            None => {
+                history.dealer_cards = table.dealer_hand.hand.cards.clone();
+                history.player_cards = table.player_hands.iter().map(|hand| hand.hand.cards.clone()).collect();
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 读取所有牌局记录 忽略空行
+pub fn read<R: BufRead>(reader: R) -> Result<Vec<SHandHistory>, EHandHistoryError> {
+    let mut histories = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(EHandHistoryError::IoError)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let history = serde_json::from_str(&line).map_err(|e| EHandHistoryError::ParseError {
+            line: index + 1,
+            error: e.to_string(),
+        })?;
+        histories.push(history);
+    }
+    Ok(histories)
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<SHandHistory>, EHandHistoryError> {
+    let file = std::fs::File::open(path).map_err(EHandHistoryError::IoError)?;
+    read(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::player::static_strategy_player::SStaticStrategyPlayer;
+    use super::super::player::EPlayerAction;
+    use super::super::rule::SGameRule;
+    use super::super::table::{ETableOutputEvent, STable};
+    use super::{load, read, EHandHistoryError, SHandHistoryRecorder};
+
+    #[test]
+    fn test_record_and_load() {
+        let path = std::env::temp_dir().join(format!("hand_history_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let player = SStaticStrategyPlayer::new();
+        let mut table = STable::new_queue_deck(SGameRule::default(), 3);
+        table.buy_chips(10000);
+        table.history_recorder = Some(SHandHistoryRecorder::open(&path).unwrap());
+        let mut settlements = Vec::new();
+        while settlements.len() < 5 {
+            for event in table.receive_player_action(player.action(&table)).unwrap() {
+                if let ETableOutputEvent::GameOver { seats, .. } = event {
+                    settlements.push(seats);
+                }
+            }
+        }
+        // 本局未结算的操作不写入
+        table.receive_player_action(player.action(&table)).unwrap();
+
+        let histories = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(histories.len(), 5);
+        for (index, history) in histories.iter().enumerate() {
+            assert_eq!(history.round, index + 1);
+            assert_eq!(history.seed, 3);
+            assert_eq!(history.settlement, settlements[index]);
+            assert!(matches!(history.steps[0].action, EPlayerAction::Bet(2)));
+            let events = history.steps.last().unwrap().result.as_ref().unwrap();
+            assert!(events.iter().any(|event| matches!(event, ETableOutputEvent::GameOver { .. })));
+            assert!(history.dealer_cards.len() >= 2);
+            assert!(history.player_cards.iter().all(|cards| cards.len() >= 2));
+        }
+        assert_eq!(histories[0].shoe.remain_cards_num, Some(52));
+        // 每局开始时的牌库位置与上一局发出的牌数一致
+        let dealt: usize = histories[0].player_cards.iter().map(|cards| cards.len()).sum::<usize>()
+            + histories[0].dealer_cards.len();
+        assert_eq!(histories[1].shoe.remain_cards_num, Some(52 - dealt));
+    }
+
+    #[test]
+    fn test_read_error() {
+        let s = "\n{}\n";
+        assert!(matches!(
+            read(s.as_bytes()),
+            Err(EHandHistoryError::ParseError { line: 2, .. })
+        ));
+        assert!(read("".as_bytes()).unwrap().is_empty());
+    }
+}
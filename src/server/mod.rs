@@ -3,7 +3,14 @@ pub mod hand;
 pub mod card;
 pub mod value;
 pub mod rule;
+pub mod preset;
 pub mod table;
+pub mod snapshot;
+pub mod history;
+pub mod replay;
+pub mod seat;
+pub mod side_bet;
+pub mod bonus;
 pub mod player;
 pub mod solver;
 mod tests;
\ No newline at end of file
@@ -1,13 +1,29 @@
 pub mod static_strategy_player;
 
-#[derive(Debug, Clone)]
+use super::side_bet::SSideBetWager;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EPlayerAction {
     Bet(usize),
+    // 同时在多个spot下注 按spot顺序
+    BetSpots(Vec<usize>),
+    // 多个spot下注 同时在指定spot上下注边注
+    BetWithSideBets(Vec<usize>, Vec<SSideBetWager>),
+    // 买保险 0表示不买 允许买不超过insurance_max的部分保险
     BuyInsurance(usize),
+    // 玩家blackjack且庄家明牌为A时 选择直接按1:1赔付
+    EvenMoney,
     Split,
     DoubleDown,
+    // double for less 追加少于原下注的筹码
+    DoubleDownForLess(usize),
     Hit,
     Stand,
+    Surrender,
+    // Blackjack Switch 交换两手牌的第二张牌
+    Switch,
+    // 推进庄家行动的状态 receive_player_action会自动推进 只有从庄家行动的状态恢复牌桌时需要
     WaitNext,
     NoAction
 }
\ No newline at end of file
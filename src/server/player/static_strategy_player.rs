@@ -16,11 +16,21 @@ impl SStaticStrategyPlayer {
                 // 永远bet 2 todo
                 EPlayerAction::Bet(2)
             }
+            ETableState::PlayerSwitch(_) => {
+                // 永远不交换 todo
+                EPlayerAction::NoAction
+            }
             ETableState::PlayerBuyInsurance => {
                 // 永远不买 todo
                 EPlayerAction::BuyInsurance(0)
             }
-            ETableState::PlayerSplitOrDoubleDownOrHitOrStand(_) => {
+            ETableState::PlayerSurrender(_) => {
+                // 永远不投降 todo
+                EPlayerAction::NoAction
+            }
+            ETableState::PlayerSplitOrDoubleDownOrHitOrStand(_)
+            | ETableState::PlayerSplitOrHitOrStand(_)
+            | ETableState::PlayerSplitOrStand(_) => {
                 // 必定split todo
                 EPlayerAction::Split
             }
@@ -39,6 +49,10 @@ impl SStaticStrategyPlayer {
                     _ => EPlayerAction::Stand,
                 }
             }
+            ETableState::PlayerRescueOrStand(_) => {
+                // 永远不救援 todo
+                EPlayerAction::Stand
+            }
             _ => EPlayerAction::WaitNext
         }
     }
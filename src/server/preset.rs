@@ -0,0 +1,169 @@
+use super::bonus::EBonus21;
+use super::card::ECardNumber;
+use super::rule::{
+    EDealerSoft17Rule, EDoubleDownRule, ESurrenderRule, SGameRule, SGameRuleBuilder,
+};
+use super::side_bet::{ESideBet, SSideBetRule};
+use fraction::Fraction;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+/// 赌场规则预设 可以通过名称选择 保证结果可以复现
+#[derive(Debug, Copy, Clone, EnumIter, Hash, Display, PartialEq, Eq)]
+pub enum ERulePreset {
+    /// 拉斯维加斯大道 6副牌 S17 允许split后double 晚投降
+    #[strum(to_string = "Vegas Strip 6D S17 DAS")]
+    VegasStrip6D,
+    /// 大西洋城 8副牌 S17 允许split后double 晚投降
+    #[strum(to_string = "Atlantic City 8D late surrender")]
+    AtlanticCity8D,
+    /// 拉斯维加斯市中心 2副牌 H17 允许split后double
+    #[strum(to_string = "Downtown 2D H17")]
+    Downtown2D,
+    /// 单副牌 blackjack按6:5赔付 H17 只允许10-11点double
+    #[strum(to_string = "Single deck 6:5")]
+    SingleDeck65,
+    /// 西班牙21点 6副移除10的牌 H17 玩家21点总是获胜 晚投降 double救援
+    #[strum(to_string = "Spanish 21 6D H17")]
+    Spanish21,
+    /// Free Bet 6副牌 H17 硬9-11点免费double 除10点牌外免费split 庄家22点平局
+    #[strum(to_string = "Free Bet 6D H17 push 22")]
+    FreeBet6D,
+    /// Blackjack Switch 6副牌 H17 每个下注两手牌可交换第二张牌 blackjack按1:1赔付 庄家22点平局
+    #[strum(to_string = "Blackjack Switch 6D H17")]
+    BlackjackSwitch6D,
+}
+
+impl ERulePreset {
+    /// 根据名称查找预设 忽略大小写
+    pub fn from_name(name: &str) -> Option<Self> {
+        ERulePreset::iter().find(|preset| preset.to_string().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// 牌库中的牌副数
+    pub fn decks_num(&self) -> u8 {
+        match self {
+            ERulePreset::VegasStrip6D => 6,
+            ERulePreset::AtlanticCity8D => 8,
+            ERulePreset::Downtown2D => 2,
+            ERulePreset::SingleDeck65 => 1,
+            ERulePreset::Spanish21 => 6,
+            ERulePreset::FreeBet6D => 6,
+            ERulePreset::BlackjackSwitch6D => 6,
+        }
+    }
+
+    /// 预设对应的规则 shuffle_threshold为1减去发牌深度(penetration)
+    pub fn rule(&self) -> SGameRule {
+        let builder = match self {
+            ERulePreset::VegasStrip6D => SGameRuleBuilder {
+                dealer_soft_17: EDealerSoft17Rule::Stand,
+                surrender: ESurrenderRule::Late,
+                double_after_split: true,
+                split_max_hands: 4,
+                shuffle_threshold: Fraction::new(1u64, 4u64),
+                ..casino_builder()
+            },
+            ERulePreset::AtlanticCity8D => SGameRuleBuilder {
+                dealer_soft_17: EDealerSoft17Rule::Stand,
+                surrender: ESurrenderRule::Late,
+                double_after_split: true,
+                split_max_hands: 4,
+                shuffle_threshold: Fraction::new(1u64, 3u64),
+                ..casino_builder()
+            },
+            ERulePreset::Downtown2D => SGameRuleBuilder {
+                dealer_soft_17: EDealerSoft17Rule::Hit,
+                surrender: ESurrenderRule::None,
+                double_after_split: true,
+                split_max_hands: 4,
+                shuffle_threshold: Fraction::new(7u64, 20u64),
+                ..casino_builder()
+            },
+            ERulePreset::SingleDeck65 => SGameRuleBuilder {
+                blackjack_pay: Fraction::new(6u64, 5u64),
+                dealer_soft_17: EDealerSoft17Rule::Hit,
+                surrender: ESurrenderRule::None,
+                double_down: EDoubleDownRule::TenToEleven,
+                double_after_split: false,
+                split_max_hands: 2,
+                shuffle_threshold: Fraction::new(2u64, 5u64),
+                ..casino_builder()
+            },
+            ERulePreset::Spanish21 => SGameRuleBuilder {
+                dealer_soft_17: EDealerSoft17Rule::Hit,
+                surrender: ESurrenderRule::Late,
+                double_after_split: true,
+                split_max_hands: 4,
+                resplit_aces: true,
+                shuffle_threshold: Fraction::new(1u64, 4u64),
+                removed_cards: vec![ECardNumber::Ten],
+                player_21_always_wins: true,
+                bonus_21: EBonus21::default_pay_table(),
+                double_down_rescue: true,
+                ..casino_builder()
+            },
+            ERulePreset::FreeBet6D => SGameRuleBuilder {
+                dealer_soft_17: EDealerSoft17Rule::Hit,
+                surrender: ESurrenderRule::None,
+                double_after_split: true,
+                split_max_hands: 4,
+                shuffle_threshold: Fraction::new(1u64, 4u64),
+                free_double: true,
+                free_split: true,
+                dealer_22_push: true,
+                ..casino_builder()
+            },
+            ERulePreset::BlackjackSwitch6D => SGameRuleBuilder {
+                blackjack_pay: Fraction::from(1u64),
+                dealer_soft_17: EDealerSoft17Rule::Hit,
+                surrender: ESurrenderRule::None,
+                double_after_split: true,
+                split_max_hands: 4,
+                shuffle_threshold: Fraction::new(1u64, 4u64),
+                dealer_22_push: true,
+                switch: true,
+                ..casino_builder()
+            },
+        };
+        SGameRuleBuilder {
+            decks_num: self.decks_num(),
+            ..builder
+        }
+        .build()
+        .unwrap()
+    }
+}
+
+/// 各预设共用的赌场常见规则
+/// split A之后只能拿一张牌 不允许再次split A 允许double for less 开放所有边注
+fn casino_builder() -> SGameRuleBuilder {
+    SGameRuleBuilder {
+        resplit_aces: false,
+        split_aces_one_card: true,
+        double_for_less: true,
+        side_bets: ESideBet::iter().map(SSideBetRule::new).collect(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ERulePreset;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_presets() {
+        for preset in ERulePreset::iter() {
+            let rule = preset.rule();
+            println!("{preset}: {} decks {:?}", preset.decks_num(), rule);
+            assert_eq!(rule.decks_num, preset.decks_num());
+            assert_eq!(ERulePreset::from_name(&preset.to_string()), Some(preset));
+        }
+        assert_eq!(
+            ERulePreset::from_name("downtown 2d h17"),
+            Some(ERulePreset::Downtown2D)
+        );
+        assert_eq!(ERulePreset::from_name("Reno"), None);
+    }
+}
@@ -0,0 +1,274 @@
+use std::fmt;
+use std::path::Path;
+
+use super::card::{ECard, ECardColor, ECardNumber};
+use super::deck::diy_deck::SDiyDeck;
+use super::hand::dealer_hand::SDealerHand;
+use super::hand::player_hand::SPlayerHand;
+use super::history::{load, EHandHistoryError, SHandHistory, SHandHistoryStep};
+use super::table::{ETableOutputEvent, ETableState, STable};
+
+/// 占位牌 填充牌库中本局不会发出的位置和烧掉的牌 回放正确时不会出现在事件中
+const PLACEHOLDER_CARD: ECard = ECard {
+    color: ECardColor::Spades,
+    value: ECardNumber::Ace,
+};
+
+/// 回放结果与记录不一致的一次操作 step为该操作在本局中的index(从0开始)
+#[derive(Debug, Clone)]
+pub struct SReplayMismatch {
+    pub round: usize,
+    pub step: usize,
+    pub expected: SHandHistoryStep,
+    pub actual: SHandHistoryStep,
+}
+
+#[derive(Debug)]
+pub enum EReplayError {
+    // 记录不是从下注或洗牌开始(如从快照中途恢复) 无法重建手牌
+    StartStateError { round: usize, state: ETableState },
+    // 回放的牌桌状态或输出事件与记录不一致
+    Mismatch(Box<SReplayMismatch>),
+}
+
+impl fmt::Display for EReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EReplayError::StartStateError { round, state } => {
+                write!(f, "round {round} can not be replayed from state {state:?}")
+            }
+            EReplayError::Mismatch(mismatch) => {
+                let expected = serde_json::to_string(&mismatch.expected).unwrap_or_default();
+                let actual = serde_json::to_string(&mismatch.actual).unwrap_or_default();
+                write!(
+                    f,
+                    "round {} step {} mismatch\n  expected: {expected}\n  actual:   {actual}",
+                    mismatch.round, mismatch.step
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for EReplayError {}
+
+/// 根据输出事件按抽牌顺序还原本局从牌库抽出的牌 烧牌不公开 用占位牌代替
+pub fn get_drawn_cards(history: &SHandHistory) -> Vec<ECard> {
+    let mut cards = Vec::new();
+    for event in history.steps.iter().flat_map(|step| step.result.iter().flatten()) {
+        match event {
+            ETableOutputEvent::Shuffle { burn_cards_num, .. } => {
+                cards.extend(std::iter::repeat_n(PLACEHOLDER_CARD, *burn_cards_num));
+            }
+            // 每个spot依次拿第一张牌 庄家拿明牌 每个spot依次拿第二张牌 庄家拿底牌
+            ETableOutputEvent::InitGameWithCards {
+                player_cards,
+                dealer_cards,
+                ..
+            } => {
+                cards.extend(player_cards.iter().map(|hand| hand[0]));
+                cards.extend(dealer_cards.first());
+                cards.extend(player_cards.iter().map(|hand| hand[1]));
+                cards.extend(dealer_cards.iter().skip(1));
+            }
+            ETableOutputEvent::PlayerSplitCards { card1, card2 } => cards.extend([*card1, *card2]),
+            ETableOutputEvent::PlayerDrawCard { card, .. } | ETableOutputEvent::DealerHit { card } => cards.push(*card),
+            _ => {}
+        }
+    }
+    cards
+}
+
+/// 构建与记录时牌数和位置相同的牌库 使切牌卡和洗牌的判断与记录时一致
+/// 本局以洗牌开始时(从洗牌状态恢复的牌桌) 洗牌后从牌库开头抽牌
+fn new_replay_deck(history: &SHandHistory) -> SDiyDeck {
+    let drawn = get_drawn_cards(history);
+    let (mut cards, cards_num, draw_ptr) = match (history.shoe.cards_num, history.shoe.remain_cards_num) {
+        (Some(cards_num), Some(remain_cards_num)) => {
+            let dealt_num = cards_num.saturating_sub(remain_cards_num);
+            match history.steps.first().map(|step| &step.state) {
+                Some(ETableState::DealerShuffle) => (drawn, cards_num, dealt_num),
+                _ => {
+                    let mut cards = vec![PLACEHOLDER_CARD; dealt_num];
+                    cards.extend(drawn);
+                    (cards, cards_num, dealt_num)
+                }
+            }
+        }
+        // 无限牌库 多放一张占位牌 避免牌库用尽
+        _ => {
+            let cards_num = drawn.len() + 1;
+            (drawn, cards_num, 0)
+        }
+    };
+    if cards.len() < cards_num {
+        cards.resize(cards_num, PLACEHOLDER_CARD);
+    }
+    SDiyDeck::from_fixed_order(cards, draw_ptr, history.seed)
+}
+
+/// 比较序列化后的结果 事件和错误类型没有实现PartialEq
+fn is_same_step(expected: &SHandHistoryStep, actual: &SHandHistoryStep) -> bool {
+    match (serde_json::to_value(expected), serde_json::to_value(actual)) {
+        (Ok(expected), Ok(actual)) => expected == actual,
+        _ => false,
+    }
+}
+
+/// 用记录的牌序重建牌桌 依次执行记录的操作 遇到第一个不一致的操作时返回
+pub fn replay_round(history: &SHandHistory) -> Result<(), EReplayError> {
+    let Some(state) = history.steps.first().map(|step| step.state.clone()) else {
+        return Ok(());
+    };
+    if !matches!(state, ETableState::PlayerBet | ETableState::DealerShuffle) {
+        return Err(EReplayError::StartStateError {
+            round: history.round,
+            state,
+        });
+    }
+
+    let deck = new_replay_deck(history);
+    let player_chips = history.player_chips.first().copied().unwrap_or(0);
+    let mut table = STable::new(
+        history.rule.clone(),
+        SDealerHand::new(),
+        vec![SPlayerHand::new()],
+        player_chips,
+        Box::new(deck),
+    );
+    for &player_chips in history.player_chips.iter().skip(1) {
+        table.add_seat(player_chips);
+    }
+    table.state = state;
+    table.seed = history.seed;
+    table.rng = history.rng.clone();
+    table.cut_card_remain = history.shoe.cut_card_remain;
+
+    for (index, expected) in history.steps.iter().enumerate() {
+        let actual = SHandHistoryStep {
+            state: table.state.clone(),
+            action: expected.action.clone(),
+            result: table.receive_player_action(expected.action.clone()),
+        };
+        if !is_same_step(expected, &actual) {
+            return Err(EReplayError::Mismatch(Box::new(SReplayMismatch {
+                round: history.round,
+                step: index,
+                expected: expected.clone(),
+                actual,
+            })));
+        }
+    }
+    Ok(())
+}
+
+/// 依次回放每一局 返回所有不一致的局 为空时表示没有回归
+pub fn replay_session(histories: &[SHandHistory]) -> Vec<EReplayError> {
+    histories.iter().filter_map(|history| replay_round(history).err()).collect()
+}
+
+/// 回放JSONL牌局记录文件
+pub fn replay_file<P: AsRef<Path>>(path: P) -> Result<Vec<EReplayError>, EHandHistoryError> {
+    Ok(replay_session(&load(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use super::super::history::{read, SHandHistory, SHandHistoryRecorder};
+    use super::super::player::static_strategy_player::SStaticStrategyPlayer;
+    use super::super::preset::ERulePreset;
+    use super::super::rule::SGameRule;
+    use super::super::table::{ETableOutputEvent, ETableState, STable};
+    use super::{replay_round, replay_session, EReplayError};
+
+    /// 写入共享内存的writer 用于在测试中读取记录
+    #[derive(Clone, Default)]
+    struct SSharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SSharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// 两个座位按基本策略进行rounds局 返回所有牌局记录
+    fn record(mut table: STable, rounds: usize) -> Vec<SHandHistory> {
+        let player = SStaticStrategyPlayer::new();
+        let writer = SSharedWriter::default();
+        table.buy_chips(10000);
+        table.add_seat(10000);
+        table.history_recorder = Some(SHandHistoryRecorder::new(Box::new(writer.clone())));
+        let mut round = 0;
+        while round < rounds {
+            let events = table.receive_player_action(player.action(&table)).unwrap();
+            if events.iter().any(|event| matches!(event, ETableOutputEvent::GameOver { .. })) {
+                round += 1;
+            }
+        }
+        let buffer = writer.0.lock().unwrap().clone();
+        read(buffer.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_replay_session() {
+        let rule = SGameRule {
+            cut_card_jitter: 3,
+            burn_cards: 1,
+            ..SGameRule::default()
+        };
+        let histories = record(STable::new_queue_deck(rule, 5), 20);
+        // 单副牌20局中一定有洗牌 洗牌在结算后自动进行
+        assert!(histories
+            .iter()
+            .flat_map(|history| history.steps.iter().flat_map(|step| step.result.iter().flatten()))
+            .any(|event| matches!(event, ETableOutputEvent::Shuffle { .. })));
+        assert!(replay_session(&histories).is_empty());
+
+        for preset in [ERulePreset::Spanish21, ERulePreset::BlackjackSwitch6D] {
+            assert!(replay_session(&record(STable::from_preset(preset, 1), 20)).is_empty());
+        }
+        assert!(replay_session(&record(STable::new_random_deck(SGameRule::default(), 2), 20)).is_empty());
+        assert!(replay_session(&record(STable::new_csm_deck(20, SGameRule { decks_num: 2, ..Default::default() }, 3), 20)).is_empty());
+    }
+
+    #[test]
+    fn test_replay_mismatch() {
+        let mut histories = record(STable::new_queue_deck(SGameRule { decks_num: 6, ..Default::default() }, 8), 3);
+        // 修改记录中操作前的牌桌状态 牌序从记录中还原 修改牌面不会产生不一致
+        histories[1].steps[1].state = ETableState::DealerHitOrStand;
+        // 修改记录的结算结果
+        for event in histories[2].steps.last_mut().unwrap().result.iter_mut().flatten() {
+            if let ETableOutputEvent::GameOver { seats, .. } = event {
+                seats[0].player_chips += 1;
+            }
+        }
+
+        let errors = replay_session(&histories);
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(&errors[0], EReplayError::Mismatch(mismatch) if mismatch.round == 2 && mismatch.step == 1));
+        assert!(matches!(
+            &errors[1],
+            EReplayError::Mismatch(mismatch) if mismatch.round == 3 && mismatch.step == histories[2].steps.len() - 1
+        ));
+        println!("{}", errors[1]);
+
+        // 从牌局中途开始的记录
+        let start = histories[0]
+            .steps
+            .iter()
+            .position(|step| !matches!(step.state, ETableState::PlayerBet | ETableState::DealerShuffle))
+            .unwrap();
+        histories[0].steps.drain(..start);
+        assert!(matches!(
+            replay_round(&histories[0]),
+            Err(EReplayError::StartStateError { round: 1, .. })
+        ));
+    }
+}
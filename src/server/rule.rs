@@ -1,6 +1,71 @@
-use fraction::Fraction;
+use super::bonus::EBonus21;
+use super::card::{ECardNumber, ECardPoint};
+use super::deck::shuffle::{EShuffleProcedure, SDealerShuffle};
+use super::side_bet::{ESideBet, SSideBetRule};
+use super::value::EValue;
+use fraction::{Fraction, ToPrimitive};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{serde_as, DeserializeAs, SerializeAs};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+use strum_macros::Display;
 
-#[derive(Debug)]
+/// 一局至少需要发出的牌数 一个spot和庄家各两张牌
+pub const MIN_ROUND_CARDS: usize = 4;
+
+/// 庄家软17规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EDealerSoft17Rule {
+    /// S17 庄家软17停牌
+    #[default]
+    Stand,
+    /// H17 庄家软17继续拿牌
+    Hit,
+}
+
+/// 投降规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ESurrenderRule {
+    /// 不允许投降
+    #[default]
+    None,
+    /// 晚投降 庄家检查blackjack之后才能投降
+    Late,
+    /// 早投降 庄家检查blackjack之前即可投降
+    Early,
+}
+
+/// double down规则 限制允许double的手牌点数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EDoubleDownRule {
+    /// 任意两张牌
+    #[default]
+    Any,
+    /// 仅限9-11点
+    NineToEleven,
+    /// 仅限10-11点
+    TenToEleven,
+}
+
+/// 庄家底牌规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EHoleCardRule {
+    /// 美式 发牌时庄家拿两张牌 其中一张为底牌
+    #[default]
+    HoleCard,
+    /// 欧式无底牌(ENHC) 玩家行动结束后庄家才拿第二张牌
+    /// 庄家blackjack时玩家输掉全部下注(包括double和split追加的筹码)
+    NoHoleCard,
+    /// 欧式无底牌 庄家blackjack时玩家只输掉原始下注(OBO)
+    NoHoleCardOBO,
+}
+
+/// 游戏规则 只能通过SGameRuleBuilder校验后创建
+/// 反序列化时同样经过校验 序列化格式与SGameRuleBuilder相同
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SGameRuleBuilder", into = "SGameRuleBuilder")]
 pub struct SGameRule {
     pub blackjack_pay: Fraction,
     pub insurance_pay: Fraction,
@@ -9,48 +74,568 @@ pub struct SGameRule {
     pub bet_step: usize,
     pub insurance_max: Fraction,
     pub bet_options: Vec<usize>,
+    // 牌库中的牌副数 用于校验切牌卡和烧牌的位置
+    pub decks_num: u8,
+    // 切牌卡(cut card)后面的牌数占牌库的比例 即1减去发牌深度(penetration)
     pub shuffle_threshold: Fraction,
+    // 切牌卡后面的牌数 不为None时代替shuffle_threshold的比例
+    pub cut_card_cards: Option<usize>,
+    // 每次洗牌后 切牌卡在±cut_card_jitter张牌的范围内随机放置
+    pub cut_card_jitter: usize,
+    // 每次洗牌后烧掉(不使用)的牌数
+    pub burn_cards: usize,
+    // 洗牌方式 用于STable::new_queue_deck创建的牌库
+    pub shuffle_procedure: EShuffleProcedure,
+    pub dealer_soft_17: EDealerSoft17Rule,
+    pub surrender: ESurrenderRule,
+    // 最多允许split成几手牌
+    pub split_max_hands: usize,
+    // 是否允许再次split A
+    pub resplit_aces: bool,
+    // split A之后每手牌只能再拿一张牌
+    pub split_aces_one_card: bool,
+    // 是否允许split之后double down
+    pub double_after_split: bool,
+    pub double_down: EDoubleDownRule,
+    // 是否允许软牌double down 软牌按A计1点判断是否符合double_down规则
+    pub double_down_soft: bool,
+    // 是否允许double down时追加少于原下注的筹码
+    pub double_for_less: bool,
+    pub hole_card: EHoleCardRule,
+    // 庄家明牌为A或10点时 是否先检查底牌是否blackjack(peek)
+    pub dealer_peek: bool,
+    // 每局最多同时下注的spot数量
+    pub spots_max: usize,
+    // 允许下注的边注及其赔率表
+    pub side_bets: Vec<SSideBetRule>,
+    // 从牌库中移除的牌面 西班牙21点移除所有10(保留JQK)
+    pub removed_cards: Vec<ECardNumber>,
+    // 玩家的21点(非blackjack)总是获胜 即使庄家同为21点
+    pub player_21_always_wins: bool,
+    // 21点奖金的赔率表 为空时不支付奖金
+    pub bonus_21: Vec<(EBonus21, Fraction)>,
+    // double down之后的21点是否支付奖金
+    pub bonus_21_after_double: bool,
+    // double down拿牌之后是否允许救援(rescue) 输掉原始下注 退还double追加的筹码
+    pub double_down_rescue: bool,
+    // Free Bet 硬9-11点免费double 追加的下注由庄家出资
+    pub free_double: bool,
+    // Free Bet 除10点牌以外的对子免费split 新手牌的下注由庄家出资
+    pub free_split: bool,
+    // 庄家以22点bust时 与所有未bust的非blackjack手牌平局
+    pub dealer_22_push: bool,
+    // Blackjack Switch 每个下注同时发两手牌 发牌后可以交换两手牌的第二张牌
+    pub switch: bool,
+    // N张牌Charlie 手牌达到N张且未bust时自动获胜 None表示不使用
+    pub charlie_cards: Option<usize>,
+    // Charlie获胜的赔率
+    pub charlie_pay: Fraction,
 }
 
 impl Default for SGameRule {
     fn default() -> Self {
-        let blackjack_pay = Fraction::new(3u64, 2u64);
-        let insurance_pay = Fraction::new(2u64, 1u64);
-        let bet_min = 1;
-        let bet_max = 10;
-        let bet_step = 1;
-        let insurance_max = Fraction::new(1u64, 2u64);
-        let shuffle_threshold = Fraction::new(1u64, 5u64);
-        // 参数校验
-        assert!(bet_min <= bet_step);
-        assert!(bet_max >= bet_step);
-        assert_eq!(bet_step % bet_min, 0);
-        assert_eq!(bet_max % bet_step, 0);
-        assert!(shuffle_threshold.ge(&Fraction::from(0)));
-        assert!(shuffle_threshold.le(&Fraction::from(1)));
-        let mut bet_options = Vec::new();
-        for x in (bet_min..=bet_max).step_by(bet_step) {
-            bet_options.push(x);
+        SGameRuleBuilder::default().build().unwrap()
+    }
+}
+
+/// 游戏规则的构建器 字段与SGameRule相同(不包括由下注参数生成的bet_options)
+/// 可以从JSON/TOML文件反序列化 文件中缺少的字段使用默认规则
+/// 分数字段在文件中写作字符串("3/2" "0.2")或数字
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SGameRuleBuilder {
+    #[serde_as(as = "SFractionStr")]
+    pub blackjack_pay: Fraction,
+    #[serde_as(as = "SFractionStr")]
+    pub insurance_pay: Fraction,
+    pub bet_min: usize,
+    pub bet_max: usize,
+    pub bet_step: usize,
+    #[serde_as(as = "SFractionStr")]
+    pub insurance_max: Fraction,
+    pub decks_num: u8,
+    #[serde_as(as = "SFractionStr")]
+    pub shuffle_threshold: Fraction,
+    pub cut_card_cards: Option<usize>,
+    pub cut_card_jitter: usize,
+    pub burn_cards: usize,
+    pub shuffle_procedure: EShuffleProcedure,
+    pub dealer_soft_17: EDealerSoft17Rule,
+    pub surrender: ESurrenderRule,
+    pub split_max_hands: usize,
+    pub resplit_aces: bool,
+    pub split_aces_one_card: bool,
+    pub double_after_split: bool,
+    pub double_down: EDoubleDownRule,
+    pub double_down_soft: bool,
+    pub double_for_less: bool,
+    pub hole_card: EHoleCardRule,
+    pub dealer_peek: bool,
+    pub spots_max: usize,
+    pub side_bets: Vec<SSideBetRule>,
+    pub removed_cards: Vec<ECardNumber>,
+    pub player_21_always_wins: bool,
+    #[serde_as(as = "Vec<(_, SFractionStr)>")]
+    pub bonus_21: Vec<(EBonus21, Fraction)>,
+    pub bonus_21_after_double: bool,
+    pub double_down_rescue: bool,
+    pub free_double: bool,
+    pub free_split: bool,
+    pub dealer_22_push: bool,
+    pub switch: bool,
+    pub charlie_cards: Option<usize>,
+    #[serde_as(as = "SFractionStr")]
+    pub charlie_pay: Fraction,
+}
+
+/// 默认规则不限制split 不允许double for less 不开放边注 与没有这些规则时的行为相同
+impl Default for SGameRuleBuilder {
+    fn default() -> Self {
+        SGameRuleBuilder {
+            blackjack_pay: Fraction::new(3u64, 2u64),
+            insurance_pay: Fraction::new(2u64, 1u64),
+            bet_min: 1,
+            bet_max: 10,
+            bet_step: 1,
+            insurance_max: Fraction::new(1u64, 2u64),
+            decks_num: 1,
+            shuffle_threshold: Fraction::new(1u64, 5u64),
+            cut_card_cards: None,
+            cut_card_jitter: 0,
+            burn_cards: 0,
+            shuffle_procedure: EShuffleProcedure::Uniform,
+            dealer_soft_17: EDealerSoft17Rule::Stand,
+            surrender: ESurrenderRule::None,
+            split_max_hands: usize::MAX,
+            resplit_aces: true,
+            split_aces_one_card: false,
+            double_after_split: true,
+            double_down: EDoubleDownRule::Any,
+            double_down_soft: true,
+            double_for_less: false,
+            hole_card: EHoleCardRule::HoleCard,
+            dealer_peek: true,
+            spots_max: 7,
+            side_bets: Vec::new(),
+            removed_cards: Vec::new(),
+            player_21_always_wins: false,
+            bonus_21: Vec::new(),
+            bonus_21_after_double: false,
+            double_down_rescue: false,
+            free_double: false,
+            free_split: false,
+            dealer_22_push: false,
+            switch: false,
+            charlie_cards: None,
+            charlie_pay: Fraction::from(1u64),
+        }
+    }
+}
+
+impl From<SGameRule> for SGameRuleBuilder {
+    fn from(rule: SGameRule) -> Self {
+        SGameRuleBuilder {
+            blackjack_pay: rule.blackjack_pay,
+            insurance_pay: rule.insurance_pay,
+            bet_min: rule.bet_min,
+            bet_max: rule.bet_max,
+            bet_step: rule.bet_step,
+            insurance_max: rule.insurance_max,
+            decks_num: rule.decks_num,
+            shuffle_threshold: rule.shuffle_threshold,
+            cut_card_cards: rule.cut_card_cards,
+            cut_card_jitter: rule.cut_card_jitter,
+            burn_cards: rule.burn_cards,
+            shuffle_procedure: rule.shuffle_procedure,
+            dealer_soft_17: rule.dealer_soft_17,
+            surrender: rule.surrender,
+            split_max_hands: rule.split_max_hands,
+            resplit_aces: rule.resplit_aces,
+            split_aces_one_card: rule.split_aces_one_card,
+            double_after_split: rule.double_after_split,
+            double_down: rule.double_down,
+            double_down_soft: rule.double_down_soft,
+            double_for_less: rule.double_for_less,
+            hole_card: rule.hole_card,
+            dealer_peek: rule.dealer_peek,
+            spots_max: rule.spots_max,
+            side_bets: rule.side_bets,
+            removed_cards: rule.removed_cards,
+            player_21_always_wins: rule.player_21_always_wins,
+            bonus_21: rule.bonus_21,
+            bonus_21_after_double: rule.bonus_21_after_double,
+            double_down_rescue: rule.double_down_rescue,
+            free_double: rule.free_double,
+            free_split: rule.free_split,
+            dealer_22_push: rule.dealer_22_push,
+            switch: rule.switch,
+            charlie_cards: rule.charlie_cards,
+            charlie_pay: rule.charlie_pay,
         }
-        Self {
-            blackjack_pay,
-            insurance_pay,
-            bet_min,
-            bet_max,
-            bet_step,
-            insurance_max,
+    }
+}
+
+impl TryFrom<SGameRuleBuilder> for SGameRule {
+    type Error = ERuleError;
+
+    fn try_from(builder: SGameRuleBuilder) -> Result<Self, Self::Error> {
+        builder.build()
+    }
+}
+
+impl SGameRuleBuilder {
+    /// 校验所有参数 返回所有不合法的字段
+    pub fn validate(&self) -> Vec<SRuleFieldError> {
+        let mut errors = Vec::new();
+        let mut check = |valid: bool, field: ERuleField, reason: &str| {
+            if !valid {
+                errors.push(SRuleFieldError {
+                    field,
+                    reason: reason.to_string(),
+                });
+            }
+        };
+        let zero = Fraction::from(0);
+        let one = Fraction::from(1);
+        let is_number = |x: &Fraction| !x.is_nan() && !x.is_infinite();
+
+        check(
+            is_number(&self.blackjack_pay) && self.blackjack_pay >= zero,
+            ERuleField::BlackjackPay,
+            "must be a non-negative fraction",
+        );
+        check(
+            is_number(&self.insurance_pay) && self.insurance_pay >= zero,
+            ERuleField::InsurancePay,
+            "must be a non-negative fraction",
+        );
+        check(self.bet_min >= 1, ERuleField::BetMin, "must be at least 1");
+        check(
+            self.bet_min <= self.bet_step,
+            ERuleField::BetMin,
+            "must not be greater than bet_step",
+        );
+        check(
+            self.bet_min == 0 || self.bet_step.is_multiple_of(self.bet_min),
+            ERuleField::BetStep,
+            "must be a multiple of bet_min",
+        );
+        check(
+            self.bet_max >= self.bet_step,
+            ERuleField::BetMax,
+            "must not be less than bet_step",
+        );
+        check(
+            self.bet_step == 0 || self.bet_max.is_multiple_of(self.bet_step),
+            ERuleField::BetMax,
+            "must be a multiple of bet_step",
+        );
+        check(
+            is_number(&self.insurance_max) && self.insurance_max >= zero && self.insurance_max <= one,
+            ERuleField::InsuranceMax,
+            "must be between 0 and 1",
+        );
+        check(
+            is_number(&self.shuffle_threshold)
+                && self.shuffle_threshold >= zero
+                && self.shuffle_threshold <= one,
+            ERuleField::ShuffleThreshold,
+            "must be between 0 and 1",
+        );
+        check(self.decks_num >= 1, ERuleField::DecksNum, "must be at least 1");
+        // 牌库为空或shuffle_threshold不合法时 已有对应字段的错误 不再检查切牌卡位置
+        let cards_num = self.get_cards_num();
+        if cards_num > 0
+            && is_number(&self.shuffle_threshold)
+            && self.shuffle_threshold >= zero
+            && self.shuffle_threshold <= one
+        {
+            // 切牌卡位置按最靠前和最靠后的偏移检查 保证切牌卡前后都留有一局所需的牌
+            let (cut_card_field, cut_card_remain) = match self.cut_card_cards {
+                Some(cards) => (ERuleField::CutCardCards, cards),
+                None => (
+                    ERuleField::ShuffleThreshold,
+                    get_threshold_remain(&self.shuffle_threshold, cards_num),
+                ),
+            };
+            check(
+                cut_card_remain >= self.cut_card_jitter + MIN_ROUND_CARDS,
+                cut_card_field,
+                &format!("must leave at least {MIN_ROUND_CARDS} cards behind the cut card after cut_card_jitter"),
+            );
+            check(
+                self.burn_cards + cut_card_remain + self.cut_card_jitter + MIN_ROUND_CARDS <= cards_num,
+                match self.burn_cards {
+                    0 => cut_card_field,
+                    _ => ERuleField::BurnCards,
+                },
+                &format!(
+                    "burn_cards + cut card + cut_card_jitter must leave at least {MIN_ROUND_CARDS} cards in a {cards_num} card shoe"
+                ),
+            );
+        }
+        if let EShuffleProcedure::Dealer(shuffle) = self.shuffle_procedure {
+            // 荷官洗牌的字段可以直接构造 需要与反序列化时同样校验
+            if let Err(error) = SDealerShuffle::new(shuffle.plug, shuffle.riffles, shuffle.clumping, shuffle.strip_cuts) {
+                check(false, ERuleField::ShuffleProcedure, &error.to_string());
+            }
+        }
+        check(self.split_max_hands >= 1, ERuleField::SplitMaxHands, "must be at least 1");
+        check(self.spots_max >= 1, ERuleField::SpotsMax, "must be at least 1");
+        for (index, side_bet_rule) in self.side_bets.iter().enumerate() {
+            check(
+                !self.side_bets[..index]
+                    .iter()
+                    .any(|rule| rule.side_bet == side_bet_rule.side_bet),
+                ERuleField::SideBets,
+                &format!("{} is configured more than once", side_bet_rule.side_bet),
+            );
+            check(
+                side_bet_rule.pay_table.iter().all(|(_, pay)| is_number(pay) && *pay >= zero),
+                ERuleField::SideBets,
+                &format!("{} pays must be non-negative fractions", side_bet_rule.side_bet),
+            );
+        }
+        check(
+            ECardNumber::iter().any(|value| !self.removed_cards.contains(&value)),
+            ERuleField::RemovedCards,
+            "must not remove every card",
+        );
+        for (index, (bonus, pay)) in self.bonus_21.iter().enumerate() {
+            check(
+                !self.bonus_21[..index].iter().any(|(b, _)| b == bonus),
+                ERuleField::Bonus21,
+                &format!("{bonus} is configured more than once"),
+            );
+            check(
+                is_number(pay) && *pay >= zero,
+                ERuleField::Bonus21,
+                &format!("{bonus} pay must be a non-negative fraction"),
+            );
+        }
+        check(
+            self.charlie_cards.is_none_or(|cards| cards >= 3),
+            ERuleField::CharlieCards,
+            "must be at least 3",
+        );
+        check(
+            is_number(&self.charlie_pay) && self.charlie_pay >= zero,
+            ERuleField::CharliePay,
+            "must be a non-negative fraction",
+        );
+        errors
+    }
+
+    /// 牌库的初始牌数 按牌副数和移除的牌面计算
+    pub fn get_cards_num(&self) -> usize {
+        get_cards_num(self.decks_num, &self.removed_cards)
+    }
+
+    /// 校验参数并生成规则
+    pub fn build(self) -> Result<SGameRule, ERuleError> {
+        let errors = self.validate();
+        if !errors.is_empty() {
+            return Err(ERuleError::InvalidFields(errors));
+        }
+        let bet_options = (self.bet_min..=self.bet_max).step_by(self.bet_step).collect();
+        Ok(SGameRule {
+            blackjack_pay: self.blackjack_pay,
+            insurance_pay: self.insurance_pay,
+            bet_min: self.bet_min,
+            bet_max: self.bet_max,
+            bet_step: self.bet_step,
+            insurance_max: self.insurance_max,
             bet_options,
-            shuffle_threshold,
+            decks_num: self.decks_num,
+            shuffle_threshold: self.shuffle_threshold,
+            cut_card_cards: self.cut_card_cards,
+            cut_card_jitter: self.cut_card_jitter,
+            burn_cards: self.burn_cards,
+            shuffle_procedure: self.shuffle_procedure,
+            dealer_soft_17: self.dealer_soft_17,
+            surrender: self.surrender,
+            split_max_hands: self.split_max_hands,
+            resplit_aces: self.resplit_aces,
+            split_aces_one_card: self.split_aces_one_card,
+            double_after_split: self.double_after_split,
+            double_down: self.double_down,
+            double_down_soft: self.double_down_soft,
+            double_for_less: self.double_for_less,
+            hole_card: self.hole_card,
+            dealer_peek: self.dealer_peek,
+            spots_max: self.spots_max,
+            side_bets: self.side_bets,
+            removed_cards: self.removed_cards,
+            player_21_always_wins: self.player_21_always_wins,
+            bonus_21: self.bonus_21,
+            bonus_21_after_double: self.bonus_21_after_double,
+            double_down_rescue: self.double_down_rescue,
+            free_double: self.free_double,
+            free_split: self.free_split,
+            dealer_22_push: self.dealer_22_push,
+            switch: self.switch,
+            charlie_cards: self.charlie_cards,
+            charlie_pay: self.charlie_pay,
+        })
+    }
+}
+
+/// 规则中的字段 用于标识校验失败的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum ERuleField {
+    BlackjackPay,
+    InsurancePay,
+    BetMin,
+    BetMax,
+    BetStep,
+    InsuranceMax,
+    DecksNum,
+    ShuffleThreshold,
+    CutCardCards,
+    BurnCards,
+    ShuffleProcedure,
+    SplitMaxHands,
+    SpotsMax,
+    SideBets,
+    RemovedCards,
+    Bonus21,
+    CharlieCards,
+    CharliePay,
+}
+
+/// 单个字段的校验错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SRuleFieldError {
+    pub field: ERuleField,
+    pub reason: String,
+}
+
+impl fmt::Display for SRuleFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.reason)
+    }
+}
+
+#[derive(Debug)]
+pub enum ERuleError {
+    // 读取文件失败
+    IoError(std::io::Error),
+    // 文件扩展名不是json或toml
+    UnknownFormatError(String),
+    // 文件格式错误
+    ParseError(String),
+    // 字段校验失败 包括所有不合法的字段
+    InvalidFields(Vec<SRuleFieldError>),
+}
+
+impl fmt::Display for ERuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ERuleError::IoError(e) => write!(f, "failed to read rule file: {e}"),
+            ERuleError::UnknownFormatError(ext) => write!(f, "unknown rule file format: {ext:?}"),
+            ERuleError::ParseError(e) => write!(f, "failed to parse rule file: {e}"),
+            ERuleError::InvalidFields(errors) => {
+                write!(f, "invalid rule fields: ")?;
+                for (index, error) in errors.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ERuleError {}
+
+/// 分数在文件中的表示 序列化为字符串 反序列化时接受字符串或数字
+pub struct SFractionStr;
+
+impl SerializeAs<Fraction> for SFractionStr {
+    fn serialize_as<S: Serializer>(source: &Fraction, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&source.to_string())
+    }
+}
+
+impl<'de> DeserializeAs<'de, Fraction> for SFractionStr {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Fraction, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum EFractionValue {
+            Int(u64),
+            Float(f64),
+            Str(String),
+        }
+        match EFractionValue::deserialize(deserializer)? {
+            EFractionValue::Int(x) => Ok(Fraction::from(x)),
+            EFractionValue::Float(x) => Ok(Fraction::from(x)),
+            EFractionValue::Str(x) => {
+                // 分母为0时Fraction::from_str会panic
+                if x.split_once('/').is_some_and(|(_, den)| den.trim() == "0") {
+                    return Err(de::Error::custom(format!("zero denominator in fraction {x:?}")));
+                }
+                Fraction::from_str(x.trim())
+                    .map_err(|_| de::Error::custom(format!("invalid fraction {x:?}")))
+            }
         }
     }
 }
 
 impl SGameRule {
+    /// 从JSON字符串读取规则
+    pub fn from_json_str(s: &str) -> Result<Self, ERuleError> {
+        let builder: SGameRuleBuilder =
+            serde_json::from_str(s).map_err(|e| ERuleError::ParseError(e.to_string()))?;
+        builder.build()
+    }
+
+    /// 从TOML字符串读取规则
+    pub fn from_toml_str(s: &str) -> Result<Self, ERuleError> {
+        let builder: SGameRuleBuilder =
+            toml::from_str(s).map_err(|e| ERuleError::ParseError(e.to_string()))?;
+        builder.build()
+    }
+
+    /// 从文件读取规则 根据扩展名判断文件格式(json/toml)
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ERuleError> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path).map_err(ERuleError::IoError)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => SGameRule::from_json_str(&s),
+            Some("toml") => SGameRule::from_toml_str(&s),
+            ext => Err(ERuleError::UnknownFormatError(ext.unwrap_or("").to_string())),
+        }
+    }
+
     /// 判断player的bet的amount是否合法
     pub fn check_bet(&self, bet: usize) -> bool {
         self.bet_options.contains(&bet)
     }
 
+    /// 判断player同时下注的spot数量是否合法
+    pub fn check_spots(&self, spots_num: usize) -> bool {
+        spots_num >= 1 && spots_num <= self.spots_max
+    }
+
+    /// 获取边注规则 未开放该边注时为None
+    pub fn get_side_bet_rule(&self, side_bet: ESideBet) -> Option<&SSideBetRule> {
+        self.side_bets.iter().find(|rule| rule.side_bet == side_bet)
+    }
+
+    /// 判断边注是否开放 以及边注的amount是否合法
+    pub fn check_side_bet(&self, side_bet: ESideBet, amount: usize) -> bool {
+        self.get_side_bet_rule(side_bet).is_some() && self.check_bet(amount)
+    }
+
+    /// 获取21点奖金的赔率 不在赔率表中时为None
+    pub fn get_bonus_21_pay(&self, bonus: EBonus21) -> Option<Fraction> {
+        self.bonus_21.iter().find(|(b, _)| *b == bonus).map(|(_, pay)| *pay)
+    }
+
     /// 判断player buy insurance的amount是否合法
     pub fn check_insurance(&self, bet: usize, insurance: usize) -> bool {
         let max_insurance = self.insurance_max * bet;
@@ -61,6 +646,73 @@ impl SGameRule {
         }
     }
 
+    /// 判断庄家在当前手牌value下是否停止拿牌
+    /// 不足17继续拿牌 硬17及以上或bust停牌
+    /// 软17根据dealer_soft_17规则决定
+    pub fn is_dealer_stop(&self, value: EValue) -> bool {
+        match value {
+            EValue::S17 => self.dealer_soft_17 == EDealerSoft17Rule::Stand,
+            EValue::Bust => true,
+            _ => value.to_point() >= 17,
+        }
+    }
+
+    /// 判断手牌value是否允许double down
+    pub fn check_double_down(&self, value: EValue) -> bool {
+        let is_soft = value.is_soft();
+        if is_soft && !self.double_down_soft {
+            return false;
+        }
+        // 软牌中的A按1点计算
+        let point = match is_soft {
+            true => value.to_point() - 10,
+            false => value.to_point(),
+        };
+        match self.double_down {
+            EDoubleDownRule::Any => true,
+            EDoubleDownRule::NineToEleven => (9..=11).contains(&point),
+            EDoubleDownRule::TenToEleven => (10..=11).contains(&point),
+        }
+    }
+
+    /// 判断double down是否免费 Free Bet规则下硬9-11点免费
+    pub fn check_free_double(&self, value: EValue) -> bool {
+        self.free_double && !value.is_soft() && (9..=11).contains(&value.to_point())
+    }
+
+    /// 判断cards_num张未bust的手牌是否为Charlie
+    pub fn check_charlie(&self, cards_num: usize) -> bool {
+        self.charlie_cards.is_some_and(|cards| cards_num >= cards)
+    }
+
+    /// 判断split是否免费 Free Bet规则下除10点牌以外的对子免费
+    pub fn check_free_split(&self, value: ECardNumber) -> bool {
+        self.free_split && Into::<ECardPoint>::into(value) != ECardPoint::Ten
+    }
+
+    /// 判断double for less的追加筹码是否合法
+    /// bet 原下注
+    /// amount 追加筹码
+    pub fn check_double_for_less(&self, bet: usize, amount: usize) -> bool {
+        self.double_for_less && amount > 0 && amount <= bet
+    }
+
+    /// 庄家是否在发牌时没有底牌(ENHC)
+    pub fn is_no_hole_card(&self) -> bool {
+        self.hole_card != EHoleCardRule::HoleCard
+    }
+
+    /// 庄家是否在玩家行动之前检查blackjack
+    /// 无底牌时庄家无法peek
+    pub fn is_dealer_peek(&self) -> bool {
+        self.dealer_peek && !self.is_no_hole_card()
+    }
+
+    /// 庄家blackjack时 是否只输掉原始下注
+    pub fn is_original_bets_only(&self) -> bool {
+        self.hole_card == EHoleCardRule::NoHoleCardOBO
+    }
+
     /// 判断剩余牌数是否触发阈值
     /// cards_num 牌库的初始牌数
     /// cards_remain 剩余未发出的牌数
@@ -69,15 +721,323 @@ impl SGameRule {
         Fraction::new(cards_remain as u64, cards_num as u64) <= self.shuffle_threshold
             // || cards_remain <= 0
     }
+
+    /// 洗牌后放置切牌卡 返回切牌卡后面的牌数 切牌卡发出后本局结束时洗牌
+    /// cards_num 牌库的初始牌数
+    /// jitter 切牌卡位置的随机偏移 需要在±cut_card_jitter范围内
+    pub fn get_cut_card_remain(&self, cards_num: usize, jitter: isize) -> usize {
+        let remain = match self.cut_card_cards {
+            Some(cards) => cards,
+            None => get_threshold_remain(&self.shuffle_threshold, cards_num),
+        };
+        remain.saturating_add_signed(jitter)
+    }
+
+    /// 牌库的初始牌数 按牌副数和移除的牌面计算
+    pub fn get_cards_num(&self) -> usize {
+        get_cards_num(self.decks_num, &self.removed_cards)
+    }
+}
+
+fn get_cards_num(decks_num: u8, removed_cards: &[ECardNumber]) -> usize {
+    let values_num = ECardNumber::iter().filter(|value| !removed_cards.contains(value)).count();
+    decks_num as usize * values_num * 4
+}
+
+// 按shuffle_threshold的比例计算切牌卡后面的牌数
+fn get_threshold_remain(shuffle_threshold: &Fraction, cards_num: usize) -> usize {
+    (*shuffle_threshold * Fraction::from(cards_num)).floor().to_usize().unwrap()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::SGameRule;
+    use super::{
+        EDealerSoft17Rule, EDoubleDownRule, ERuleError, ERuleField, ESurrenderRule, SGameRule,
+        SGameRuleBuilder,
+    };
+    use crate::server::bonus::EBonus21;
+    use crate::server::card::ECardNumber;
+    use crate::server::deck::shuffle::{EShuffleProcedure, SDealerShuffle};
+    use crate::server::side_bet::ESideBet;
+    use crate::server::value::EValue;
+    use fraction::Fraction;
+    use strum::IntoEnumIterator;
 
     #[tokio::test]
     async fn test() {
         let game_rule = SGameRule::default();
         println!("{:?}", game_rule);
     }
+
+    #[test]
+    fn test_is_dealer_stop() {
+        let mut game_rule = SGameRule::default();
+        assert!(!game_rule.is_dealer_stop(EValue::H16));
+        assert!(!game_rule.is_dealer_stop(EValue::S16));
+        assert!(game_rule.is_dealer_stop(EValue::S17));
+        assert!(game_rule.is_dealer_stop(EValue::H17));
+        assert!(game_rule.is_dealer_stop(EValue::S18));
+        assert!(game_rule.is_dealer_stop(EValue::Bust));
+
+        game_rule.dealer_soft_17 = EDealerSoft17Rule::Hit;
+        assert!(!game_rule.is_dealer_stop(EValue::S17));
+        assert!(game_rule.is_dealer_stop(EValue::H17));
+        assert!(game_rule.is_dealer_stop(EValue::S18));
+    }
+
+    /// 默认规则不限制split 不开放double for less和边注
+    #[test]
+    fn test_default() {
+        let rule = SGameRule::default();
+        assert_eq!(rule.split_max_hands, usize::MAX);
+        assert!(rule.resplit_aces);
+        assert!(!rule.split_aces_one_card);
+        assert!(!rule.double_for_less);
+        assert!(rule.side_bets.is_empty());
+        let rule = SGameRule::from_json_str("{}").unwrap();
+        assert_eq!(rule.split_max_hands, usize::MAX);
+        assert!(rule.side_bets.is_empty());
+    }
+
+    #[test]
+    fn test_check_double_down() {
+        let mut game_rule = SGameRule::default();
+        assert!(game_rule.check_double_down(EValue::H16));
+        assert!(game_rule.check_double_down(EValue::S17));
+
+        game_rule.double_down = EDoubleDownRule::NineToEleven;
+        assert!(game_rule.check_double_down(EValue::H9));
+        assert!(game_rule.check_double_down(EValue::H11));
+        assert!(!game_rule.check_double_down(EValue::H8));
+        assert!(!game_rule.check_double_down(EValue::H12));
+        assert!(!game_rule.check_double_down(EValue::S17));
+        // 软19按A计1点为9点
+        assert!(game_rule.check_double_down(EValue::S19));
+        game_rule.double_down_soft = false;
+        assert!(!game_rule.check_double_down(EValue::S19));
+
+        game_rule.double_down = EDoubleDownRule::TenToEleven;
+        assert!(!game_rule.check_double_down(EValue::H9));
+        assert!(game_rule.check_double_down(EValue::H10));
+
+        // 默认不允许double for less
+        assert!(!game_rule.check_double_for_less(4, 1));
+        game_rule.double_for_less = true;
+        assert!(game_rule.check_double_for_less(4, 1));
+        assert!(!game_rule.check_double_for_less(4, 5));
+        assert!(!game_rule.check_double_for_less(4, 0));
+        game_rule.double_for_less = false;
+        assert!(!game_rule.check_double_for_less(4, 1));
+
+        assert!(!game_rule.check_free_double(EValue::H10));
+        game_rule.free_double = true;
+        assert!(game_rule.check_free_double(EValue::H9));
+        assert!(game_rule.check_free_double(EValue::H11));
+        assert!(!game_rule.check_free_double(EValue::H12));
+        // 软19按A计1点为9点 但软牌不能免费double
+        assert!(!game_rule.check_free_double(EValue::S19));
+        assert!(!game_rule.check_free_split(ECardNumber::Eight));
+        game_rule.free_split = true;
+        assert!(game_rule.check_free_split(ECardNumber::Eight));
+        assert!(game_rule.check_free_split(ECardNumber::Ace));
+        assert!(!game_rule.check_free_split(ECardNumber::King));
+    }
+
+    #[test]
+    fn test_load() {
+        let rule = SGameRule::from_toml_str(include_str!("../../rules/example.toml")).unwrap();
+        println!("{:?}", rule);
+        assert_eq!(rule.dealer_soft_17, EDealerSoft17Rule::Hit);
+        assert_eq!(rule.surrender, ESurrenderRule::Late);
+        assert_eq!(rule.blackjack_pay, Fraction::new(3u64, 2u64));
+        assert_eq!(rule.bet_options, (1..=10).collect::<Vec<usize>>());
+        assert!(rule.get_side_bet_rule(ESideBet::LuckyLadies).is_none());
+
+        // 缺少的字段使用默认规则 分数可以写作数字
+        let rule = SGameRule::from_json_str(r#"{"blackjack_pay": 1.2, "bet_max": 20, "bet_step": 5}"#).unwrap();
+        assert_eq!(rule.blackjack_pay, Fraction::new(6u64, 5u64));
+        assert_eq!(rule.bet_options, vec![1, 6, 11, 16]);
+        assert_eq!(rule.spots_max, SGameRule::default().spots_max);
+
+        // 序列化后可以重新读取
+        let json = serde_json::to_string(&rule).unwrap();
+        let rule2: SGameRule = serde_json::from_str(&json).unwrap();
+        assert_eq!(rule2.blackjack_pay, rule.blackjack_pay);
+        assert_eq!(rule2.bet_options, rule.bet_options);
+
+        assert!(matches!(
+            SGameRule::from_json_str(r#"{"blackjack_pay": "3/0"}"#),
+            Err(ERuleError::ParseError(_))
+        ));
+        assert!(matches!(
+            SGameRule::from_json_str(r#"{"unknown_field": 1}"#),
+            Err(ERuleError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let builder = SGameRuleBuilder {
+            bet_min: 0,
+            bet_max: 15,
+            bet_step: 10,
+            shuffle_threshold: Fraction::new(3u64, 2u64),
+            spots_max: 0,
+            ..Default::default()
+        };
+        let Err(ERuleError::InvalidFields(errors)) = builder.build() else {
+            panic!("invalid rule should not build");
+        };
+        println!("{}", ERuleError::InvalidFields(errors.clone()));
+        let fields: Vec<ERuleField> = errors.iter().map(|error| error.field).collect();
+        assert_eq!(
+            fields,
+            vec![
+                ERuleField::BetMin,
+                ERuleField::BetMax,
+                ERuleField::ShuffleThreshold,
+                ERuleField::SpotsMax
+            ]
+        );
+
+        // 反序列化时同样校验
+        assert!(serde_json::from_str::<SGameRule>(r#"{"split_max_hands": 0}"#).is_err());
+        assert!(matches!(
+            SGameRule::from_toml_str("split_max_hands = 0"),
+            Err(ERuleError::InvalidFields(errors)) if errors[0].field == ERuleField::SplitMaxHands
+        ));
+
+        // 西班牙21点规则 不能移除所有牌 奖金不能重复配置
+        let rule = SGameRule::from_json_str(
+            r#"{"removed_cards": ["Ten"], "player_21_always_wins": true, "bonus_21": [["FiveCard", "3/2"]]}"#,
+        )
+        .unwrap();
+        assert_eq!(rule.removed_cards, vec![ECardNumber::Ten]);
+        assert_eq!(rule.get_bonus_21_pay(EBonus21::FiveCard), Some(Fraction::new(3u64, 2u64)));
+        assert_eq!(rule.get_bonus_21_pay(EBonus21::SixCard), None);
+        let builder = SGameRuleBuilder {
+            removed_cards: ECardNumber::iter().collect(),
+            bonus_21: vec![(EBonus21::FiveCard, Fraction::from(1)), (EBonus21::FiveCard, Fraction::from(2))],
+            charlie_cards: Some(2),
+            ..Default::default()
+        };
+        let fields: Vec<ERuleField> = builder.validate().iter().map(|error| error.field).collect();
+        assert_eq!(
+            fields,
+            vec![ERuleField::RemovedCards, ERuleField::Bonus21, ERuleField::CharlieCards]
+        );
+
+        // 五张牌Charlie
+        let rule = SGameRule::from_toml_str("charlie_cards = 5\ncharlie_pay = \"2\"").unwrap();
+        assert!(!rule.check_charlie(4));
+        assert!(rule.check_charlie(5));
+        assert!(rule.check_charlie(6));
+        assert_eq!(rule.charlie_pay, Fraction::from(2));
+        assert!(!SGameRule::default().check_charlie(7));
+    }
+
+    #[test]
+    fn test_cut_card() {
+        let mut rule = SGameRule::default();
+        // 默认按比例放置 52张牌时切牌卡后面有10张牌
+        assert_eq!(rule.get_cut_card_remain(52, 0), 10);
+        assert_eq!(rule.get_cut_card_remain(52, -3), 7);
+        assert_eq!(rule.get_cut_card_remain(52, -20), 0);
+        rule.cut_card_cards = Some(60);
+        assert_eq!(rule.get_cut_card_remain(312, 5), 65);
+        let rule = SGameRule::from_json_str(
+            r#"{"decks_num": 6, "cut_card_cards": 78, "cut_card_jitter": 10, "burn_cards": 1}"#,
+        )
+        .unwrap();
+        assert_eq!(rule.get_cards_num(), 312);
+        assert_eq!(rule.cut_card_cards, Some(78));
+        assert_eq!(rule.cut_card_jitter, 10);
+        assert_eq!(rule.burn_cards, 1);
+
+        // 切牌卡后面至少留有一局所需的牌
+        let fields = |builder: SGameRuleBuilder| -> Vec<ERuleField> {
+            builder.validate().iter().map(|error| error.field).collect()
+        };
+        assert_eq!(
+            fields(SGameRuleBuilder {
+                shuffle_threshold: Fraction::from(0),
+                ..Default::default()
+            }),
+            vec![ERuleField::ShuffleThreshold]
+        );
+        assert_eq!(
+            fields(SGameRuleBuilder {
+                cut_card_cards: Some(0),
+                ..Default::default()
+            }),
+            vec![ERuleField::CutCardCards]
+        );
+        assert_eq!(
+            fields(SGameRuleBuilder {
+                cut_card_jitter: 7,
+                ..Default::default()
+            }),
+            vec![ERuleField::ShuffleThreshold]
+        );
+        // 切牌卡和烧牌的牌数按规则的牌库大小校验 6副牌时合法的切牌卡在1副牌时不合法
+        assert!(fields(SGameRuleBuilder {
+            decks_num: 6,
+            cut_card_cards: Some(78),
+            ..Default::default()
+        })
+        .is_empty());
+        assert!(matches!(
+            SGameRule::from_toml_str("cut_card_cards = 78"),
+            Err(ERuleError::InvalidFields(errors)) if errors[0].field == ERuleField::CutCardCards
+        ));
+        assert_eq!(
+            fields(SGameRuleBuilder {
+                cut_card_cards: Some(40),
+                burn_cards: 10,
+                ..Default::default()
+            }),
+            vec![ERuleField::BurnCards]
+        );
+        // 移除10之后单副牌只有48张
+        assert_eq!(
+            fields(SGameRuleBuilder {
+                cut_card_cards: Some(46),
+                removed_cards: vec![ECardNumber::Ten],
+                ..Default::default()
+            }),
+            vec![ERuleField::CutCardCards]
+        );
+        assert!(SGameRule::from_json_str(r#"{"decks_num": 0}"#).is_err());
+    }
+
+    #[test]
+    fn test_shuffle_procedure() {
+        let rule = SGameRule::from_toml_str(
+            "[shuffle_procedure.Dealer]\nplug = true\nriffles = 3\nclumping = 0.2\nstrip_cuts = 1",
+        )
+        .unwrap();
+        assert_eq!(
+            rule.shuffle_procedure,
+            EShuffleProcedure::Dealer(SDealerShuffle {
+                riffles: 3,
+                clumping: 0.2,
+                ..Default::default()
+            })
+        );
+        assert_eq!(SGameRule::default().shuffle_procedure, EShuffleProcedure::Uniform);
+        assert!(SGameRule::from_toml_str(
+            "[shuffle_procedure.Dealer]\nplug = true\nriffles = 0\nclumping = 0.2\nstrip_cuts = 1",
+        )
+        .is_err());
+        // 直接构造的荷官洗牌参数同样需要校验
+        let builder = SGameRuleBuilder {
+            shuffle_procedure: EShuffleProcedure::Dealer(SDealerShuffle {
+                clumping: 2.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let fields: Vec<ERuleField> = builder.validate().iter().map(|error| error.field).collect();
+        assert_eq!(fields, vec![ERuleField::ShuffleProcedure]);
+    }
 }
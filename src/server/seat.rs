@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use super::card::ECard;
+use super::side_bet::SSideBetWager;
+use super::value::EValue;
+
+/// 座位 每个座位对应一个独立的玩家
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SSeat {
+    // 玩家筹码
+    pub player_chips: usize,
+    // 本局各spot的下注 发牌后清空
+    pub bets: Vec<usize>,
+    // 本局的边注 发牌后清空
+    pub side_bets: Vec<SSideBetWager>,
+}
+
+impl SSeat {
+    pub fn new(player_chips: usize) -> Self {
+        SSeat {
+            player_chips,
+            bets: Vec::new(),
+            side_bets: Vec::new(),
+        }
+    }
+}
+
+/// 单个座位的结算结果
+/// bet_chips和win_chips只包括主注 保险单独结算
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SSeatSettlement {
+    pub player_chips: usize,
+    pub bet_chips: usize,
+    pub win_chips: usize,
+    pub insurance_chips: usize,
+    pub insurance_win_chips: usize,
+}
+
+/// 单手牌的结算结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EHandOutcome {
+    Win,
+    Lose,
+    Push,
+    // blackjack获胜 按规则的blackjack赔率赔付
+    BlackJack,
+    // 玩家bust 无论庄家结果均失败
+    Bust,
+    Surrender,
+    // double down之后救援 输掉原始下注
+    Rescue,
+    // blackjack选择even money 按1:1赔付
+    EvenMoney,
+}
+
+/// 单手牌的结算详情 split产生的手牌分别结算
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SHandSettlement {
+    pub seat: usize,
+    pub spot: usize,
+    pub outcome: EHandOutcome,
+    // 主注 包括double和split追加的筹码 不包括保险和庄家出资的免费下注
+    pub wager: usize,
+    // 返还给玩家的筹码 包括退还的下注
+    pub payout: usize,
+    pub value: EValue,
+    pub cards: Vec<ECard>,
+}
@@ -0,0 +1,282 @@
+use super::card::{ECard, ECardColor, ECardNumber, ECardPoint};
+use super::rule::SFractionStr;
+use fraction::{Fraction, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use strum_macros::{Display, EnumIter};
+
+/// 边注类型
+#[derive(Debug, Copy, Clone, EnumIter, Hash, Display, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ESideBet {
+    /// 玩家前两张牌组成对子
+    PerfectPairs,
+    /// 玩家前两张牌和庄家明牌组成扑克牌型
+    TwentyOnePlusThree,
+    /// 玩家前两张牌合计20点
+    LuckyLadies,
+}
+
+/// 边注的中奖结果 未中奖时为None
+#[derive(Debug, Copy, Clone, EnumIter, Hash, Display, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ESideBetResult {
+    // Perfect Pairs 同花色对子
+    PerfectPair,
+    // Perfect Pairs 同颜色不同花色的对子
+    ColoredPair,
+    // Perfect Pairs 不同颜色的对子
+    MixedPair,
+    // 21+3 同花色三条
+    SuitedTrips,
+    // 21+3 同花顺
+    StraightFlush,
+    // 21+3 三条
+    ThreeOfAKind,
+    // 21+3 顺子 A可以作为最小或最大的牌
+    Straight,
+    // 21+3 同花
+    Flush,
+    // Lucky Ladies 一对红桃Q且庄家blackjack
+    QueenOfHeartsPairWithDealerBlackjack,
+    // Lucky Ladies 一对红桃Q
+    QueenOfHeartsPair,
+    // Lucky Ladies 相同点数和花色的20点
+    MatchedTwenty,
+    // Lucky Ladies 同花色的20点
+    SuitedTwenty,
+    // Lucky Ladies 任意20点
+    AnyTwenty,
+}
+
+/// 边注规则 pay_table中为各中奖结果的赔率 不在pay_table中的结果视为未中奖
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SSideBetRule {
+    pub side_bet: ESideBet,
+    #[serde_as(as = "Vec<(_, SFractionStr)>")]
+    pub pay_table: Vec<(ESideBetResult, Fraction)>,
+}
+
+/// 玩家在某个spot上的边注下注
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SSideBetWager {
+    pub spot: usize,
+    pub side_bet: ESideBet,
+    pub amount: usize,
+}
+
+/// 单个边注的结算结果 win_chips包括退还的下注
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SSideBetSettlement {
+    pub seat: usize,
+    pub spot: usize,
+    pub side_bet: ESideBet,
+    pub amount: usize,
+    pub result: Option<ESideBetResult>,
+    pub win_chips: usize,
+}
+
+// ESideBet impl ------------------------------------------------------------------------------------
+impl ESideBet {
+    /// 发牌后根据玩家前两张牌和庄家的牌判断边注结果
+    /// 无底牌规则下庄家只有一张牌 Lucky Ladies不会出现庄家blackjack的结果
+    pub fn settle(&self, player_cards: &[ECard; 2], dealer_cards: &[ECard]) -> Option<ESideBetResult> {
+        match self {
+            ESideBet::PerfectPairs => get_perfect_pairs_result(player_cards[0], player_cards[1]),
+            ESideBet::TwentyOnePlusThree => {
+                get_twenty_one_plus_three_result(player_cards[0], player_cards[1], dealer_cards[0])
+            }
+            ESideBet::LuckyLadies => {
+                let is_dealer_blackjack = dealer_cards.len() == 2
+                    && is_blackjack_pair(dealer_cards[0].value, dealer_cards[1].value);
+                get_lucky_ladies_result(player_cards[0], player_cards[1], is_dealer_blackjack)
+            }
+        }
+    }
+
+    /// 标准赔率表
+    pub fn default_pay_table(&self) -> Vec<(ESideBetResult, Fraction)> {
+        let pays: Vec<(ESideBetResult, u64)> = match self {
+            ESideBet::PerfectPairs => vec![
+                (ESideBetResult::PerfectPair, 25),
+                (ESideBetResult::ColoredPair, 12),
+                (ESideBetResult::MixedPair, 6),
+            ],
+            ESideBet::TwentyOnePlusThree => vec![
+                (ESideBetResult::SuitedTrips, 100),
+                (ESideBetResult::StraightFlush, 40),
+                (ESideBetResult::ThreeOfAKind, 30),
+                (ESideBetResult::Straight, 10),
+                (ESideBetResult::Flush, 5),
+            ],
+            ESideBet::LuckyLadies => vec![
+                (ESideBetResult::QueenOfHeartsPairWithDealerBlackjack, 1000),
+                (ESideBetResult::QueenOfHeartsPair, 125),
+                (ESideBetResult::MatchedTwenty, 19),
+                (ESideBetResult::SuitedTwenty, 9),
+                (ESideBetResult::AnyTwenty, 4),
+            ],
+        };
+        pays.into_iter()
+            .map(|(result, pay)| (result, Fraction::from(pay)))
+            .collect()
+    }
+}
+
+// SSideBetRule impl --------------------------------------------------------------------------------
+impl SSideBetRule {
+    pub fn new(side_bet: ESideBet) -> Self {
+        SSideBetRule {
+            side_bet,
+            pay_table: side_bet.default_pay_table(),
+        }
+    }
+
+    /// 获取中奖结果的赔率 不在赔率表中时为None
+    pub fn get_pay(&self, result: ESideBetResult) -> Option<Fraction> {
+        self.pay_table
+            .iter()
+            .find(|(r, _)| *r == result)
+            .map(|(_, pay)| *pay)
+    }
+
+    /// 计算边注的返还筹码 包括退还的下注 未中奖时为0
+    pub fn get_win(&self, amount: usize, result: Option<ESideBetResult>) -> usize {
+        match result.and_then(|result| self.get_pay(result)) {
+            Some(pay) => ((pay + Fraction::from(1)) * Fraction::from(amount))
+                .floor()
+                .to_usize()
+                .unwrap(),
+            None => 0,
+        }
+    }
+}
+
+// 结果判断 -----------------------------------------------------------------------------------------
+/// 判断花色是否为红色
+pub fn is_red(color: ECardColor) -> bool {
+    matches!(color, ECardColor::Hearts | ECardColor::Diamonds)
+}
+
+/// 判断三张牌的点数是否组成顺子 A可以作为最小(A23)或最大(QKA)的牌
+pub fn is_straight(values: [ECardNumber; 3]) -> bool {
+    let mut ranks = values.map(|value| value as usize);
+    ranks.sort();
+    (ranks[1] == ranks[0] + 1 && ranks[2] == ranks[1] + 1)
+        || ranks == [ECardNumber::Ace as usize, ECardNumber::Queen as usize, ECardNumber::King as usize]
+}
+
+/// 判断两张牌是否组成blackjack
+pub fn is_blackjack_pair(value1: ECardNumber, value2: ECardNumber) -> bool {
+    let point1: ECardPoint = value1.into();
+    let point2: ECardPoint = value2.into();
+    matches!(
+        (point1, point2),
+        (ECardPoint::Ace, ECardPoint::Ten) | (ECardPoint::Ten, ECardPoint::Ace)
+    )
+}
+
+/// 判断两张牌合计是否为20点 A计11点
+pub fn is_twenty(value1: ECardNumber, value2: ECardNumber) -> bool {
+    (value1 + value2).to_point() == 20
+}
+
+fn get_perfect_pairs_result(card1: ECard, card2: ECard) -> Option<ESideBetResult> {
+    if card1.value != card2.value {
+        None
+    } else if card1.color == card2.color {
+        Some(ESideBetResult::PerfectPair)
+    } else if is_red(card1.color) == is_red(card2.color) {
+        Some(ESideBetResult::ColoredPair)
+    } else {
+        Some(ESideBetResult::MixedPair)
+    }
+}
+
+fn get_twenty_one_plus_three_result(card1: ECard, card2: ECard, card3: ECard) -> Option<ESideBetResult> {
+    let is_flush = card1.color == card2.color && card2.color == card3.color;
+    let is_trips = card1.value == card2.value && card2.value == card3.value;
+    let is_straight = is_straight([card1.value, card2.value, card3.value]);
+    match (is_trips, is_straight, is_flush) {
+        (true, _, true) => Some(ESideBetResult::SuitedTrips),
+        (_, true, true) => Some(ESideBetResult::StraightFlush),
+        (true, _, false) => Some(ESideBetResult::ThreeOfAKind),
+        (_, true, false) => Some(ESideBetResult::Straight),
+        (_, _, true) => Some(ESideBetResult::Flush),
+        _ => None,
+    }
+}
+
+fn get_lucky_ladies_result(card1: ECard, card2: ECard, is_dealer_blackjack: bool) -> Option<ESideBetResult> {
+    if !is_twenty(card1.value, card2.value) {
+        return None;
+    }
+    let queen_of_hearts = ECard {
+        color: ECardColor::Hearts,
+        value: ECardNumber::Queen,
+    };
+    if card1 == queen_of_hearts && card2 == queen_of_hearts {
+        match is_dealer_blackjack {
+            true => Some(ESideBetResult::QueenOfHeartsPairWithDealerBlackjack),
+            false => Some(ESideBetResult::QueenOfHeartsPair),
+        }
+    } else if card1 == card2 {
+        Some(ESideBetResult::MatchedTwenty)
+    } else if card1.color == card2.color {
+        Some(ESideBetResult::SuitedTwenty)
+    } else {
+        Some(ESideBetResult::AnyTwenty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ESideBet, ESideBetResult, SSideBetRule};
+    use crate::server::card::{ECard, ECardColor, ECardNumber};
+
+    fn card(color: ECardColor, value: ECardNumber) -> ECard {
+        ECard { color, value }
+    }
+
+    #[test]
+    fn test_settle() {
+        let side_bet = ESideBet::PerfectPairs;
+        let h8 = card(ECardColor::Hearts, ECardNumber::Eight);
+        let d8 = card(ECardColor::Diamonds, ECardNumber::Eight);
+        let s8 = card(ECardColor::Spades, ECardNumber::Eight);
+        let s9 = card(ECardColor::Spades, ECardNumber::Nine);
+        assert_eq!(side_bet.settle(&[h8, h8], &[s9]), Some(ESideBetResult::PerfectPair));
+        assert_eq!(side_bet.settle(&[h8, d8], &[s9]), Some(ESideBetResult::ColoredPair));
+        assert_eq!(side_bet.settle(&[h8, s8], &[s9]), Some(ESideBetResult::MixedPair));
+        assert_eq!(side_bet.settle(&[h8, s9], &[s8]), None);
+
+        let side_bet = ESideBet::TwentyOnePlusThree;
+        let sa = card(ECardColor::Spades, ECardNumber::Ace);
+        let sk = card(ECardColor::Spades, ECardNumber::King);
+        let sq = card(ECardColor::Spades, ECardNumber::Queen);
+        let h7 = card(ECardColor::Hearts, ECardNumber::Seven);
+        assert_eq!(side_bet.settle(&[sa, sk], &[sq]), Some(ESideBetResult::StraightFlush));
+        assert_eq!(side_bet.settle(&[h8, s9], &[h7]), Some(ESideBetResult::Straight));
+        assert_eq!(side_bet.settle(&[h8, d8], &[s8]), Some(ESideBetResult::ThreeOfAKind));
+        assert_eq!(side_bet.settle(&[h8, h8], &[h8]), Some(ESideBetResult::SuitedTrips));
+        assert_eq!(side_bet.settle(&[sa, s9], &[s8]), Some(ESideBetResult::Flush));
+        assert_eq!(side_bet.settle(&[sa, h8], &[s9]), None);
+
+        let side_bet = ESideBet::LuckyLadies;
+        let hq = card(ECardColor::Hearts, ECardNumber::Queen);
+        let hk = card(ECardColor::Hearts, ECardNumber::King);
+        assert_eq!(
+            side_bet.settle(&[hq, hq], &[sa, sk]),
+            Some(ESideBetResult::QueenOfHeartsPairWithDealerBlackjack)
+        );
+        assert_eq!(side_bet.settle(&[hq, hq], &[sa, s9]), Some(ESideBetResult::QueenOfHeartsPair));
+        assert_eq!(side_bet.settle(&[sk, sk], &[sa]), Some(ESideBetResult::MatchedTwenty));
+        assert_eq!(side_bet.settle(&[hq, hk], &[sa]), Some(ESideBetResult::SuitedTwenty));
+        assert_eq!(side_bet.settle(&[sa, h8], &[s9]), None);
+        assert_eq!(side_bet.settle(&[sa, s9], &[s9]), Some(ESideBetResult::SuitedTwenty));
+
+        let rule = SSideBetRule::new(ESideBet::PerfectPairs);
+        assert_eq!(rule.get_win(2, Some(ESideBetResult::PerfectPair)), 52);
+        assert_eq!(rule.get_win(2, Some(ESideBetResult::Flush)), 0);
+        assert_eq!(rule.get_win(2, None), 0);
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::deck::EDeckSnapshot;
+use super::hand::dealer_hand::SDealerHand;
+use super::hand::player_hand::SPlayerHand;
+use super::rule::SGameRule;
+use super::seat::SSeat;
+use super::table::{ETableState, STable};
+use rand_chacha::ChaCha12Rng;
+
+/// 快照格式的版本 格式不兼容时递增
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// 牌桌快照 保存恢复牌局所需的全部状态
+/// 恢复后的牌桌与原牌桌的后续牌局完全相同
+#[derive(Clone, Serialize, Deserialize)]
+pub struct STableSnapshot {
+    pub version: u32,
+    pub state: ETableState,
+    pub rule: SGameRule,
+    pub dealer_hand: SDealerHand,
+    pub player_hands: Vec<SPlayerHand>,
+    pub seats: Vec<SSeat>,
+    pub current_seat: usize,
+    pub deck: EDeckSnapshot,
+    pub cut_card_remain: usize,
+    pub seed: u64,
+    pub rng: ChaCha12Rng,
+}
+
+#[derive(Debug)]
+pub enum ESnapshotError {
+    // 读写文件失败
+    IoError(std::io::Error),
+    // 文件格式错误
+    ParseError(String),
+    // 快照版本与当前版本不一致
+    VersionError(u32),
+}
+
+impl fmt::Display for ESnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ESnapshotError::IoError(e) => write!(f, "failed to access snapshot file: {e}"),
+            ESnapshotError::ParseError(e) => write!(f, "failed to parse snapshot: {e}"),
+            ESnapshotError::VersionError(version) => write!(
+                f,
+                "unsupported snapshot version {version}, expected {SNAPSHOT_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ESnapshotError {}
+
+impl STableSnapshot {
+    pub fn to_json_str(&self) -> Result<String, ESnapshotError> {
+        serde_json::to_string_pretty(self).map_err(|e| ESnapshotError::ParseError(e.to_string()))
+    }
+
+    /// 先检查版本 再解析完整的快照
+    pub fn from_json_str(s: &str) -> Result<Self, ESnapshotError> {
+        #[derive(Deserialize)]
+        struct SVersion {
+            version: u32,
+        }
+        let SVersion { version } =
+            serde_json::from_str(s).map_err(|e| ESnapshotError::ParseError(e.to_string()))?;
+        if version != SNAPSHOT_VERSION {
+            return Err(ESnapshotError::VersionError(version));
+        }
+        serde_json::from_str(s).map_err(|e| ESnapshotError::ParseError(e.to_string()))
+    }
+}
+
+impl STable {
+    pub fn snapshot(&self) -> STableSnapshot {
+        STableSnapshot {
+            version: SNAPSHOT_VERSION,
+            state: self.state.clone(),
+            rule: self.rule.clone(),
+            dealer_hand: self.dealer_hand.clone(),
+            player_hands: self.player_hands.clone(),
+            seats: self.seats.clone(),
+            current_seat: self.current_seat,
+            deck: self.deck.snapshot(),
+            cut_card_remain: self.cut_card_remain,
+            seed: self.seed,
+            rng: self.rng.clone(),
+        }
+    }
+
+    pub fn from_snapshot(snapshot: STableSnapshot) -> Self {
+        STable {
+            state: snapshot.state,
+            rule: snapshot.rule,
+            dealer_hand: snapshot.dealer_hand,
+            player_hands: snapshot.player_hands,
+            seats: snapshot.seats,
+            current_seat: snapshot.current_seat,
+            deck: snapshot.deck.into_deck(),
+            cut_card_remain: snapshot.cut_card_remain,
+            seed: snapshot.seed,
+            rng: snapshot.rng,
+            history_recorder: None,
+        }
+    }
+
+    /// 保存牌桌快照到JSON文件
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ESnapshotError> {
+        let s = self.snapshot().to_json_str()?;
+        std::fs::write(path, s).map_err(ESnapshotError::IoError)
+    }
+
+    /// 从JSON文件恢复牌桌
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ESnapshotError> {
+        let s = std::fs::read_to_string(path).map_err(ESnapshotError::IoError)?;
+        Ok(STable::from_snapshot(STableSnapshot::from_json_str(&s)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::deck::shuffle::{EShuffleProcedure, SDealerShuffle};
+    use super::super::deck::queue_deck::SQueueDeck;
+    use super::super::hand::dealer_hand::SDealerHand;
+    use super::super::hand::player_hand::SPlayerHand;
+    use super::super::player::static_strategy_player::SStaticStrategyPlayer;
+    use super::super::rule::SGameRule;
+    use super::super::table::{ETableState, STable};
+    use super::{ESnapshotError, STableSnapshot, SNAPSHOT_VERSION};
+
+    /// 继续进行若干局 记录每次操作的结果
+    fn play(table: &mut STable, steps: usize) -> Vec<String> {
+        let player = SStaticStrategyPlayer::new();
+        (0..steps)
+            .map(|_| format!("{:?}", table.receive_player_action(player.action(table))))
+            .collect()
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let rule = SGameRule {
+            cut_card_jitter: 3,
+            ..Default::default()
+        };
+        let deck = SQueueDeck::new(1, 7).with_shuffle_procedure(EShuffleProcedure::Dealer(SDealerShuffle::default()));
+        let mut table = STable::new(rule, SDealerHand::new(), vec![SPlayerHand::new()], 10000, Box::new(deck));
+        // 停在牌局中间
+        play(&mut table, 23);
+        while matches!(table.get_state(), ETableState::PlayerBet) {
+            play(&mut table, 1);
+        }
+
+        let s = table.snapshot().to_json_str().unwrap();
+        let mut restored = STable::from_snapshot(STableSnapshot::from_json_str(&s).unwrap());
+        assert_eq!(restored.get_state(), table.get_state());
+        assert_eq!(restored.seed, 7);
+        assert_eq!(restored.remain_cards_num(), table.remain_cards_num());
+        assert_eq!(restored.get_point_probability_map(), table.get_point_probability_map());
+        assert_eq!(play(&mut restored, 500), play(&mut table, 500));
+        assert_eq!(restored.seats[0].player_chips, table.seats[0].player_chips);
+    }
+
+    #[test]
+    fn test_snapshot_version() {
+        let table = STable::new_csm_deck(20, SGameRule { decks_num: 2, ..Default::default() }, 0);
+        let mut snapshot = table.snapshot();
+        assert_eq!(snapshot.version, SNAPSHOT_VERSION);
+        snapshot.version = SNAPSHOT_VERSION + 1;
+        let s = snapshot.to_json_str().unwrap();
+        assert!(matches!(
+            STableSnapshot::from_json_str(&s),
+            Err(ESnapshotError::VersionError(version)) if version == SNAPSHOT_VERSION + 1
+        ));
+        assert!(matches!(
+            STableSnapshot::from_json_str("{}"),
+            Err(ESnapshotError::ParseError(_))
+        ));
+    }
+}
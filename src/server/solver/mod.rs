@@ -1,3 +1,3 @@
-mod static_solver;
+pub mod static_solver;
 mod dynamic_solver;
-mod strategy_analyzer;
\ No newline at end of file
+pub mod strategy_analyzer;
\ No newline at end of file
@@ -4,6 +4,7 @@ use strum::IntoEnumIterator;
 use tabled::Tabled;
 use super::super::super::super::card::ECardPoint;
 use super::super::super::super::deck::TDeck;
+use super::super::super::super::rule::SGameRule;
 use super::dealer_value_final_distribution::EDealerFinalValue;
 use super::dealer_value_final_distribution;
 
@@ -15,35 +16,52 @@ pub struct SDealerFirstFinalDistributionCell {
     pub p19: Fraction,
     pub p20: Fraction,
     pub p21: Fraction,
+    pub p22: Fraction,
     pub bust: Fraction,
     pub blackjack: Fraction,
 }
 
 pub type SDealerFirstFinalDistribution = Vec<SDealerFirstFinalDistributionCell>;
 
-pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>) -> HashMap<ECardPoint, HashMap::<EDealerFinalValue, Fraction>> {
+/// 庄家明牌为first_card_point时 庄家blackjack的概率
+pub fn get_blackjack_probability(probability_map: &HashMap<ECardPoint, Fraction>, first_card_point: ECardPoint) -> Fraction {
+    match first_card_point {
+        ECardPoint::Ace => probability_map.get(&ECardPoint::Ten).unwrap().clone(),
+        ECardPoint::Ten => probability_map.get(&ECardPoint::Ace).unwrap().clone(),
+        _ => Fraction::new(0u64, 1u64),
+    }
+}
+
+/// 庄家明牌对应的最终点数分布
+/// 庄家peek时已检查blackjack 玩家行动时庄家必然不是blackjack 分布以此为条件
+/// 庄家不peek(包括无底牌ENHC)时玩家行动时不知道庄家是否blackjack 使用无条件分布
+pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<ECardPoint, HashMap::<EDealerFinalValue, Fraction>> {
     let mut map = HashMap::<ECardPoint, HashMap::<EDealerFinalValue, Fraction>>::new();
-    let value_final_map = dealer_value_final_distribution::get_map(probability_map);
+    let value_final_map = dealer_value_final_distribution::get_map(probability_map, rule);
     for card_point in ECardPoint::iter() {
         
         let mut m = value_final_map.get(&card_point.into()).unwrap().clone();
-        let black_jack = match card_point {
-            ECardPoint::Ace => probability_map.get(&ECardPoint::Ten).unwrap().clone(),
-            ECardPoint::Ten => probability_map.get(&ECardPoint::Ace).unwrap().clone(),
-            _ => Fraction::new(0u64, 1u64),
-        };
+        let black_jack = get_blackjack_probability(probability_map, card_point);
         m.insert(EDealerFinalValue::Blackjack, black_jack);
         m.insert(EDealerFinalValue::P21,
                  *m.get(&EDealerFinalValue::P21).unwrap_or(&Fraction::new(0u64, 1u64)) - black_jack
         );
+        if rule.is_dealer_peek() && black_jack < Fraction::from(1u64) {
+            // 以庄家非blackjack为条件
+            let not_black_jack = Fraction::from(1u64) - black_jack;
+            for (_, prob) in m.iter_mut() {
+                *prob /= not_black_jack;
+            }
+            m.insert(EDealerFinalValue::Blackjack, Fraction::new(0u64, 1u64));
+        }
         map.insert(card_point, m);
     }
     map
 }
 
-pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>) -> SDealerFirstFinalDistribution {
+pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> SDealerFirstFinalDistribution {
     let mut table = Vec::<SDealerFirstFinalDistributionCell>::new();
-    let map = get_map(probability_map);
+    let map = get_map(probability_map, rule);
     for card_point in ECardPoint::iter() {
         let m = map.get(&card_point.into()).unwrap();
         table.push(SDealerFirstFinalDistributionCell {
@@ -53,6 +71,7 @@ pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>) -> SDealerFirs
             p19: m.get(&EDealerFinalValue::P19).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
             p20: m.get(&EDealerFinalValue::P20).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
             p21: m.get(&EDealerFinalValue::P21).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
+            p22: m.get(&EDealerFinalValue::P22).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
             bust: m.get(&EDealerFinalValue::Bust).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
             blackjack: m.get(&EDealerFinalValue::Blackjack).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
         })
@@ -66,16 +85,19 @@ mod test {
     use tabled::Table;
     use super::super::super::super::super::deck::random_deck::SRandomDeck;
     use super::super::super::super::super::deck::TDeck;
+    use super::super::super::super::super::rule::{EDealerSoft17Rule, EHoleCardRule, SGameRule};
+    use super::super::super::super::super::card::ECardPoint;
     use super::super::dealer_first_final_distribution::get_table;
 
     #[test]
     fn test1() {
-        let table = Table::new(get_table(SRandomDeck::new().get_point_probability_map())).to_string();
+        let rule = SGameRule::default();
+        let table = Table::new(get_table(SRandomDeck::new(0).get_point_probability_map(), &rule)).to_string();
         println!("{}", &table);
-        for row in get_table(SRandomDeck::new().get_point_probability_map()) {
+        for row in get_table(SRandomDeck::new(0).get_point_probability_map(), &rule) {
             print!("Check {}:", row.first_card_point);
 
-            // let sum = row.blackjack + row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.bust;
+            // let sum = row.blackjack + row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.p22 + row.bust;
             // if (Fraction::new(1u64, 1u64) == sum) {
             //     println!("pass");
             // } else {
@@ -83,9 +105,47 @@ mod test {
             // }
 
             assert_eq!(Fraction::new(1u64, 1u64),
-                       row.blackjack + row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.bust
+                       row.blackjack + row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.p22 + row.bust
             );
             println!("pass");
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_h17() {
+        let mut rule = SGameRule::default();
+        rule.dealer_soft_17 = EDealerSoft17Rule::Hit;
+        let table = get_table(SRandomDeck::new(0).get_point_probability_map(), &rule);
+        println!("{}", Table::new(&table).to_string());
+        for row in table {
+            assert_eq!(Fraction::new(1u64, 1u64),
+                       row.blackjack + row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.p22 + row.bust
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_hole_card() {
+        let mut rule = SGameRule::default();
+        let deck = SRandomDeck::new(0);
+        // 庄家已peek
+        for row in get_table(deck.get_point_probability_map(), &rule) {
+            assert_eq!(Fraction::new(0u64, 1u64), row.blackjack);
+        }
+
+        rule.hole_card = EHoleCardRule::NoHoleCard;
+        let table = get_table(deck.get_point_probability_map(), &rule);
+        println!("{}", Table::new(&table).to_string());
+        for row in table {
+            let blackjack = match row.first_card_point {
+                ECardPoint::Ace => Fraction::new(4u64, 13u64),
+                ECardPoint::Ten => Fraction::new(1u64, 13u64),
+                _ => Fraction::new(0u64, 1u64),
+            };
+            assert_eq!(blackjack, row.blackjack);
+            assert_eq!(Fraction::new(1u64, 1u64),
+                       row.blackjack + row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.p22 + row.bust
+            );
+        }
+    }
+}
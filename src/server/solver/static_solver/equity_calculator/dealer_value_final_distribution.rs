@@ -8,6 +8,7 @@ use strum_macros::{Display, EnumIter};
 use tabled::Tabled;
 use super::super::super::super::card::ECardPoint;
 use super::super::super::super::deck::TDeck;
+use super::super::super::super::rule::SGameRule;
 use super::value_hit_distribution;
 use super::super::super::super::value::EValue;
 
@@ -19,6 +20,7 @@ pub struct SDealerValueFinalDistributionCell {
     pub p19: Fraction,
     pub p20: Fraction,
     pub p21: Fraction,
+    pub p22: Fraction,
     pub bust: Fraction,
 }
 pub type SDealerValueFinalDistribution = Vec<SDealerValueFinalDistributionCell>;
@@ -32,6 +34,8 @@ pub enum EDealerFinalValue {
     P20,
     P21,
     Blackjack,
+    // 庄家22点 庄家22点平局规则下单独统计 不计入Bust
+    P22,
     Bust,
 }
 
@@ -45,6 +49,7 @@ impl EDealerFinalValue {
             EDealerFinalValue::P20 => 20,
             EDealerFinalValue::P21 => 21,
             EDealerFinalValue::Blackjack => 22,
+            EDealerFinalValue::P22 => 22,
             EDealerFinalValue::Bust => 1,
         }
     }
@@ -64,12 +69,25 @@ impl From<EValue> for EDealerFinalValue {
     }
 }
 
+/// 庄家是否在该value停牌 软17是否停牌由规则决定
 #[inline]
-fn is_final_value(value: EValue) -> bool {
-    EDealerFinalValue::from(value) != EDealerFinalValue::None
+fn is_final_value(value: EValue, rule: &SGameRule) -> bool {
+    EDealerFinalValue::from(value) != EDealerFinalValue::None && rule.is_dealer_stop(value)
 }
 
-pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>) -> HashMap<EValue, HashMap::<EDealerFinalValue, Fraction>> {
+/// 庄家硬牌value拿一张牌后恰好为22点的概率
+fn get_p22_probability(probability_map: &HashMap<ECardPoint, Fraction>, value: EValue) -> Fraction {
+    let mut p22 = Fraction::new(0u64, 1u64);
+    for (card_point, prob) in probability_map {
+        // A计1点时 需要21点才能到达22点 而21点必然停牌
+        if *card_point != ECardPoint::Ace && value.to_point() + Into::<EValue>::into(*card_point).to_point() == 22 {
+            p22 += *prob;
+        }
+    }
+    p22
+}
+
+pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<EValue, HashMap::<EDealerFinalValue, Fraction>> {
     let mut map = HashMap::<EValue, HashMap::<EDealerFinalValue, Fraction>>::new();
     let hit_map = value_hit_distribution::get_map(probability_map);
     // println!("hit_map:{:?}", &hit_map);
@@ -78,7 +96,7 @@ pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>) -> HashMap<EValu
             EValue::None => {}
             _ => {
                 let mut m = HashMap::<EDealerFinalValue, Fraction>::new();
-                if is_final_value(value.clone()) {
+                if is_final_value(value.clone(), rule) {
                     m.insert(value.into(), Fraction::from(1u64));
                 } else {
                     let transfer_map = hit_map.get(&value).unwrap();
@@ -103,6 +121,17 @@ pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>) -> HashMap<EValu
                             }
                         }
                     }
+                    // 庄家22点平局规则下 硬牌拿牌后直接到达22点的概率从Bust中分出
+                    // 拿牌后继续拿牌到达22点的概率已包含在后续value的分布中
+                    // 软牌拿一张牌不会bust 因此只需考虑硬牌
+                    if rule.dealer_22_push && !value.is_soft() {
+                        let p22 = get_p22_probability(probability_map, value);
+                        let zero = Fraction::new(0u64, 1u64);
+                        let bust = *m.get(&EDealerFinalValue::Bust).unwrap_or(&zero);
+                        let inherited_p22 = *m.get(&EDealerFinalValue::P22).unwrap_or(&zero);
+                        m.insert(EDealerFinalValue::Bust, bust - p22);
+                        m.insert(EDealerFinalValue::P22, inherited_p22 + p22);
+                    }
                 }
                 // println!("m:{:?}", &m);
                 map.insert(value, m);
@@ -112,9 +141,9 @@ pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>) -> HashMap<EValu
     map
 }
 
-pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>) -> SDealerValueFinalDistribution {
+pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> SDealerValueFinalDistribution {
     let mut table = Vec::<SDealerValueFinalDistributionCell>::new();
-    let map = get_map(probability_map);
+    let map = get_map(probability_map, rule);
     for value in EValue::iter() {
         match value {
             EValue::None => {}
@@ -127,6 +156,7 @@ pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>) -> SDealerValu
                     p19: m.get(&EDealerFinalValue::P19).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
                     p20: m.get(&EDealerFinalValue::P20).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
                     p21: m.get(&EDealerFinalValue::P21).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
+                    p22: m.get(&EDealerFinalValue::P22).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
                     bust: m.get(&EDealerFinalValue::Bust).unwrap_or(&Fraction::new(0u64, 1u64)).clone(),
                 })
             }
@@ -141,23 +171,65 @@ mod test {
     use tabled::{Table};
     use super::super::super::super::super::deck::random_deck::SRandomDeck;
     use super::super::super::super::super::deck::TDeck;
+    use super::super::super::super::super::rule::{EDealerSoft17Rule, SGameRule};
+    use super::super::super::super::super::value::EValue;
     use super::super::dealer_value_final_distribution::get_table;
     #[test]
     fn test_table() {
-        let table = Table::new(get_table(SRandomDeck::new().get_point_probability_map())).to_string();
+        let rule = SGameRule::default();
+        let table = Table::new(get_table(SRandomDeck::new(0).get_point_probability_map(), &rule)).to_string();
         println!("{table}");
-        for row in get_table(SRandomDeck::new().get_point_probability_map()) {
+        for row in get_table(SRandomDeck::new(0).get_point_probability_map(), &rule) {
             print!("Check {}:", row.value);
-            // let sum = row.blackjack + row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.bust;
+            // let sum = row.blackjack + row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.p22 + row.bust;
             // if (Fraction::new(1u64, 1u64) == sum) {
             //     println!("pass");
             // } else {
             //     println!("fail: {}", sum);
             // }
             assert_eq!(Fraction::new(1u64, 1u64),
-                       row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.bust
+                       row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.p22 + row.bust
             );
             println!("pass");
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_table_h17() {
+        let mut rule = SGameRule::default();
+        rule.dealer_soft_17 = EDealerSoft17Rule::Hit;
+        let table = get_table(SRandomDeck::new(0).get_point_probability_map(), &rule);
+        for row in &table {
+            assert_eq!(Fraction::new(1u64, 1u64),
+                       row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.p22 + row.bust
+            );
+        }
+        // H17下 软17不再是终止状态
+        let s17 = table.iter().find(|row| row.value == EValue::S17).unwrap();
+        assert!(s17.p17 < Fraction::new(1u64, 1u64));
+        assert!(s17.bust > Fraction::new(0u64, 1u64));
+        let h17 = table.iter().find(|row| row.value == EValue::H17).unwrap();
+        assert_eq!(Fraction::new(1u64, 1u64), h17.p17);
+    }
+
+    #[test]
+    fn test_table_22_push() {
+        let mut rule = SGameRule::default();
+        let bust_table = get_table(SRandomDeck::new(0).get_point_probability_map(), &rule);
+        rule.dealer_22_push = true;
+        let table = get_table(SRandomDeck::new(0).get_point_probability_map(), &rule);
+        for (row, bust_row) in table.iter().zip(bust_table.iter()) {
+            assert_eq!(Fraction::new(1u64, 1u64),
+                       row.p17 + row.p18 + row.p19 + row.p20 + row.p21 + row.p22 + row.bust
+            );
+            // 22点只是从bust中分出 其余点数的概率不变
+            assert_eq!(bust_row.bust, row.p22 + row.bust);
+            assert_eq!(bust_row.p17, row.p17);
+        }
+        // 硬16拿到6点牌时为22点
+        let h16 = table.iter().find(|row| row.value == EValue::H16).unwrap();
+        assert_eq!(Fraction::new(1u64, 13u64), h16.p22);
+        let h12 = table.iter().find(|row| row.value == EValue::H12).unwrap();
+        assert!(h12.p22 > Fraction::new(0u64, 1u64));
+    }
+}
@@ -1,9 +1,11 @@
 // 计算各操作的EV
 
 mod first_card_distribution;
-mod dealer_first_final_distribution;
-mod player_value_stand_equity;
-mod value_hit_distribution;
-mod player_value_hit_equity;
+pub mod dealer_first_final_distribution;
+pub mod player_value_stand_equity;
+pub mod value_hit_distribution;
+pub mod player_value_hit_equity;
 mod player_split_equity;
-mod dealer_value_final_distribution;
\ No newline at end of file
+pub mod player_value_action_equity;
+mod dealer_value_final_distribution;
+pub mod side_bet_equity;
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use fraction::Fraction;
+use strum::IntoEnumIterator;
+use tabled::Tabled;
+
+use super::super::super::super::card::ECardPoint;
+use super::super::super::super::rule::{ESurrenderRule, SGameRule};
+use super::super::super::super::value::EValue;
+use super::super::strategy::hit_stand_strategy;
+use super::{dealer_first_final_distribution, player_value_stand_equity, value_hit_distribution};
+
+/// double down之后手牌的张数
+const DOUBLE_CARDS_NUM: usize = 3;
+
+/// 玩家在某一value下 面对庄家明牌时各操作的期望
+#[derive(Clone, Debug)]
+pub struct SPlayerActionEquity {
+    pub stand: Fraction,
+    // 拿牌后按最优策略继续拿牌或停牌的期望
+    pub hit: Fraction,
+    // 规则不允许double down时为None
+    pub double: Option<Fraction>,
+    // 规则不允许投降时为None
+    pub surrender: Option<Fraction>,
+}
+
+#[derive(Tabled)]
+pub struct SPlayerValueActionEquityCell {
+    pub value: EValue,
+    pub dealer_card_point: ECardPoint,
+    pub stand: Fraction,
+    pub hit: Fraction,
+    #[tabled(display_with = "display_option")]
+    pub double: Option<Fraction>,
+    #[tabled(display_with = "display_option")]
+    pub surrender: Option<Fraction>,
+}
+
+pub type SPlayerValueActionEquity = Vec<SPlayerValueActionEquityCell>;
+
+fn display_option(value: &Option<Fraction>) -> String {
+    match value {
+        Some(x) => x.to_string(),
+        None => String::from("-"),
+    }
+}
+
+/// Charlie自动获胜的期望 庄家blackjack时失败 庄家peek时玩家行动前已排除庄家blackjack
+pub fn get_charlie_equity(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule, dealer_card_point: ECardPoint) -> Fraction {
+    let dealer_blackjack = match rule.is_dealer_peek() {
+        true => Fraction::from(0),
+        false => dealer_first_final_distribution::get_blackjack_probability(probability_map, dealer_card_point),
+    };
+    rule.charlie_pay * (Fraction::from(1) - dealer_blackjack) - dealer_blackjack
+}
+
+/// double down期望
+/// 下注翻倍后只拿一张牌 即拿一张牌后stand期望的两倍 拿牌后达到Charlie张数且未bust时按Charlie获胜
+/// 无底牌OBO规则下 庄家blackjack时退还追加的下注 期望增加庄家blackjack的概率
+/// 允许救援时 拿牌后未bust的手牌可以只输掉原始下注 即每种结果的期望至少为-1
+/// 手牌value不符合规则时为None
+pub fn get_double_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<EValue, HashMap::<ECardPoint, Option<Fraction>>> {
+    let mut map = HashMap::<EValue, HashMap::<ECardPoint, Option<Fraction>>>::new();
+    let hit_map = get_double_hit_map(probability_map, rule);
+    for player_value in EValue::iter() {
+        if player_value == EValue::None {
+            continue;
+        }
+        let mut m = HashMap::<ECardPoint, Option<Fraction>>::new();
+        for dealer_card_point in ECardPoint::iter() {
+            let equity = match rule.check_double_down(player_value) {
+                true => {
+                    let hit = hit_map.get(&player_value).unwrap().get(&dealer_card_point).unwrap_or(&Fraction::new(0u64, 1u64)).clone();
+                    let refund = match rule.is_original_bets_only() {
+                        true => dealer_first_final_distribution::get_blackjack_probability(probability_map, dealer_card_point),
+                        false => Fraction::new(0u64, 1u64),
+                    };
+                    Some(hit * Fraction::from(2u64) + refund)
+                }
+                false => None,
+            };
+            m.insert(dealer_card_point, equity);
+        }
+        map.insert(player_value, m);
+    }
+    map
+}
+
+/// double拿一张牌后stand、Charlie获胜或救援的期望(以翻倍后下注的一半为单位)
+/// Charlie获胜的手牌不会救援 允许救援时损失一半翻倍后的下注
+fn get_double_hit_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<EValue, HashMap::<ECardPoint, Fraction>> {
+    let mut map = HashMap::<EValue, HashMap::<ECardPoint, Fraction>>::new();
+    let stand_map = player_value_stand_equity::get_map(probability_map, rule);
+    let value_hit_map = value_hit_distribution::get_map(probability_map);
+    let rescue = -Fraction::new(1u64, 2u64);
+    let is_charlie = rule.check_charlie(DOUBLE_CARDS_NUM);
+    for player_value in EValue::iter() {
+        if player_value == EValue::None {
+            continue;
+        }
+        let mut m = HashMap::<ECardPoint, Fraction>::new();
+        for (after_hit_value, hit_prob) in value_hit_map.get(&player_value).unwrap() {
+            let equity_map = stand_map.get(after_hit_value).unwrap();
+            for dealer_card_point in ECardPoint::iter() {
+                let stand = *equity_map.get(&dealer_card_point).unwrap();
+                let equity = match *after_hit_value {
+                    EValue::Bust => stand,
+                    _ if is_charlie => get_charlie_equity(probability_map, rule, dealer_card_point),
+                    _ if rule.double_down_rescue && stand < rescue => rescue,
+                    _ => stand,
+                };
+                *m.entry(dealer_card_point).or_insert(Fraction::from(0)) += *hit_prob * equity;
+            }
+        }
+        map.insert(player_value, m);
+    }
+    map
+}
+
+/// 投降期望
+/// 早投降：直接损失一半下注
+/// 晚投降：庄家blackjack时损失全部下注 否则损失一半下注
+/// 庄家peek时已检查blackjack 晚投降同样只损失一半下注
+pub fn get_surrender_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<ECardPoint, Option<Fraction>> {
+    let mut map = HashMap::<ECardPoint, Option<Fraction>>::new();
+    for dealer_card_point in ECardPoint::iter() {
+        let equity = match rule.surrender {
+            ESurrenderRule::None => None,
+            ESurrenderRule::Early => Some(-Fraction::new(1u64, 2u64)),
+            ESurrenderRule::Late => {
+                let blackjack = match rule.is_dealer_peek() {
+                    true => Fraction::new(0u64, 1u64),
+                    false => dealer_first_final_distribution::get_blackjack_probability(probability_map, dealer_card_point),
+                };
+                Some(-(Fraction::from(1u64) + blackjack) / Fraction::from(2u64))
+            }
+        };
+        map.insert(dealer_card_point, equity);
+    }
+    map
+}
+
+pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<EValue, HashMap::<ECardPoint, SPlayerActionEquity>> {
+    let mut map = HashMap::<EValue, HashMap::<ECardPoint, SPlayerActionEquity>>::new();
+    let stand_map = player_value_stand_equity::get_map(probability_map, rule);
+    // 拿牌的期望按Charlie规则随张数变化 使用拿牌停牌策略的递归计算
+    let hit_stand_map = hit_stand_strategy::get_map(probability_map, rule);
+    let double_map = get_double_map(probability_map, rule);
+    let surrender_map = get_surrender_map(probability_map, rule);
+    for player_value in EValue::iter() {
+        if player_value == EValue::None {
+            continue;
+        }
+        let mut m = HashMap::<ECardPoint, SPlayerActionEquity>::new();
+        for dealer_card_point in ECardPoint::iter() {
+            let stand = *stand_map.get(&player_value).unwrap().get(&dealer_card_point).unwrap_or(&Fraction::new(0u64, 1u64));
+            // bust之后无法拿牌 期望与停牌相同
+            let hit = match hit_stand_map.get(&player_value) {
+                Some(equity_map) => Fraction::from(equity_map.get(&dealer_card_point).unwrap().hit),
+                None => stand,
+            };
+            m.insert(dealer_card_point, SPlayerActionEquity {
+                stand,
+                hit,
+                double: double_map.get(&player_value).unwrap().get(&dealer_card_point).unwrap().clone(),
+                surrender: surrender_map.get(&dealer_card_point).unwrap().clone(),
+            });
+        }
+        map.insert(player_value, m);
+    }
+    map
+}
+
+pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> SPlayerValueActionEquity {
+    let mut table = Vec::<SPlayerValueActionEquityCell>::new();
+    let map = get_map(probability_map, rule);
+    for player_value in EValue::iter().rev() {
+        if player_value == EValue::None {
+            continue;
+        }
+        let equity_map = map.get(&player_value).unwrap();
+        for dealer_card_point in ECardPoint::iter() {
+            let equity = equity_map.get(&dealer_card_point).unwrap().clone();
+            table.push(SPlayerValueActionEquityCell {
+                value: player_value,
+                dealer_card_point,
+                stand: equity.stand,
+                hit: equity.hit,
+                double: equity.double,
+                surrender: equity.surrender,
+            })
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use fraction::{Fraction, ToPrimitive};
+    use tabled::Table;
+
+    use super::super::super::super::super::card::ECardPoint;
+    use super::super::super::super::super::deck::random_deck::SRandomDeck;
+    use super::super::super::super::super::deck::TDeck;
+    use super::super::super::super::super::rule::{EDoubleDownRule, EHoleCardRule, ESurrenderRule, SGameRule};
+    use super::super::super::super::super::value::EValue;
+    use super::super::player_value_action_equity::{get_double_map, get_map, get_surrender_map, get_table};
+
+    #[test]
+    fn test1() {
+        let mut rule = SGameRule::default();
+        rule.surrender = ESurrenderRule::Late;
+        let table = Table::new(get_table(SRandomDeck::new(0).get_point_probability_map(), &rule)).to_string();
+        println!("{}", &table);
+    }
+
+    #[test]
+    fn test_surrender() {
+        let deck = SRandomDeck::new(0);
+        let mut rule = SGameRule::default();
+        let map = get_surrender_map(deck.get_point_probability_map(), &rule);
+        assert_eq!(None, *map.get(&ECardPoint::Ten).unwrap());
+
+        rule.surrender = ESurrenderRule::Early;
+        let map = get_surrender_map(deck.get_point_probability_map(), &rule);
+        assert_eq!(Some(-Fraction::new(1u64, 2u64)), *map.get(&ECardPoint::Ten).unwrap());
+
+        // 晚投降 庄家peek时已检查blackjack
+        rule.surrender = ESurrenderRule::Late;
+        let map = get_surrender_map(deck.get_point_probability_map(), &rule);
+        assert_eq!(Some(-Fraction::new(1u64, 2u64)), *map.get(&ECardPoint::Ten).unwrap());
+
+        // 晚投降 无底牌时庄家明牌为10 有1/13的概率为blackjack
+        rule.hole_card = EHoleCardRule::NoHoleCard;
+        let map = get_surrender_map(deck.get_point_probability_map(), &rule);
+        assert_eq!(Some(-Fraction::new(7u64, 13u64)), *map.get(&ECardPoint::Ten).unwrap());
+        assert_eq!(Some(-Fraction::new(1u64, 2u64)), *map.get(&ECardPoint::Six).unwrap());
+    }
+
+    #[test]
+    fn test_double() {
+        let deck = SRandomDeck::new(0);
+        let mut rule = SGameRule::default();
+        let map = get_double_map(deck.get_point_probability_map(), &rule);
+        assert!(map.get(&EValue::H16).unwrap().get(&ECardPoint::Ten).unwrap().is_some());
+
+        rule.double_down = EDoubleDownRule::NineToEleven;
+        let map = get_double_map(deck.get_point_probability_map(), &rule);
+        assert!(map.get(&EValue::H16).unwrap().get(&ECardPoint::Ten).unwrap().is_none());
+        assert!(map.get(&EValue::H11).unwrap().get(&ECardPoint::Ten).unwrap().is_some());
+
+        // OBO规则下 庄家blackjack时退还追加的下注
+        rule.hole_card = EHoleCardRule::NoHoleCard;
+        let map = get_double_map(deck.get_point_probability_map(), &rule);
+        let double = map.get(&EValue::H11).unwrap().get(&ECardPoint::Ten).unwrap().unwrap();
+        rule.hole_card = EHoleCardRule::NoHoleCardOBO;
+        let map = get_double_map(deck.get_point_probability_map(), &rule);
+        let double_obo = map.get(&EValue::H11).unwrap().get(&ECardPoint::Ten).unwrap().unwrap();
+        assert_eq!(double + Fraction::new(1u64, 13u64), double_obo);
+
+        // 救援只会提高double的期望
+        rule.hole_card = EHoleCardRule::HoleCard;
+        rule.double_down = EDoubleDownRule::Any;
+        let map = get_double_map(deck.get_point_probability_map(), &rule);
+        rule.double_down_rescue = true;
+        let map_rescue = get_double_map(deck.get_point_probability_map(), &rule);
+        let get = |map: &HashMap<EValue, HashMap<ECardPoint, Option<Fraction>>>, value| {
+            map.get(&value).unwrap().get(&ECardPoint::Ten).unwrap().unwrap()
+        };
+        assert!(get(&map_rescue, EValue::H11) > get(&map, EValue::H11));
+        assert!(get(&map_rescue, EValue::H12) > get(&map, EValue::H12));
+        // 硬20拿牌后只能是21点或bust 不会救援
+        assert_eq!(get(&map_rescue, EValue::H20), get(&map, EValue::H20));
+    }
+
+    /// 三张牌Charlie double或拿牌后只要不bust即获胜
+    #[test]
+    fn test_charlie() {
+        let deck = SRandomDeck::new(0);
+        let mut rule = SGameRule {
+            charlie_cards: Some(3),
+            ..Default::default()
+        };
+        // H12拿一张牌 只有10点牌bust
+        let map = get_double_map(deck.get_point_probability_map(), &rule);
+        let double = map.get(&EValue::H12).unwrap().get(&ECardPoint::Six).unwrap().unwrap();
+        assert_eq!(double, Fraction::new(10u64, 13u64));
+        let map = get_map(deck.get_point_probability_map(), &rule);
+        let equity = map.get(&EValue::H12).unwrap().get(&ECardPoint::Six).unwrap();
+        assert!((equity.hit.to_f64().unwrap() - 5f64 / 13f64).abs() < 1e-9);
+        assert!(equity.hit > equity.stand);
+
+        // Charlie获胜的手牌不会救援
+        rule.double_down_rescue = true;
+        let map = get_double_map(deck.get_point_probability_map(), &rule);
+        assert_eq!(map.get(&EValue::H12).unwrap().get(&ECardPoint::Six).unwrap().unwrap(), double);
+    }
+}
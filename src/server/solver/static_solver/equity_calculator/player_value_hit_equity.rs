@@ -6,6 +6,7 @@ use tabled::Tabled;
 
 use super::super::super::super::card::ECardPoint;
 use super::super::super::super::deck::TDeck;
+use super::super::super::super::rule::SGameRule;
 use super::{player_value_stand_equity, value_hit_distribution};
 use super::super::super::super::value::EValue;
 
@@ -26,9 +27,9 @@ pub struct SPlayerValueHitEquityCell {
 
 pub type SPlayerValueHitEquity = Vec<SPlayerValueHitEquityCell>;
 
-pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>) -> HashMap<EValue, HashMap::<ECardPoint, Fraction>> {
+pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<EValue, HashMap::<ECardPoint, Fraction>> {
     let mut map = HashMap::<EValue, HashMap::<ECardPoint, Fraction>>::new();
-    let player_value_stand_equity_map = player_value_stand_equity::get_map(probability_map);
+    let player_value_stand_equity_map = player_value_stand_equity::get_map(probability_map, rule);
     let value_hit_map = value_hit_distribution::get_map(probability_map);
     for player_value_before_hit in EValue::iter() {
         if player_value_before_hit == EValue::None {
@@ -56,9 +57,9 @@ pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>) -> HashMap<EValu
     map
 }
 
-pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>) -> SPlayerValueHitEquity {
+pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> SPlayerValueHitEquity {
     let mut table = Vec::<SPlayerValueHitEquityCell>::new();
-    let map = get_map(probability_map);
+    let map = get_map(probability_map, rule);
     for player_value in EValue::iter().rev() {
         if player_value == EValue::None {
             continue;
@@ -88,13 +89,14 @@ mod test {
 
     use super::super::super::super::super::deck::random_deck::SRandomDeck;
     use super::super::super::super::super::deck::TDeck;
+    use super::super::super::super::super::rule::SGameRule;
     use super::super::player_value_hit_equity::get_table;
 
     #[test]
     fn test1() {
-        let table = Table::new(get_table(SRandomDeck::new().get_point_probability_map())).to_string();
+        let table = Table::new(get_table(SRandomDeck::new(0).get_point_probability_map(), &SGameRule::default())).to_string();
         println!("{}", &table);
-        // for row in get_table(Box::new(SRandomDeck::new())) {
+        // for row in get_table(Box::new(SRandomDeck::new(0))) {
         //     print!("Check {}:", row.value);
         // 
         //     assert_eq!(Fraction::new(1u64, 1u64),
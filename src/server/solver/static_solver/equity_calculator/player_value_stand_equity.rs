@@ -4,6 +4,7 @@ use strum::IntoEnumIterator;
 use tabled::Tabled;
 use super::super::super::super::card::ECardPoint;
 use super::super::super::super::deck::TDeck;
+use super::super::super::super::rule::SGameRule;
 use super::dealer_value_final_distribution::EDealerFinalValue;
 use super::dealer_first_final_distribution;
 use super::super::super::super::value::EValue;
@@ -25,22 +26,30 @@ pub struct SPlayerValueStandEquityCell {
 
 pub type SPlayerValueStandEquity = Vec<SPlayerValueStandEquityCell>;
 
-pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>) -> HashMap<EValue, HashMap::<ECardPoint, Fraction>> {
+/// 玩家停牌的期望
+/// 规则允许玩家21点总是获胜时 玩家21点对庄家非blackjack的21点也获胜
+/// 庄家22点平局规则下 庄家22点时未bust的玩家平局
+pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<EValue, HashMap::<ECardPoint, Fraction>> {
     let mut map = HashMap::<EValue, HashMap::<ECardPoint, Fraction>>::new();
-    let dealer_first_final_map = dealer_first_final_distribution::get_map(probability_map);
+    let dealer_first_final_map = dealer_first_final_distribution::get_map(probability_map, rule);
     for player_value in EValue::iter().rev() {
         match player_value {
             EValue::None => {}
             _ => {
                 let mut equity_map = HashMap::<ECardPoint, Fraction>::new();
+                let is_player_21_win = rule.player_21_always_wins && player_value.to_point() == 21;
                 for dealer_card_point in ECardPoint::iter() {
                     let dealer_final_map = dealer_first_final_map.get(&dealer_card_point.into()).unwrap();
                     let mut equity = Fraction::from(0);
                     for dealer_final_value in EDealerFinalValue::iter() {
                         let prob = dealer_final_map.get(&dealer_final_value).unwrap_or(&Fraction::new(0u64, 1u64)).clone();
-                        if player_value == EValue::Bust || dealer_final_value.to_point() > player_value.to_point() {
+                        if player_value != EValue::Bust && dealer_final_value == EDealerFinalValue::P22 {
+                            // 平局
+                        } else if player_value == EValue::Bust || dealer_final_value.to_point() > player_value.to_point() {
                             equity -= prob;
-                        } else if dealer_final_value.to_point() < player_value.to_point() {
+                        } else if dealer_final_value.to_point() < player_value.to_point()
+                            || (is_player_21_win && dealer_final_value == EDealerFinalValue::P21)
+                        {
                             equity += prob;
                         };
                     }
@@ -53,9 +62,32 @@ pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>) -> HashMap<EValu
     map
 }
 
-pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>) -> SPlayerValueStandEquity {
+/// 玩家21点(非blackjack)获胜的概率 21点奖金只在获胜时代替1:1赔付
+/// 庄家blackjack时失败 庄家21点时平局(玩家21点总是获胜时获胜) 庄家22点平局规则下庄家22点时平局
+pub fn get_21_win_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<ECardPoint, Fraction> {
+    let mut map = HashMap::<ECardPoint, Fraction>::new();
+    let dealer_first_final_map = dealer_first_final_distribution::get_map(probability_map, rule);
+    for dealer_card_point in ECardPoint::iter() {
+        let dealer_final_map = dealer_first_final_map.get(&dealer_card_point).unwrap();
+        let mut win = Fraction::from(0);
+        for (dealer_final_value, prob) in dealer_final_map {
+            let is_win = match dealer_final_value {
+                EDealerFinalValue::P21 => rule.player_21_always_wins,
+                EDealerFinalValue::Blackjack | EDealerFinalValue::P22 | EDealerFinalValue::None => false,
+                _ => true,
+            };
+            if is_win {
+                win += *prob;
+            }
+        }
+        map.insert(dealer_card_point, win);
+    }
+    map
+}
+
+pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> SPlayerValueStandEquity {
     let mut table = Vec::<SPlayerValueStandEquityCell>::new();
-    let map = get_map(probability_map);
+    let map = get_map(probability_map, rule);
     for player_value in EValue::iter().rev() {
         if player_value == EValue::None {
             continue;
@@ -84,13 +116,14 @@ mod test {
     use tabled::Table;
     use super::super::super::super::super::deck::random_deck::SRandomDeck;
     use super::super::super::super::super::deck::TDeck;
+    use super::super::super::super::super::rule::SGameRule;
     use super::super::player_value_stand_equity::get_table;
 
     #[test]
     fn test1() {
-        let table = Table::new(get_table(SRandomDeck::new().get_point_probability_map())).to_string();
+        let table = Table::new(get_table(SRandomDeck::new(0).get_point_probability_map(), &SGameRule::default())).to_string();
         println!("{}", &table);
-        // for row in get_table(Box::new(SRandomDeck::new())) {
+        // for row in get_table(Box::new(SRandomDeck::new(0))) {
         //     print!("Check {}:", row.value);
         // 
         //     assert_eq!(Fraction::new(1u64, 1u64),
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use fraction::Fraction;
+use strum::IntoEnumIterator;
+use tabled::Tabled;
+
+use super::super::super::super::card::{ECard, ECardNumber, ECardPoint};
+use super::super::super::super::rule::SGameRule;
+use super::super::super::super::side_bet::{ESideBet, ESideBetResult, SSideBetRule};
+
+#[derive(Tabled)]
+pub struct SSideBetEquityCell {
+    pub side_bet: ESideBet,
+    pub equity: Fraction,
+}
+
+pub type SSideBetEquity = Vec<SSideBetEquityCell>;
+
+/// 剩余每张牌(点数和花色)的数量 转换为u64便于计算组合数
+/// 边注的结果取决于花色 需要按花色实际剩余的数量计算
+fn get_card_counts(card_map: &HashMap<ECard, usize>) -> HashMap<ECard, u64> {
+    card_map.iter().map(|(card, num)| (*card, *num as u64)).collect()
+}
+
+/// 玩家前两张牌的所有组合及其抽取方式数量
+fn get_two_cards_ways(card_map: &HashMap<ECard, u64>) -> Vec<([ECard; 2], u64)> {
+    let mut ways = Vec::new();
+    for (card1, num1) in card_map.iter().filter(|(_, num)| **num > 0) {
+        for (card2, num2) in card_map.iter() {
+            let num2 = *num2 - if card1 == card2 { 1 } else { 0 };
+            if num2 > 0 {
+                ways.push(([*card1, *card2], *num1 * num2));
+            }
+        }
+    }
+    ways
+}
+
+/// 剩余的牌是否足够发出玩家前两张牌和庄家明牌
+fn can_deal(card_map: &HashMap<ECard, usize>) -> bool {
+    card_map.values().sum::<usize>() >= 3
+}
+
+/// 各中奖结果的概率
+/// 玩家前两张牌和庄家明牌按剩余牌的点数和花色不放回抽取 未中奖的概率不包含在结果中
+/// 剩余的牌不足以发牌时返回空map
+pub fn get_result_map(
+    side_bet: ESideBet,
+    card_map: &HashMap<ECard, usize>,
+    rule: &SGameRule,
+) -> HashMap<ESideBetResult, Fraction> {
+    let mut map = HashMap::<ESideBetResult, Fraction>::new();
+    if !can_deal(card_map) {
+        return map;
+    }
+    let card_map = get_card_counts(card_map);
+    let cards_num: u64 = card_map.values().sum();
+    let mut add = |result: Option<ESideBetResult>, probability: Fraction| {
+        if let Some(result) = result {
+            let p = map.entry(result).or_insert(Fraction::from(0));
+            *p += probability;
+        }
+    };
+
+    match side_bet {
+        ESideBet::PerfectPairs => {
+            let denominator = cards_num * (cards_num - 1);
+            for (cards, ways) in get_two_cards_ways(&card_map) {
+                add(side_bet.settle(&cards, &cards[..1]), Fraction::new(ways, denominator));
+            }
+        }
+        ESideBet::TwentyOnePlusThree => {
+            let denominator = cards_num * (cards_num - 1) * (cards_num - 2);
+            let mut counts = HashMap::<ESideBetResult, u64>::new();
+            for (cards, ways) in get_two_cards_ways(&card_map) {
+                for (card3, num3) in card_map.iter() {
+                    let num3 = *num3
+                        - cards.iter().filter(|card| *card == card3).count() as u64;
+                    if num3 == 0 {
+                        continue;
+                    }
+                    if let Some(result) = side_bet.settle(&cards, &[*card3]) {
+                        *counts.entry(result).or_insert(0) += ways * num3;
+                    }
+                }
+            }
+            for (result, count) in counts {
+                add(Some(result), Fraction::new(count, denominator));
+            }
+        }
+        ESideBet::LuckyLadies => {
+            let denominator = cards_num * (cards_num - 1);
+            let ace_num: u64 = card_map
+                .iter()
+                .filter(|(card, _)| card.value == ECardNumber::Ace)
+                .map(|(_, num)| *num)
+                .sum();
+            let ten_num: u64 = card_map
+                .iter()
+                .filter(|(card, _)| Into::<ECardPoint>::into(card.value) == ECardPoint::Ten)
+                .map(|(_, num)| *num)
+                .sum();
+            for (cards, ways) in get_two_cards_ways(&card_map) {
+                let probability = Fraction::new(ways, denominator);
+                match side_bet.settle(&cards, &cards[..1]) {
+                    Some(ESideBetResult::QueenOfHeartsPair) => {
+                        // 庄家从剩余的牌中拿到blackjack的概率 无底牌规则下庄家不会在发牌时blackjack
+                        let dealer_blackjack = match (
+                            rule.is_no_hole_card(),
+                            ten_num.checked_sub(2),
+                            cards_num.checked_sub(3),
+                        ) {
+                            (false, Some(ten_remain), Some(cards_remain)) if cards_remain > 0 => Fraction::new(
+                                2 * ace_num * ten_remain,
+                                (cards_remain + 1) * cards_remain,
+                            ),
+                            _ => Fraction::from(0),
+                        };
+                        add(
+                            Some(ESideBetResult::QueenOfHeartsPairWithDealerBlackjack),
+                            probability * dealer_blackjack,
+                        );
+                        add(
+                            Some(ESideBetResult::QueenOfHeartsPair),
+                            probability * (Fraction::from(1) - dealer_blackjack),
+                        );
+                    }
+                    result => add(result, probability),
+                }
+            }
+        }
+    }
+    map
+}
+
+/// 边注的期望 以边注下注额为单位 剩余的牌不足以发牌时为0
+pub fn get_equity(
+    side_bet_rule: &SSideBetRule,
+    card_map: &HashMap<ECard, usize>,
+    rule: &SGameRule,
+) -> Fraction {
+    if !can_deal(card_map) {
+        return Fraction::from(0);
+    }
+    let result_map = get_result_map(side_bet_rule.side_bet, card_map, rule);
+    let mut equity = -Fraction::from(1);
+    for (result, probability) in result_map {
+        if let Some(pay) = side_bet_rule.get_pay(result) {
+            equity += probability * (pay + Fraction::from(1));
+        }
+    }
+    equity
+}
+
+/// 规则中开放的所有边注的期望
+pub fn get_map(card_map: &HashMap<ECard, usize>, rule: &SGameRule) -> HashMap<ESideBet, Fraction> {
+    let mut map = HashMap::<ESideBet, Fraction>::new();
+    for side_bet_rule in rule.side_bets.iter() {
+        map.insert(side_bet_rule.side_bet, get_equity(side_bet_rule, card_map, rule));
+    }
+    map
+}
+
+pub fn get_table(card_map: &HashMap<ECard, usize>, rule: &SGameRule) -> SSideBetEquity {
+    let mut table = Vec::<SSideBetEquityCell>::new();
+    let map = get_map(card_map, rule);
+    for side_bet in ESideBet::iter() {
+        if let Some(equity) = map.get(&side_bet) {
+            table.push(SSideBetEquityCell {
+                side_bet,
+                equity: *equity,
+            })
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use fraction::{Fraction, ToPrimitive};
+    use strum::IntoEnumIterator;
+    use tabled::Table;
+
+    use super::super::super::super::super::card::{ECard, ECardColor, ECardNumber};
+    use super::super::super::super::super::deck::queue_deck::SQueueDeck;
+    use super::super::super::super::super::rule::SGameRule;
+    use super::super::super::super::super::side_bet::{ESideBet, SSideBetRule};
+    use super::super::super::super::super::side_bet::ESideBetResult;
+    use super::super::side_bet_equity::{get_map, get_result_map, get_table};
+
+    #[test]
+    fn test1() {
+        let deck = SQueueDeck::new(6, 0);
+        let rule = SGameRule {
+            side_bets: ESideBet::iter().map(SSideBetRule::new).collect(),
+            ..Default::default()
+        };
+        let table = Table::new(get_table(&deck.get_card_map(), &rule)).to_string();
+        println!("{}", &table);
+
+        // 6副牌的标准赔率表
+        // Perfect Pairs: (5*26 + 6*13 + 12*7) / 311 - 1
+        let map = get_map(&deck.get_card_map(), &rule);
+        assert_eq!(
+            *map.get(&ESideBet::PerfectPairs).unwrap(),
+            Fraction::new(292u64, 311u64) - Fraction::from(1)
+        );
+        let twenty_one_plus_three = map.get(&ESideBet::TwentyOnePlusThree).unwrap().to_f64().unwrap();
+        assert!((twenty_one_plus_three + 0.0462).abs() < 0.0001);
+        let lucky_ladies = map.get(&ESideBet::LuckyLadies).unwrap().to_f64().unwrap();
+        assert!((lucky_ladies + 0.2471).abs() < 0.0001);
+    }
+
+    /// 剩余牌的组成影响边注期望
+    #[test]
+    fn test_composition() {
+        let rule = SGameRule {
+            side_bets: ESideBet::iter().map(SSideBetRule::new).collect(),
+            ..Default::default()
+        };
+        let mut card_map = SQueueDeck::new(6, 0).get_card_map();
+        for side_bet in [ESideBet::PerfectPairs, ESideBet::TwentyOnePlusThree, ESideBet::LuckyLadies] {
+            let sum: Fraction = get_result_map(side_bet, &card_map, &rule).values().sum();
+            assert!(sum < Fraction::from(1));
+        }
+
+        // 移除所有10点牌后 Lucky Ladies只剩A9组成20点
+        let before = *get_map(&card_map, &rule).get(&ESideBet::LuckyLadies).unwrap();
+        card_map.retain(|card, _| {
+            !matches!(card.value, ECardNumber::Ten | ECardNumber::Jack | ECardNumber::Queen | ECardNumber::King)
+        });
+        let after = *get_map(&card_map, &rule).get(&ESideBet::LuckyLadies).unwrap();
+        println!("lucky ladies before:{} after:{}", before, after);
+        assert!(after < before);
+    }
+
+    /// 按花色实际剩余的数量计算 而不是按点数平均分配到各花色
+    #[test]
+    fn test_suits() {
+        let rule = SGameRule::default();
+        // 只剩红桃时 所有对子都是同花色对子 所有21+3都是同花
+        let mut card_map = SQueueDeck::new(6, 0).get_card_map();
+        card_map.retain(|card, _| card.color == ECardColor::Hearts);
+        let perfect_pairs = get_result_map(ESideBet::PerfectPairs, &card_map, &rule);
+        assert_eq!(perfect_pairs.get(&ESideBetResult::ColoredPair), None);
+        assert_eq!(perfect_pairs.get(&ESideBetResult::MixedPair), None);
+        // 78张红桃中同点数的牌各6张
+        assert_eq!(
+            *perfect_pairs.get(&ESideBetResult::PerfectPair).unwrap(),
+            Fraction::new(5u64, 77u64)
+        );
+        let twenty_one_plus_three = get_result_map(ESideBet::TwentyOnePlusThree, &card_map, &rule);
+        assert!(twenty_one_plus_three.keys().all(|result| matches!(
+            result,
+            ESideBetResult::SuitedTrips | ESideBetResult::StraightFlush | ESideBetResult::Flush
+        )));
+        assert_eq!(twenty_one_plus_three.values().sum::<Fraction>(), Fraction::from(1));
+    }
+
+    /// 牌库即将用尽时不会溢出
+    #[test]
+    fn test_tiny_deck() {
+        let rule = SGameRule::default();
+        let queen = |color| ECard {
+            color,
+            value: ECardNumber::Queen,
+        };
+        let mut card_map = HashMap::new();
+        for side_bet in [ESideBet::PerfectPairs, ESideBet::TwentyOnePlusThree, ESideBet::LuckyLadies] {
+            assert!(get_result_map(side_bet, &card_map, &rule).is_empty());
+        }
+        card_map.insert(queen(ECardColor::Hearts), 2);
+        assert!(get_map(&card_map, &rule).values().all(|equity| *equity == Fraction::from(0)));
+
+        // 只剩5张Q 其中两张红桃Q
+        card_map.insert(queen(ECardColor::Spades), 3);
+        for side_bet in [ESideBet::PerfectPairs, ESideBet::TwentyOnePlusThree, ESideBet::LuckyLadies] {
+            let sum: Fraction = get_result_map(side_bet, &card_map, &rule).values().sum();
+            assert!(sum <= Fraction::from(1));
+        }
+        card_map.remove(&queen(ECardColor::Spades));
+        card_map.insert(
+            ECard {
+                color: ECardColor::Clubs,
+                value: ECardNumber::Ace,
+            },
+            1,
+        );
+        for side_bet in [ESideBet::PerfectPairs, ESideBet::TwentyOnePlusThree, ESideBet::LuckyLadies] {
+            let sum: Fraction = get_result_map(side_bet, &card_map, &rule).values().sum();
+            assert!(sum <= Fraction::from(1));
+        }
+        println!("{}", Table::new(get_table(&card_map, &rule)));
+    }
+}
@@ -164,7 +164,7 @@ mod test {
 
     #[test]
     fn test1() {
-        let deck = SRandomDeck::new();
+        let deck = SRandomDeck::new(0);
         let v = get_table(deck.get_point_probability_map());
         let table = Table::new(v).to_string();
         print!("{table}");
@@ -1,2 +1,69 @@
 pub mod equity_calculator;
 pub mod strategy;
+
+use std::collections::HashMap;
+
+use fraction::Fraction;
+
+use super::super::card::ECardPoint;
+use super::super::deck::queue_deck::SQueueDeck;
+use super::super::deck::TDeck;
+use super::super::preset::ERulePreset;
+use super::super::rule::SGameRule;
+use equity_calculator::player_value_action_equity::{self, SPlayerValueActionEquity};
+use equity_calculator::side_bet_equity::{self, SSideBetEquity};
+use strategy::basic_strategy::{self, SBasicStrategy};
+use strategy::switch_strategy::{self, SSwitchGain};
+
+/// 计算玩家各value面对庄家明牌时各操作的期望
+pub fn get_action_equity_table(
+    probability_map: &HashMap<ECardPoint, Fraction>,
+    rule: &SGameRule,
+) -> SPlayerValueActionEquity {
+    player_value_action_equity::get_table(probability_map, rule)
+}
+
+/// 计算玩家各value面对庄家明牌时期望最大的操作
+pub fn get_strategy_table(
+    probability_map: &HashMap<ECardPoint, Fraction>,
+    rule: &SGameRule,
+) -> SBasicStrategy {
+    basic_strategy::get_table(probability_map, rule)
+}
+
+/// 预设规则对应的完整牌库 根据规则移除部分牌面
+fn get_preset_deck(preset: ERulePreset, rule: &SGameRule) -> SQueueDeck {
+    SQueueDeck::new_without(preset.decks_num(), &rule.removed_cards, 0)
+}
+
+/// 使用预设规则和对应副数的完整牌库 计算各操作的期望
+pub fn get_preset_action_equity_table(preset: ERulePreset) -> SPlayerValueActionEquity {
+    let rule = preset.rule();
+    let deck = get_preset_deck(preset, &rule);
+    get_action_equity_table(deck.get_point_probability_map(), &rule)
+}
+
+/// 使用预设规则和对应副数的完整牌库 计算策略表
+pub fn get_preset_strategy_table(preset: ERulePreset) -> SBasicStrategy {
+    let rule = preset.rule();
+    let deck = get_preset_deck(preset, &rule);
+    get_strategy_table(deck.get_point_probability_map(), &rule)
+}
+
+/// 使用预设规则和对应副数的完整牌库 计算Blackjack Switch交换带来的期望增加量
+/// 规则不允许交换时为None
+pub fn get_preset_switch_gain_table(preset: ERulePreset) -> Option<SSwitchGain> {
+    let rule = preset.rule();
+    if !rule.switch {
+        return None;
+    }
+    let deck = get_preset_deck(preset, &rule);
+    Some(switch_strategy::get_switch_gain_table(deck.get_point_probability_map(), &rule))
+}
+
+/// 使用预设规则和对应副数的完整牌库 计算各边注的期望
+pub fn get_preset_side_bet_equity_table(preset: ERulePreset) -> SSideBetEquity {
+    let rule = preset.rule();
+    let deck = get_preset_deck(preset, &rule);
+    side_bet_equity::get_table(&deck.get_card_map(), &rule)
+}
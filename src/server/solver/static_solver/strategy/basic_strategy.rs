@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use fraction::{Fraction, ToPrimitive};
+use strum::IntoEnumIterator;
+use strum_macros::Display;
+use tabled::Tabled;
+
+use super::super::super::super::bonus::EBonus21;
+use super::super::super::super::card::ECardPoint;
+use super::super::super::super::rule::SGameRule;
+use super::super::super::super::value::EValue;
+use super::super::super::strategy_analyzer::two_card_combination_distribution::{self, STwoCardCombination};
+use super::super::equity_calculator::player_value_action_equity::{get_double_map, get_surrender_map};
+use super::super::equity_calculator::player_value_stand_equity;
+use super::hit_stand_strategy;
+
+/// 策略表中的操作 double或投降不可用时(如已经拿过牌)使用后一个操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum EStrategyAction {
+    #[strum(to_string = "H")]
+    Hit,
+    #[strum(to_string = "S")]
+    Stand,
+    #[strum(to_string = "Dh")]
+    DoubleOrHit,
+    #[strum(to_string = "Ds")]
+    DoubleOrStand,
+    #[strum(to_string = "Rh")]
+    SurrenderOrHit,
+    #[strum(to_string = "Rs")]
+    SurrenderOrStand,
+}
+
+#[derive(Tabled)]
+pub struct SBasicStrategyCell {
+    pub value: EValue,
+    pub two: EStrategyAction,
+    pub three: EStrategyAction,
+    pub four: EStrategyAction,
+    pub five: EStrategyAction,
+    pub six: EStrategyAction,
+    pub seven: EStrategyAction,
+    pub eight: EStrategyAction,
+    pub nine: EStrategyAction,
+    pub ten: EStrategyAction,
+    pub ace: EStrategyAction,
+}
+
+pub type SBasicStrategy = Vec<SBasicStrategyCell>;
+
+/// 两张牌的各value拿一张牌组成6-7-8或7-7-7并获胜时 21点奖金代替1:1赔付增加的期望
+/// 求解以手牌value为状态 按两张牌各组合在该value中的概率加权 花色按均匀分布计算
+fn get_three_card_bonus_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<EValue, HashMap<ECardPoint, f64>> {
+    let mut map = HashMap::<EValue, HashMap<ECardPoint, f64>>::new();
+    let win_21_map = player_value_stand_equity::get_21_win_map(probability_map, rule);
+    let combination_map = two_card_combination_distribution::get_map(probability_map);
+    // 按花色的期望赔付 不同花色15/16 同花色非黑桃3/64 黑桃1/64 未设置的奖金按1:1赔付
+    let get_extra_pay = |bonuses: [(EBonus21, Fraction); 3]| {
+        bonuses
+            .iter()
+            .map(|(bonus, prob)| rule.get_bonus_21_pay(*bonus).map_or(Fraction::from(0), |pay| *prob * (pay - Fraction::from(1))))
+            .fold(Fraction::from(0), |sum, extra| sum + extra)
+    };
+    let suit_probs = [Fraction::new(15u64, 16u64), Fraction::new(3u64, 64u64), Fraction::new(1u64, 64u64)];
+    let extra_678 = get_extra_pay([
+        (EBonus21::Mixed678, suit_probs[0]),
+        (EBonus21::Suited678, suit_probs[1]),
+        (EBonus21::Spades678, suit_probs[2]),
+    ]);
+    let extra_777 = get_extra_pay([
+        (EBonus21::Mixed777, suit_probs[0]),
+        (EBonus21::Suited777, suit_probs[1]),
+        (EBonus21::Spades777, suit_probs[2]),
+    ]);
+    let mut value_prob_map = HashMap::<EValue, Fraction>::new();
+    for (combination, prob) in &combination_map {
+        if !combination.is_blackjack() {
+            *value_prob_map.entry(combination.value()).or_insert(Fraction::from(0)) += *prob;
+        }
+    }
+    // 两张牌的组合 补足奖金所需的第三张牌 奖金的额外赔付
+    let bonus_hands = [
+        (ECardPoint::Six, ECardPoint::Seven, ECardPoint::Eight, extra_678),
+        (ECardPoint::Six, ECardPoint::Eight, ECardPoint::Seven, extra_678),
+        (ECardPoint::Seven, ECardPoint::Eight, ECardPoint::Six, extra_678),
+        (ECardPoint::Seven, ECardPoint::Seven, ECardPoint::Seven, extra_777),
+    ];
+    for (first, second, third, extra) in bonus_hands {
+        let combination = STwoCardCombination::new(first, second);
+        let value = combination.value();
+        let combination_prob = *combination_map.get(&combination).unwrap();
+        let value_prob = *value_prob_map.get(&value).unwrap();
+        if combination_prob == Fraction::from(0) || extra == Fraction::from(0) {
+            continue;
+        }
+        let third_prob = *probability_map.get(&third).unwrap();
+        for (dealer_card_point, win_21) in &win_21_map {
+            let equity = combination_prob / value_prob * third_prob * *win_21 * extra;
+            *map.entry(value).or_default().entry(*dealer_card_point).or_insert(0f64) += equity.to_f64().unwrap();
+        }
+    }
+    map
+}
+
+/// 各value面对庄家明牌时期望最大的操作(不包括split)
+/// 牌库组成、21点总是获胜、double救援和投降均由rule决定
+/// 张数奖金由拿牌递归计入 6-7-8和7-7-7奖金按组合概率计入拿牌和double的期望
+pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<EValue, HashMap<ECardPoint, EStrategyAction>> {
+    let mut map = HashMap::<EValue, HashMap<ECardPoint, EStrategyAction>>::new();
+    let hit_stand_map = hit_stand_strategy::get_map(probability_map, rule);
+    let double_map = get_double_map(probability_map, rule);
+    let surrender_map = get_surrender_map(probability_map, rule);
+    let bonus_map = get_three_card_bonus_map(probability_map, rule);
+    for (player_value, equity_map) in hit_stand_map {
+        let mut m = HashMap::<ECardPoint, EStrategyAction>::new();
+        for (dealer_card_point, mut equity) in equity_map {
+            // double的下注加倍 奖金也按加倍的下注赔付
+            let bonus = bonus_map.get(&player_value).and_then(|x| x.get(&dealer_card_point)).copied().unwrap_or(0f64);
+            let double_bonus = match rule.bonus_21_after_double {
+                true => bonus * 2f64,
+                false => 0f64,
+            };
+            equity.hit += bonus;
+            let is_hit = equity.hit > equity.stand;
+            let best = equity.best();
+            let double = double_map.get(&player_value).unwrap().get(&dealer_card_point).unwrap().and_then(|x| x.to_f64());
+            let double = double.map(|double| double + double_bonus);
+            let surrender = surrender_map.get(&dealer_card_point).unwrap().and_then(|x| x.to_f64());
+            let double = double.filter(|double| *double > best);
+            let surrender = surrender.filter(|surrender| *surrender > best);
+            let action = match (double, surrender, is_hit) {
+                (Some(double), Some(surrender), true) if surrender > double => EStrategyAction::SurrenderOrHit,
+                (Some(double), Some(surrender), false) if surrender > double => EStrategyAction::SurrenderOrStand,
+                (Some(_), _, true) => EStrategyAction::DoubleOrHit,
+                (Some(_), _, false) => EStrategyAction::DoubleOrStand,
+                (None, Some(_), true) => EStrategyAction::SurrenderOrHit,
+                (None, Some(_), false) => EStrategyAction::SurrenderOrStand,
+                (None, None, true) => EStrategyAction::Hit,
+                (None, None, false) => EStrategyAction::Stand,
+            };
+            m.insert(dealer_card_point, action);
+        }
+        map.insert(player_value, m);
+    }
+    map
+}
+
+pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> SBasicStrategy {
+    let mut table = Vec::<SBasicStrategyCell>::new();
+    let map = get_map(probability_map, rule);
+    for player_value in EValue::iter().rev() {
+        let Some(action_map) = map.get(&player_value) else {
+            continue;
+        };
+        let get = |dealer_card_point| *action_map.get(&dealer_card_point).unwrap();
+        table.push(SBasicStrategyCell {
+            value: player_value,
+            two: get(ECardPoint::Two),
+            three: get(ECardPoint::Three),
+            four: get(ECardPoint::Four),
+            five: get(ECardPoint::Five),
+            six: get(ECardPoint::Six),
+            seven: get(ECardPoint::Seven),
+            eight: get(ECardPoint::Eight),
+            nine: get(ECardPoint::Nine),
+            ten: get(ECardPoint::Ten),
+            ace: get(ECardPoint::Ace),
+        })
+    }
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use tabled::Table;
+
+    use super::super::super::super::super::card::{ECardNumber, ECardPoint};
+    use super::super::super::super::super::deck::queue_deck::SQueueDeck;
+    use super::super::super::super::super::deck::TDeck;
+    use super::super::super::super::super::preset::ERulePreset;
+    use super::super::super::super::super::rule::SGameRule;
+    use super::super::super::super::super::value::EValue;
+    use super::{get_map, get_table, EStrategyAction};
+
+    #[test]
+    fn test1() {
+        let preset = ERulePreset::VegasStrip6D;
+        let deck = SQueueDeck::new(preset.decks_num(), 0);
+        let rule = preset.rule();
+        println!("{}", Table::new(get_table(deck.get_point_probability_map(), &rule)));
+        let map = get_map(deck.get_point_probability_map(), &rule);
+        let get = |value, dealer_card_point| *map.get(&value).unwrap().get(&dealer_card_point).unwrap();
+        assert_eq!(get(EValue::H11, ECardPoint::Six), EStrategyAction::DoubleOrHit);
+        assert_eq!(get(EValue::H16, ECardPoint::Ten), EStrategyAction::SurrenderOrHit);
+        assert_eq!(get(EValue::H17, ECardPoint::Two), EStrategyAction::Stand);
+        assert_eq!(get(EValue::H12, ECardPoint::Two), EStrategyAction::Hit);
+        assert_eq!(get(EValue::S18, ECardPoint::Six), EStrategyAction::DoubleOrStand);
+    }
+
+    /// 西班牙21点移除10后 庄家bust的概率降低 玩家需要更积极地拿牌
+    #[test]
+    fn test_spanish_21() {
+        let preset = ERulePreset::Spanish21;
+        let rule = preset.rule();
+        let deck = SQueueDeck::new_without(preset.decks_num(), &rule.removed_cards, 0);
+        assert_eq!(rule.removed_cards, vec![ECardNumber::Ten]);
+        println!("{}", Table::new(get_table(deck.get_point_probability_map(), &rule)));
+        let map = get_map(deck.get_point_probability_map(), &rule);
+        let get = |value, dealer_card_point| *map.get(&value).unwrap().get(&dealer_card_point).unwrap();
+        assert_eq!(get(EValue::H12, ECardPoint::Four), EStrategyAction::Hit);
+        assert_eq!(get(EValue::H17, ECardPoint::Two), EStrategyAction::Stand);
+        assert_eq!(get(EValue::H11, ECardPoint::Six), EStrategyAction::DoubleOrHit);
+    }
+
+    /// 6-7-8奖金使两张牌的硬13面对6时拿牌
+    #[test]
+    fn test_spanish_21_bonus() {
+        let preset = ERulePreset::Spanish21;
+        let rule = preset.rule();
+        let deck = SQueueDeck::new_without(preset.decks_num(), &rule.removed_cards, 0);
+        let without_bonus = SGameRule {
+            bonus_21: Vec::new(),
+            ..rule.clone()
+        };
+        let get = |rule, value, dealer_card_point| *get_map(deck.get_point_probability_map(), rule).get(&value).unwrap().get(&dealer_card_point).unwrap();
+        assert_eq!(get(&rule, EValue::H13, ECardPoint::Six), EStrategyAction::Hit);
+        assert_eq!(get(&without_bonus, EValue::H13, ECardPoint::Six), EStrategyAction::Stand);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use fraction::{Fraction, ToPrimitive};
+use strum::IntoEnumIterator;
+
+use super::super::super::super::bonus::EBonus21;
+use super::super::super::super::card::ECardPoint;
+use super::super::super::super::rule::SGameRule;
+use super::super::super::super::value::EValue;
+use super::super::equity_calculator::player_value_action_equity::get_charlie_equity;
+use super::super::equity_calculator::{player_value_stand_equity, value_hit_distribution};
+
+/// 玩家在某一value下 面对庄家明牌时停牌和拿牌的期望
+/// 拿牌后按最优策略继续拿牌或停牌
+/// 多次拿牌的概率相乘后分母超出u64范围 因此使用f64
+#[derive(Clone, Debug)]
+pub struct SHitStandEquity {
+    pub stand: f64,
+    pub hit: f64,
+}
+
+impl SHitStandEquity {
+    /// 停牌和拿牌中较大的期望
+    pub fn best(&self) -> f64 {
+        self.hit.max(self.stand)
+    }
+}
+
+// 张数奖金中最多的张数 7张及以上的21点奖金相同
+const MAX_BONUS_CARDS_NUM: usize = 7;
+
+/// 拿牌状态中的手牌张数
+/// 不使用Charlie规则和张数奖金时张数不影响期望 统一记为0
+/// 张数超过Charlie要求或张数奖金的最多张数时按两者中较大的记录
+fn get_cards_num_key(rule: &SGameRule, cards_num: usize) -> usize {
+    let has_cards_num_bonus = [EBonus21::FiveCard, EBonus21::SixCard, EBonus21::SevenPlusCard]
+        .into_iter()
+        .any(|bonus| rule.get_bonus_21_pay(bonus).is_some());
+    let bonus_cards = has_cards_num_bonus.then_some(MAX_BONUS_CARDS_NUM);
+    rule.charlie_cards.max(bonus_cards).map_or(0, |max_cards| cards_num.min(max_cards))
+}
+
+/// 拿牌递归中不变的参数
+struct SHitStandContext<'a> {
+    rule: &'a SGameRule,
+    dealer_card_point: ECardPoint,
+    charlie: f64,
+    // 玩家21点获胜的概率 用于计算张数奖金代替1:1赔付增加的期望
+    win_21: f64,
+    stand_map: &'a HashMap<EValue, HashMap<ECardPoint, Fraction>>,
+    value_hit_map: &'a HashMap<EValue, HashMap<EValue, Fraction>>,
+}
+
+/// 计算value和手牌张数下的最优期望 结果记录在memo中
+/// 拿牌后点数只增不减 递归必然终止于bust或Charlie
+/// 5张及以上的21点获胜时按张数奖金赔付
+fn get_best_equity(
+    value: EValue,
+    cards_num: usize,
+    context: &SHitStandContext,
+    memo: &mut HashMap<(EValue, usize), SHitStandEquity>,
+) -> f64 {
+    let mut stand = context.stand_map.get(&value).unwrap().get(&context.dealer_card_point).unwrap().to_f64().unwrap();
+    if value == EValue::Bust {
+        return stand;
+    }
+    if context.rule.check_charlie(cards_num) {
+        return context.charlie;
+    }
+    if value.to_point() == 21 {
+        let pay = EBonus21::from_cards_num(cards_num).and_then(|bonus| context.rule.get_bonus_21_pay(bonus));
+        if let Some(pay) = pay {
+            stand += context.win_21 * (pay - Fraction::from(1)).to_f64().unwrap();
+        }
+    }
+    if let Some(equity) = memo.get(&(value, cards_num)) {
+        return equity.best();
+    }
+    let next_cards_num = get_cards_num_key(context.rule, cards_num + 1);
+    let mut hit = 0f64;
+    for (after_hit_value, hit_prob) in context.value_hit_map.get(&value).unwrap() {
+        if *hit_prob == Fraction::from(0) {
+            continue;
+        }
+        hit += hit_prob.to_f64().unwrap() * get_best_equity(*after_hit_value, next_cards_num, context, memo);
+    }
+    let equity = SHitStandEquity { stand, hit };
+    let best = equity.best();
+    memo.insert((value, cards_num), equity);
+    best
+}
+
+/// 只考虑拿牌和停牌时 两张牌的各value面对庄家明牌的期望
+pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<EValue, HashMap<ECardPoint, SHitStandEquity>> {
+    get_cards_num_map(probability_map, rule, 2)
+}
+
+/// 只考虑拿牌和停牌时 cards_num张牌的各value面对庄家明牌的期望
+/// 使用Charlie规则或张数奖金时 拿牌的期望随张数变化
+/// 已达到Charlie要求的状态无需操作 不包含在结果中
+pub fn get_cards_num_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule, cards_num: usize) -> HashMap<EValue, HashMap<ECardPoint, SHitStandEquity>> {
+    let mut map = HashMap::<EValue, HashMap<ECardPoint, SHitStandEquity>>::new();
+    let stand_map = player_value_stand_equity::get_map(probability_map, rule);
+    let win_21_map = player_value_stand_equity::get_21_win_map(probability_map, rule);
+    let value_hit_map = value_hit_distribution::get_map(probability_map);
+    let cards_num = get_cards_num_key(rule, cards_num);
+    for dealer_card_point in ECardPoint::iter() {
+        let context = SHitStandContext {
+            rule,
+            dealer_card_point,
+            charlie: get_charlie_equity(probability_map, rule, dealer_card_point).to_f64().unwrap(),
+            win_21: win_21_map.get(&dealer_card_point).unwrap().to_f64().unwrap(),
+            stand_map: &stand_map,
+            value_hit_map: &value_hit_map,
+        };
+        let mut memo = HashMap::<(EValue, usize), SHitStandEquity>::new();
+        for value in EValue::iter() {
+            if value == EValue::None || value == EValue::Bust {
+                continue;
+            }
+            get_best_equity(value, cards_num, &context, &mut memo);
+        }
+        for ((value, n), equity) in memo {
+            if n == cards_num {
+                map.entry(value).or_default().insert(dealer_card_point, equity);
+            }
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::super::super::super::card::ECardPoint;
+    use super::super::super::super::super::deck::queue_deck::SQueueDeck;
+    use super::super::super::super::super::deck::random_deck::SRandomDeck;
+    use super::super::super::super::super::deck::TDeck;
+    use super::super::super::super::super::preset::ERulePreset;
+    use super::super::super::super::super::rule::SGameRule;
+    use super::super::super::super::super::value::EValue;
+    use std::collections::HashMap;
+
+    use super::{get_cards_num_map, get_map, SHitStandEquity};
+
+    #[test]
+    fn test1() {
+        let map = get_map(SRandomDeck::new(0).get_point_probability_map(), &SGameRule::default());
+        let is_hit = |value: EValue, dealer_card_point: ECardPoint| {
+            let equity = map.get(&value).unwrap().get(&dealer_card_point).unwrap();
+            equity.hit > equity.stand
+        };
+        // 标准基本策略
+        assert!(is_hit(EValue::H12, ECardPoint::Two));
+        assert!(!is_hit(EValue::H13, ECardPoint::Two));
+        assert!(!is_hit(EValue::H12, ECardPoint::Four));
+        assert!(is_hit(EValue::H16, ECardPoint::Ten));
+        assert!(!is_hit(EValue::H17, ECardPoint::Ace));
+        assert!(is_hit(EValue::S18, ECardPoint::Nine));
+        assert!(!is_hit(EValue::S18, ECardPoint::Eight));
+        assert!(is_hit(EValue::H11, ECardPoint::Ten));
+    }
+
+    /// 五张牌Charlie 四张牌时拿牌只要不bust即获胜
+    #[test]
+    fn test_charlie() {
+        let deck = SRandomDeck::new(0);
+        let mut rule = SGameRule::default();
+        rule.charlie_cards = Some(5);
+        let is_hit = |map: &HashMap<EValue, HashMap<ECardPoint, SHitStandEquity>>, value: EValue, dealer_card_point: ECardPoint| {
+            let equity = map.get(&value).unwrap().get(&dealer_card_point).unwrap();
+            equity.hit > equity.stand
+        };
+        let map = get_map(deck.get_point_probability_map(), &rule);
+        assert!(!is_hit(&map, EValue::H14, ECardPoint::Six));
+        let map = get_cards_num_map(deck.get_point_probability_map(), &rule, 4);
+        assert!(is_hit(&map, EValue::H14, ECardPoint::Six));
+        assert!(!is_hit(&map, EValue::H19, ECardPoint::Six));
+        // H14拿牌 A-7获胜 8-10 bust
+        let equity = map.get(&EValue::H14).unwrap().get(&ECardPoint::Six).unwrap();
+        assert!((equity.hit - (7f64 - 6f64) / 13f64).abs() < 1e-9);
+        // 已经达到五张牌时无需操作
+        assert!(get_cards_num_map(deck.get_point_probability_map(), &rule, 5).is_empty());
+    }
+
+    /// 西班牙21点的5张牌21点按3:2赔付 四张牌的软18拿牌
+    #[test]
+    fn test_cards_num_bonus() {
+        let preset = ERulePreset::Spanish21;
+        let rule = preset.rule();
+        let deck = SQueueDeck::new_without(preset.decks_num(), &rule.removed_cards, 0);
+        let without_bonus = SGameRule {
+            bonus_21: Vec::new(),
+            ..rule.clone()
+        };
+        let is_hit = |rule: &SGameRule, cards_num: usize| {
+            let map = get_cards_num_map(deck.get_point_probability_map(), rule, cards_num);
+            let equity = map.get(&EValue::S18).unwrap().get(&ECardPoint::Six).unwrap();
+            equity.hit > equity.stand
+        };
+        assert!(is_hit(&rule, 4));
+        assert!(!is_hit(&without_bonus, 4));
+        assert!(!is_hit(&rule, 3));
+    }
+}
@@ -1 +1,5 @@
-mod hit_stand_strategy;
\ No newline at end of file
+// 根据各操作的EV生成策略
+
+pub mod hit_stand_strategy;
+pub mod basic_strategy;
+pub mod switch_strategy;
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use fraction::{Fraction, ToPrimitive};
+use strum::IntoEnumIterator;
+use tabled::Tabled;
+
+use super::super::super::super::card::ECardPoint;
+use super::super::super::super::rule::SGameRule;
+use super::super::super::strategy_analyzer::two_card_combination_distribution::{self, STwoCardCombination};
+use super::super::equity_calculator::dealer_first_final_distribution::get_blackjack_probability;
+use super::super::equity_calculator::player_value_action_equity::{get_double_map, get_surrender_map};
+use super::hit_stand_strategy;
+
+/// Blackjack Switch 保持或交换第二张牌时两手牌的总期望
+#[derive(Clone, Debug)]
+pub struct SSwitchEquity {
+    pub keep: f64,
+    pub switch: f64,
+}
+
+impl SSwitchEquity {
+    pub fn should_switch(&self) -> bool {
+        self.switch > self.keep
+    }
+}
+
+#[derive(Tabled)]
+pub struct SSwitchGainCell {
+    pub dealer_card_point: ECardPoint,
+    // 按最优策略交换后 两手牌总期望的增加量
+    pub gain: f64,
+}
+
+pub type SSwitchGain = Vec<SSwitchGainCell>;
+
+/// 各两张牌组合面对庄家明牌时的最优期望
+/// 交换后的A+10只算21点 不算blackjack 因此保持和交换分别计算
+pub struct SSwitchHandEquityMap {
+    pub keep: HashMap<STwoCardCombination, HashMap<ECardPoint, f64>>,
+    pub switch: HashMap<STwoCardCombination, HashMap<ECardPoint, f64>>,
+}
+
+/// 各两张牌组合面对庄家明牌时的最优期望
+/// 牌桌在庄家peek之前询问是否交换 因此期望不以庄家非blackjack为条件
+/// blackjack按规则赔付 庄家blackjack时平局
+/// 其余组合以及交换后的A+10取拿牌、停牌、double和投降中的最大期望 不考虑split 庄家blackjack时输掉下注
+pub fn get_hand_equity_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> SSwitchHandEquityMap {
+    let mut keep_map = HashMap::<STwoCardCombination, HashMap<ECardPoint, f64>>::new();
+    let mut switch_map = HashMap::<STwoCardCombination, HashMap<ECardPoint, f64>>::new();
+    let hit_stand_map = hit_stand_strategy::get_map(probability_map, rule);
+    let double_map = get_double_map(probability_map, rule);
+    let surrender_map = get_surrender_map(probability_map, rule);
+    for combination in two_card_combination_distribution::get_map(probability_map).into_keys() {
+        let mut keep = HashMap::<ECardPoint, f64>::new();
+        let mut switch = HashMap::<ECardPoint, f64>::new();
+        for dealer_card_point in ECardPoint::iter() {
+            let value = combination.value();
+            let best = hit_stand_map.get(&value).unwrap().get(&dealer_card_point).unwrap().best();
+            let double = double_map.get(&value).unwrap().get(&dealer_card_point).unwrap().and_then(|x| x.to_f64());
+            let surrender = surrender_map.get(&dealer_card_point).unwrap().and_then(|x| x.to_f64());
+            let dealer_blackjack = get_blackjack_probability(probability_map, dealer_card_point);
+            // 庄家peek时玩家行动的期望以庄家非blackjack为条件 需要加上庄家blackjack的情况
+            let equity = [double, surrender].into_iter().flatten().fold(best, f64::max);
+            let equity = match rule.is_dealer_peek() {
+                true => {
+                    let dealer_blackjack = dealer_blackjack.to_f64().unwrap();
+                    (1f64 - dealer_blackjack) * equity - dealer_blackjack
+                }
+                false => equity,
+            };
+            let blackjack_equity = match combination.is_blackjack() {
+                true => (rule.blackjack_pay * (Fraction::from(1) - dealer_blackjack)).to_f64().unwrap(),
+                false => equity,
+            };
+            keep.insert(dealer_card_point, blackjack_equity);
+            switch.insert(dealer_card_point, equity);
+        }
+        keep_map.insert(combination, keep);
+        switch_map.insert(combination, switch);
+    }
+    SSwitchHandEquityMap {
+        keep: keep_map,
+        switch: switch_map,
+    }
+}
+
+/// 两手牌hand1和hand2(按发牌顺序) 保持和交换第二张牌时的总期望
+pub fn get_switch_equity(
+    hand_equity_map: &SSwitchHandEquityMap,
+    hand1: [ECardPoint; 2],
+    hand2: [ECardPoint; 2],
+    dealer_card_point: ECardPoint,
+) -> SSwitchEquity {
+    let get = |map: &HashMap<STwoCardCombination, HashMap<ECardPoint, f64>>, first, second| {
+        *map.get(&STwoCardCombination::new(first, second)).unwrap().get(&dealer_card_point).unwrap()
+    };
+    let keep = &hand_equity_map.keep;
+    let switch = &hand_equity_map.switch;
+    SSwitchEquity {
+        keep: get(keep, hand1[0], hand1[1]) + get(keep, hand2[0], hand2[1]),
+        switch: get(switch, hand1[0], hand2[1]) + get(switch, hand2[0], hand1[1]),
+    }
+}
+
+/// 面对各庄家明牌 按最优策略交换带来的期望增加量
+/// 交换的是第二张牌 因此按发牌顺序枚举四张牌
+pub fn get_switch_gain_map(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> HashMap<ECardPoint, f64> {
+    let mut map = HashMap::<ECardPoint, f64>::new();
+    let hand_equity_map = get_hand_equity_map(probability_map, rule);
+    let prob = |card_point: ECardPoint| probability_map.get(&card_point).unwrap().to_f64().unwrap();
+    for dealer_card_point in ECardPoint::iter() {
+        let mut gain = 0f64;
+        for a in ECardPoint::iter() {
+            for b in ECardPoint::iter() {
+                for c in ECardPoint::iter() {
+                    for d in ECardPoint::iter() {
+                        let equity = get_switch_equity(&hand_equity_map, [a, b], [c, d], dealer_card_point);
+                        gain += prob(a) * prob(b) * prob(c) * prob(d) * (equity.switch - equity.keep).max(0f64);
+                    }
+                }
+            }
+        }
+        map.insert(dealer_card_point, gain);
+    }
+    map
+}
+
+pub fn get_switch_gain_table(probability_map: &HashMap<ECardPoint, Fraction>, rule: &SGameRule) -> SSwitchGain {
+    let map = get_switch_gain_map(probability_map, rule);
+    ECardPoint::iter()
+        .map(|dealer_card_point| SSwitchGainCell {
+            dealer_card_point,
+            gain: *map.get(&dealer_card_point).unwrap(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use tabled::Table;
+
+    use super::super::super::super::super::card::ECardPoint;
+    use super::super::super::super::super::deck::queue_deck::SQueueDeck;
+    use super::super::super::super::super::deck::TDeck;
+    use super::super::super::super::super::preset::ERulePreset;
+    use super::super::super::super::super::rule::SGameRule;
+    use super::super::super::super::strategy_analyzer::two_card_combination_distribution::STwoCardCombination;
+    use super::{get_hand_equity_map, get_switch_equity, get_switch_gain_table};
+
+    #[test]
+    fn test_switch() {
+        let preset = ERulePreset::BlackjackSwitch6D;
+        let deck = SQueueDeck::new(preset.decks_num(), 0);
+        let rule = preset.rule();
+        let map = get_hand_equity_map(deck.get_point_probability_map(), &rule);
+        // 两手16点 交换后为12点对子和20点
+        let equity = get_switch_equity(&map, [ECardPoint::Six, ECardPoint::Ten], [ECardPoint::Ten, ECardPoint::Six], ECardPoint::Ten);
+        assert!(equity.should_switch());
+        // 交换后两手牌不变
+        let equity = get_switch_equity(&map, [ECardPoint::Ten, ECardPoint::Nine], [ECardPoint::Ten, ECardPoint::Nine], ECardPoint::Six);
+        assert!(!equity.should_switch());
+        // 保留blackjack和15点 优于交换为20点和软16点
+        let equity = get_switch_equity(&map, [ECardPoint::Ten, ECardPoint::Five], [ECardPoint::Ace, ECardPoint::Ten], ECardPoint::Ten);
+        assert!(!equity.should_switch());
+        // 交换后的A+10只算21点 期望低于blackjack
+        let blackjack = STwoCardCombination::new(ECardPoint::Ace, ECardPoint::Ten);
+        assert!(map.switch[&blackjack][&ECardPoint::Ten] < map.keep[&blackjack][&ECardPoint::Ten]);
+        // 两手A+10 交换后仍为两手A+10 但不再是blackjack
+        let equity = get_switch_equity(&map, [ECardPoint::Ace, ECardPoint::Ten], [ECardPoint::Ace, ECardPoint::Ten], ECardPoint::Six);
+        assert!(!equity.should_switch());
+
+        let table = get_switch_gain_table(deck.get_point_probability_map(), &rule);
+        println!("{}", Table::new(&table));
+        assert!(table.iter().all(|row| row.gain > 0f64));
+    }
+
+    /// 交换在庄家peek之前决定 停牌的手牌期望与庄家不peek时相同
+    #[test]
+    fn test_switch_before_peek() {
+        let preset = ERulePreset::BlackjackSwitch6D;
+        let deck = SQueueDeck::new(preset.decks_num(), 0);
+        let rule = preset.rule();
+        assert!(rule.is_dealer_peek());
+        let no_peek_rule = SGameRule {
+            dealer_peek: false,
+            ..rule.clone()
+        };
+        let map = get_hand_equity_map(deck.get_point_probability_map(), &rule);
+        let no_peek_map = get_hand_equity_map(deck.get_point_probability_map(), &no_peek_rule);
+        let twenty = STwoCardCombination::new(ECardPoint::Ten, ECardPoint::Ten);
+        let blackjack = STwoCardCombination::new(ECardPoint::Ace, ECardPoint::Ten);
+        for dealer_card_point in [ECardPoint::Ace, ECardPoint::Ten] {
+            for (combination, equity_map, no_peek_equity_map) in [
+                (twenty, &map.keep, &no_peek_map.keep),
+                (blackjack, &map.keep, &no_peek_map.keep),
+                (blackjack, &map.switch, &no_peek_map.switch),
+            ] {
+                let equity = equity_map[&combination][&dealer_card_point];
+                let no_peek_equity = no_peek_equity_map[&combination][&dealer_card_point];
+                assert!((equity - no_peek_equity).abs() < 1e-9, "{equity} != {no_peek_equity}");
+            }
+        }
+        // 庄家明牌为A时 交换后的A+10可能输给庄家blackjack
+        assert!(map.switch[&blackjack][&ECardPoint::Ace] < map.switch[&blackjack][&ECardPoint::Six]);
+    }
+}
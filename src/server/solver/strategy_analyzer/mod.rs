@@ -1,5 +1,6 @@
 /// 策略分析
 /// 给定固定策略后  计算整体期望
 
+pub mod shuffle_tracking;
 pub mod two_card_combination_distribution;
 pub mod two_card_value_distribution;
\ No newline at end of file
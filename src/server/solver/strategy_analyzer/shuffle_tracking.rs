@@ -0,0 +1,134 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tabled::Tabled;
+
+use super::super::super::deck::shuffle::EShuffleProcedure;
+
+/// 洗牌后上一靴牌序的保留程度
+/// 以上一靴的牌序给每张牌编号 洗牌后统计编号的排列
+#[derive(Tabled, Debug, Clone)]
+pub struct SShuffleTrackingCell {
+    pub procedure: String,
+    // 上升序列数 完全随机时约为牌数的一半 越少表示牌序保留越多
+    pub rising_sequences: f64,
+    // 上一靴相邻的两张牌 洗牌后仍然相邻的比例
+    pub adjacent_survival: f64,
+    // 跟踪的一段牌洗牌后分布的范围占整靴的比例 越小越容易跟踪
+    pub segment_span: f64,
+}
+
+pub type SShuffleTracking = Vec<SShuffleTrackingCell>;
+
+/// 上升序列数 编号i+1出现在编号i之前时开始新的序列
+pub fn get_rising_sequences(order: &[usize]) -> usize {
+    let mut positions = vec![0; order.len()];
+    for (position, &card) in order.iter().enumerate() {
+        positions[card] = position;
+    }
+    1 + positions.windows(2).filter(|w| w[1] < w[0]).count()
+}
+
+/// 上一靴相邻的两张牌 洗牌后仍然相邻的比例(不区分先后)
+pub fn get_adjacent_survival(order: &[usize]) -> f64 {
+    if order.len() < 2 {
+        return 0f64;
+    }
+    let survived = order.windows(2).filter(|w| w[0].abs_diff(w[1]) == 1).count();
+    survived as f64 / (order.len() - 1) as f64
+}
+
+/// 编号在segment范围内的牌 洗牌后所在位置的跨度占整靴的比例
+pub fn get_segment_span(order: &[usize], segment: std::ops::Range<usize>) -> f64 {
+    let positions: Vec<usize> = order
+        .iter()
+        .enumerate()
+        .filter(|(_, card)| segment.contains(card))
+        .map(|(position, _)| position)
+        .collect();
+    match (positions.first(), positions.last()) {
+        (Some(first), Some(last)) => (last - first + 1) as f64 / order.len() as f64,
+        _ => 0f64,
+    }
+}
+
+/// 多次洗牌后取平均
+/// dealt_num为上一靴切牌卡前发出的牌数 跟踪已发出部分中间的segment_size张牌
+pub fn get_cell(
+    name: &str,
+    procedure: EShuffleProcedure,
+    cards_num: usize,
+    dealt_num: usize,
+    segment_size: usize,
+    trials: usize,
+    seed: u64,
+) -> SShuffleTrackingCell {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let dealt_num = dealt_num.min(cards_num);
+    let segment_start = (dealt_num - segment_size.min(dealt_num)) / 2;
+    let segment = segment_start..segment_start + segment_size;
+    let (mut rising_sequences, mut adjacent_survival, mut segment_span) = (0f64, 0f64, 0f64);
+    for _ in 0..trials {
+        let mut order: Vec<usize> = (0..cards_num).collect();
+        procedure.apply(&mut order, dealt_num, &mut rng);
+        rising_sequences += get_rising_sequences(&order) as f64;
+        adjacent_survival += get_adjacent_survival(&order);
+        segment_span += get_segment_span(&order, segment.clone());
+    }
+    let trials = trials.max(1) as f64;
+    SShuffleTrackingCell {
+        procedure: name.to_string(),
+        rising_sequences: rising_sequences / trials,
+        adjacent_survival: adjacent_survival / trials,
+        segment_span: segment_span / trials,
+    }
+}
+
+/// 比较多种洗牌方式 完全随机的结果作为基准
+pub fn get_table(
+    procedures: &[(&str, EShuffleProcedure)],
+    cards_num: usize,
+    dealt_num: usize,
+    segment_size: usize,
+    trials: usize,
+    seed: u64,
+) -> SShuffleTracking {
+    procedures
+        .iter()
+        .map(|(name, procedure)| get_cell(name, *procedure, cards_num, dealt_num, segment_size, trials, seed))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use tabled::Table;
+
+    use super::super::super::super::deck::shuffle::{EShuffleProcedure, SDealerShuffle};
+    use super::{get_adjacent_survival, get_rising_sequences, get_segment_span, get_table};
+
+    #[test]
+    fn test_metrics() {
+        let order: Vec<usize> = (0..10).collect();
+        assert_eq!(get_rising_sequences(&order), 1);
+        assert_eq!(get_adjacent_survival(&order), 1f64);
+        assert_eq!(get_segment_span(&order, 2..5), 0.3);
+
+        let order = vec![0, 5, 1, 6, 2, 7, 3, 8, 4, 9];
+        assert_eq!(get_rising_sequences(&order), 2);
+        assert_eq!(get_adjacent_survival(&order), 0f64);
+        assert_eq!(get_segment_span(&order, 0..5), 0.9);
+    }
+
+    #[test]
+    fn test_table() {
+        let procedures = [
+            ("uniform", EShuffleProcedure::Uniform),
+            ("dealer", EShuffleProcedure::Dealer(SDealerShuffle::default())),
+        ];
+        let table = get_table(&procedures, 312, 234, 52, 200, 0);
+        println!("{}", Table::new(&table));
+        let (uniform, dealer) = (&table[0], &table[1]);
+        assert!(dealer.rising_sequences < uniform.rising_sequences);
+        assert!(dealer.adjacent_survival > uniform.adjacent_survival);
+        assert!(dealer.segment_span < uniform.segment_span);
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use fraction::Fraction;
+use strum::IntoEnumIterator;
+use tabled::Tabled;
+
+use super::super::super::card::ECardPoint;
+use super::super::super::value::EValue;
+
+/// 两张牌的组合 不区分顺序 first不大于second
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct STwoCardCombination {
+    pub first: ECardPoint,
+    pub second: ECardPoint,
+}
+
+impl STwoCardCombination {
+    pub fn new(first: ECardPoint, second: ECardPoint) -> Self {
+        match (first as usize) <= (second as usize) {
+            true => STwoCardCombination { first, second },
+            false => STwoCardCombination {
+                first: second,
+                second: first,
+            },
+        }
+    }
+
+    pub fn value(&self) -> EValue {
+        Into::<EValue>::into(self.first) + self.second
+    }
+
+    /// A和10点牌的组合
+    pub fn is_blackjack(&self) -> bool {
+        self.first == ECardPoint::Ace && self.second == ECardPoint::Ten
+    }
+
+    pub fn is_pair(&self) -> bool {
+        self.first == self.second
+    }
+}
+
+#[derive(Tabled)]
+pub struct STwoCardCombinationDistributionCell {
+    pub first: ECardPoint,
+    pub second: ECardPoint,
+    pub value: EValue,
+    pub probability: Fraction,
+}
+
+pub type STwoCardCombinationDistribution = Vec<STwoCardCombinationDistributionCell>;
+
+/// 从牌库中拿两张牌 各组合出现的概率
+/// 与value_hit_distribution相同 两次拿牌视为独立事件
+pub fn get_map(probability_map: &HashMap<ECardPoint, Fraction>) -> HashMap<STwoCardCombination, Fraction> {
+    let mut map = HashMap::<STwoCardCombination, Fraction>::new();
+    for first in ECardPoint::iter() {
+        for second in ECardPoint::iter() {
+            let prob = *probability_map.get(&first).unwrap() * *probability_map.get(&second).unwrap();
+            *map.entry(STwoCardCombination::new(first, second)).or_insert(Fraction::from(0)) += prob;
+        }
+    }
+    map
+}
+
+pub fn get_table(probability_map: &HashMap<ECardPoint, Fraction>) -> STwoCardCombinationDistribution {
+    let mut table = Vec::<STwoCardCombinationDistributionCell>::new();
+    let map = get_map(probability_map);
+    for first in ECardPoint::iter() {
+        for second in ECardPoint::iter().skip(first as usize) {
+            let combination = STwoCardCombination::new(first, second);
+            table.push(STwoCardCombinationDistributionCell {
+                first,
+                second,
+                value: combination.value(),
+                probability: *map.get(&combination).unwrap(),
+            });
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use fraction::Fraction;
+    use tabled::Table;
+
+    use super::super::super::super::card::ECardPoint;
+    use super::super::super::super::deck::random_deck::SRandomDeck;
+    use super::super::super::super::deck::TDeck;
+    use super::super::super::super::value::EValue;
+    use super::{get_map, get_table, STwoCardCombination};
+
+    #[test]
+    fn test_table() {
+        let deck = SRandomDeck::new(0);
+        let table = get_table(deck.get_point_probability_map());
+        println!("{}", Table::new(&table));
+        assert_eq!(table.len(), 55);
+        let sum = table.iter().fold(Fraction::from(0), |sum, row| sum + row.probability);
+        assert_eq!(Fraction::from(1), sum);
+
+        let map = get_map(deck.get_point_probability_map());
+        let blackjack = STwoCardCombination::new(ECardPoint::Ten, ECardPoint::Ace);
+        assert!(blackjack.is_blackjack());
+        assert_eq!(blackjack.value(), EValue::S21);
+        assert_eq!(*map.get(&blackjack).unwrap(), Fraction::new(8u64, 169u64));
+        let pair = STwoCardCombination::new(ECardPoint::Eight, ECardPoint::Eight);
+        assert!(pair.is_pair());
+        assert_eq!(pair.value(), EValue::H16);
+        assert_eq!(*map.get(&pair).unwrap(), Fraction::new(1u64, 169u64));
+    }
+}
@@ -1,32 +1,55 @@
 use super::card::ECard;
 use super::card::ECardPoint;
 use super::deck::ECardNum;
-use super::rule::SGameRule;
+use super::preset::ERulePreset;
+use super::rule::{ESurrenderRule, SGameRule};
+use super::deck::csm_deck::SCsmDeck;
 use super::deck::diy_deck::SDiyDeck;
 use super::deck::queue_deck::SQueueDeck;
 use super::deck::random_deck::SRandomDeck;
-use super::deck::TDeck;
+use super::deck::{new_seed, TDeck};
 use super::hand::dealer_hand::SDealerHand;
+use super::history::SHandHistoryRecorder;
 use super::hand::player_hand::SPlayerHand;
 use super::player::EPlayerAction;
+use super::seat::{EHandOutcome, SHandSettlement, SSeat, SSeatSettlement};
+use super::side_bet::{SSideBetSettlement, SSideBetWager};
 use super::value::EValue;
 use fraction::{Fraction, ToPrimitive};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, PartialEq)]
+/// 牌桌随机数(切牌卡位置)使用的ChaCha流编号 牌库使用默认的0号流
+const TABLE_RNG_STREAM: u64 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ETableState {
     PlayerBet,
+    // Blackjack Switch 询问player是否交换第index和第index+1手牌的第二张牌
+    PlayerSwitch(usize),
     DealerCheckBlackJack,
     PlayerBuyInsurance,
+    // 询问player的第index个spot是否投降 早投降在庄家检查blackjack之前 晚投降在之后
+    PlayerSurrender(usize),
     // usize代表hand的index
     PlayerSplitOrDoubleDownOrHitOrStand(usize),
+    // 不允许double down时(如split之后不允许double)
+    PlayerSplitOrHitOrStand(usize),
+    // split A之后只能拿一张牌 但允许再次split A
+    PlayerSplitOrStand(usize),
     PlayerDoubleDownOrHitOrStand(usize),
     PlayerHitOrStand(usize),
+    // double down拿牌之后 询问player是否救援(rescue)
+    PlayerRescueOrStand(usize),
     DealerHitOrStand,
     CheckResultAndReset,
+    // 切牌卡已在本局发出 结算后洗牌
+    DealerShuffle,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EPlayerActionError {
     // player action与table状态不符
     ActionStatusError(ETableState, EPlayerAction),
@@ -38,8 +61,18 @@ pub enum EPlayerActionError {
     HandLengthError,
     // 下注时筹码不足
     ChipsNotEnoughError,
+    // spot数量不符合要求
+    SpotsNumError,
     // 不符合Split要求
     SplitError,
+    // 不符合DoubleDown要求
+    DoubleDownError,
+    // 不符合EvenMoney要求(玩家不是blackjack)
+    EvenMoneyError,
+    // 边注未开放 金额不合法或spot不存在
+    SideBetError,
+    // 牌库用尽 剩余的牌不足以发牌或完成本局 需要调用shuffle重新洗牌
+    DeckExhaustedError,
 }
 
 #[derive(Debug)]
@@ -52,9 +85,20 @@ pub struct STable {
     pub state: ETableState,
     pub rule: SGameRule,
     pub dealer_hand: SDealerHand,
+    // 所有座位的手牌 按座位和spot顺序排列
     pub player_hands: Vec<SPlayerHand>,
-    pub player_chips: usize,
+    pub seats: Vec<SSeat>,
+    // 下注和保险阶段 当前需要行动的座位
+    pub current_seat: usize,
     pub deck: Box<dyn TDeck + Sync + Send>,
+    // 切牌卡后面的牌数 剩余牌数不超过该值时表示切牌卡已发出
+    pub cut_card_remain: usize,
+    // 随机数种子 与牌库使用同一个种子 相同种子可以复现整个牌局
+    // 牌桌的随机数使用TABLE_RNG_STREAM流 与牌库的洗牌随机数相互独立
+    pub seed: u64,
+    pub(super) rng: ChaCha12Rng,
+    // 牌局记录 为None时不记录
+    pub history_recorder: Option<SHandHistoryRecorder>,
 }
 
 impl Default for STable {
@@ -64,19 +108,43 @@ impl Default for STable {
         // let player_hands = vec![SPlayerHand::new()];
         // let deck = SRandomDeck::new();
         // STable::new(rule, dealer_hand, player_hands, 0, Box::new(deck))
-        STable::new_queue_deck(1)
+        STable::new_queue_deck(SGameRule::default(), new_seed())
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ETableOutputEvent {
     InitGameWithCards {
-        player_cards: [ECard; 2],
-        dealer_cards: [ECard; 2],
+        // 每个spot的两张牌
+        player_cards: Vec<[ECard; 2]>,
+        // 无底牌规则下庄家只有一张明牌
+        dealer_cards: Vec<ECard>,
+        // 发牌后立即结算的边注
+        side_bet_results: Vec<SSideBetSettlement>,
+    },
+    // 等待下一个座位下注
+    WaitPlayerBet {
+        seat: usize,
     },
     WaitPlayerBuyInsurance,
+    WaitPlayerSurrender,
+    PlayerSurrender {
+        is_surrender: bool,
+    },
+    // 交换后两手牌的cards可以从player_hands中获取
+    PlayerSwitch {
+        hand_index: usize,
+        is_switch: bool,
+    },
+    // 庄家peek之后的保险结果 保险筹码为所有座位的总和 在结算时发放
     InsuranceResult {
         is_dealer_blackjack: bool,
+        insurance_chips: usize,
+        insurance_win_chips: usize,
+    },
+    // 庄家peek发现blackjack 翻开底牌直接进入结算
+    DealerBlackJack {
+        hole_card: ECard,
     },
     PlayerSplitCards {
         card1: ECard,
@@ -90,14 +158,27 @@ pub enum ETableOutputEvent {
     PlayerStand {
         is_player_stop: bool,
     },
+    // double down之后救援 输掉原始下注
+    PlayerRescue {
+        hand_index: usize,
+        is_player_stop: bool,
+    },
     DealerHit {
         card: ECard,
     },
     DealerStand {},
+    // 每个座位分别结算 hands为每手牌的结算详情 按手牌顺序排列
     GameOver {
-        player_chips: usize,
-        bet_chips: usize,
-        win_chips: usize,
+        seats: Vec<SSeatSettlement>,
+        hands: Vec<SHandSettlement>,
+        // 庄家的最终手牌
+        dealer_cards: Vec<ECard>,
+        dealer_value: EValue,
+    },
+    // 洗牌后重新放置切牌卡 并烧掉burn_cards_num张牌
+    Shuffle {
+        burn_cards_num: usize,
+        cut_card_remain: usize,
     },
     WaitForPlayerAction,
 }
@@ -111,42 +192,71 @@ impl STable {
         deck: Box<dyn TDeck + Sync + Send>,
     ) -> Self {
         let state = ETableState::PlayerBet;
-        STable {
+        let seed = deck.seed();
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        // 与牌库的洗牌随机数流区分 同一个种子仍可复现
+        rng.set_stream(TABLE_RNG_STREAM);
+        let mut table = STable {
             state,
             rule,
             dealer_hand,
             player_hands,
-            player_chips,
+            seats: vec![SSeat::new(player_chips)],
+            current_seat: 0,
             deck,
-        }
+            cut_card_remain: 0,
+            seed,
+            rng,
+            history_recorder: None,
+        };
+        table.place_cut_card();
+        table
     }
 
-    pub fn new_random_deck() -> Self {
-        let rule = SGameRule::default();
+    pub fn new_random_deck(rule: SGameRule, seed: u64) -> Self {
         let dealer_hand = SDealerHand::new();
         let player_hands = vec![SPlayerHand::new()];
-        let deck = SRandomDeck::new();
+        let deck = SRandomDeck::new_without(&rule.removed_cards, seed);
         STable::new(rule, dealer_hand, player_hands, 0, Box::new(deck))
     }
 
-    pub fn new_diy_deck(cards: Vec<ECard>) -> Self {
-        let rule = SGameRule::default();
+    pub fn new_diy_deck(cards: Vec<ECard>, rule: SGameRule, seed: u64) -> Self {
         let dealer_hand = SDealerHand::new();
         let player_hands = vec![SPlayerHand::new()];
-        let deck = SDiyDeck::from(cards);
+        let deck = SDiyDeck::from(cards, seed);
         STable::new(rule, dealer_hand, player_hands, 0, Box::new(deck))
     }
 
-    pub fn new_queue_deck(num_of_deck: u8) -> Self {
-        let rule = SGameRule::default();
+    /// 使用预设规则和对应副数的牌库
+    pub fn from_preset(preset: ERulePreset, seed: u64) -> Self {
+        STable::new_queue_deck(preset.rule(), seed)
+    }
+
+    /// 牌副数和洗牌方式由规则的decks_num和shuffle_procedure决定
+    pub fn new_queue_deck(rule: SGameRule, seed: u64) -> Self {
+        let dealer_hand = SDealerHand::new();
+        let player_hands = vec![SPlayerHand::new()];
+        let deck = SQueueDeck::new_without(rule.decks_num, &rule.removed_cards, seed)
+            .with_shuffle_procedure(rule.shuffle_procedure);
+        STable::new(rule, dealer_hand, player_hands, 0, Box::new(deck))
+    }
+
+    /// 连续洗牌机 held_out_num为不在机器内的牌数 超过SCsmDeck::max_held_out_num时按上限处理
+    pub fn new_csm_deck(held_out_num: usize, rule: SGameRule, seed: u64) -> Self {
         let dealer_hand = SDealerHand::new();
         let player_hands = vec![SPlayerHand::new()];
-        let deck = SQueueDeck::new(num_of_deck);
+        let deck = SCsmDeck::new_without(rule.decks_num, &rule.removed_cards, held_out_num, seed);
         STable::new(rule, dealer_hand, player_hands, 0, Box::new(deck))
     }
 
     pub fn reset(&mut self) {
         self.state = ETableState::PlayerBet;
+        self.current_seat = 0;
+        for seat in self.seats.iter_mut() {
+            // 退还未发牌的下注
+            seat.player_chips += seat.bets.drain(..).sum::<usize>();
+            seat.player_chips += seat.side_bets.drain(..).map(|wager| wager.amount).sum::<usize>();
+        }
         self.dealer_hand.reset();
         self.player_hands.clear();
         self.player_hands.push(SPlayerHand::new());
@@ -154,16 +264,103 @@ impl STable {
 
     pub fn shuffle(&mut self) {
         self.reset();
+        self.shuffle_deck();
+    }
+
+    /// 洗牌 重新放置切牌卡并按规则烧牌 没有切牌卡的牌库不烧牌
+    fn shuffle_deck(&mut self) -> ETableOutputEvent {
         self.deck.shuffle();
+        self.place_cut_card();
+        let burn_cards_num = match self.deck.needs_cut_card() {
+            true => self.rule.burn_cards,
+            false => 0,
+        };
+        for _ in 0..burn_cards_num {
+            self.deck.draw();
+        }
+        ETableOutputEvent::Shuffle {
+            burn_cards_num,
+            cut_card_remain: self.cut_card_remain,
+        }
+    }
+
+    /// 根据规则放置切牌卡 位置在±cut_card_jitter张牌的范围内随机偏移
+    fn place_cut_card(&mut self) {
+        if !self.deck.needs_cut_card() {
+            self.cut_card_remain = 0;
+            return;
+        }
+        let cards_num = match self.deck.cards_num() {
+            ECardNum::Some(num) => num,
+            ECardNum::Infinite => 0,
+        };
+        let jitter = self.rule.cut_card_jitter as isize;
+        let jitter = match jitter > 0 {
+            true => self.rng.gen_range(-jitter..=jitter),
+            false => 0,
+        };
+        self.cut_card_remain = self.rule.get_cut_card_remain(cards_num, jitter);
+    }
+
+    /// 切牌卡是否已经发出 无限牌库和没有切牌卡的牌库不需要洗牌
+    pub fn is_cut_card_out(&self) -> bool {
+        if !self.deck.needs_cut_card() {
+            return false;
+        }
+        match self.deck.remain_cards_num() {
+            ECardNum::Some(num) => num <= self.cut_card_remain,
+            ECardNum::Infinite => false,
+        }
     }
 
     pub fn get_point_probability_map(&self) -> &HashMap<ECardPoint, Fraction> {
         self.deck.get_point_probability_map()
     }
 
+    /// 执行player的操作 设置了history_recorder时记录每次操作和结果
+    /// 返回本次操作依次产生的所有事件 庄家行动的状态自动推进 直到需要player操作
     pub fn receive_player_action(
         &mut self,
         action: EPlayerAction,
+    ) -> Result<Vec<ETableOutputEvent>, EPlayerActionError> {
+        let Some(mut recorder) = self.history_recorder.take() else {
+            return self.handle_player_action_and_advance(action);
+        };
+        let state = self.state.clone();
+        recorder.before_action(self);
+        let result = self.handle_player_action_and_advance(action.clone());
+        if let Err(e) = recorder.after_action(self, state, action, &result) {
+            log::error!("failed to write hand history: {e}");
+        }
+        self.history_recorder = Some(recorder);
+        result
+    }
+
+    /// 是否为庄家行动的状态 无需player操作
+    pub fn is_dealer_state(&self) -> bool {
+        matches!(
+            self.state,
+            ETableState::DealerCheckBlackJack
+                | ETableState::DealerHitOrStand
+                | ETableState::CheckResultAndReset
+                | ETableState::DealerShuffle
+        )
+    }
+
+    fn handle_player_action_and_advance(
+        &mut self,
+        action: EPlayerAction,
+    ) -> Result<Vec<ETableOutputEvent>, EPlayerActionError> {
+        let mut events = vec![self.handle_player_action(action)?];
+        while self.is_dealer_state() {
+            events.push(self.handle_player_action(EPlayerAction::WaitNext)?);
+        }
+        Ok(events)
+    }
+
+    fn handle_player_action(
+        &mut self,
+        action: EPlayerAction,
     ) -> Result<ETableOutputEvent, EPlayerActionError> {
         log::info!(
             "SERVER\t--\tserver_state:{:?}\tuser_action:{:?}",
@@ -171,191 +368,208 @@ impl STable {
             action
         );
         match (self.state.clone(), action) {
-            (ETableState::PlayerBet, EPlayerAction::Bet(value)) => {
-                if self.player_hands.len() != 1 {
-                    return Err(EPlayerActionError::HandLengthError);
-                }
-                let hand = self.player_hands.get_mut(0).unwrap();
-                // 下注
-                if self.player_chips <= value {
-                    return Err(EPlayerActionError::ChipsNotEnoughError);
-                }
-                if self.rule.check_bet(value) {
-                    self.player_chips -= value;
-                    hand.bet(value);
-                    // 抽牌
-                    // todo 多路抽牌
-                    let card1 = self.deck.draw().unwrap();
-                    hand.draw(card1);
-                    let card2 = self.deck.draw().unwrap();
-                    self.dealer_hand.draw(card2);
-                    let card3 = self.deck.draw().unwrap();
-                    hand.draw(card3);
-                    let card4 = self.deck.draw().unwrap();
-                    self.dealer_hand.draw(card4);
-                    // 状态转移
-                    self.state = ETableState::DealerCheckBlackJack;
-                    Ok(ETableOutputEvent::InitGameWithCards {
-                        player_cards: [card1, card3],
-                        dealer_cards: [card2, card4],
-                    })
-                } else {
-                    Err(EPlayerActionError::CheckBetError)
-                }
+            (ETableState::PlayerBet, EPlayerAction::Bet(value)) => self.bet(vec![value], vec![]),
+            (ETableState::PlayerBet, EPlayerAction::BetSpots(bets)) => self.bet(bets, vec![]),
+            (ETableState::PlayerBet, EPlayerAction::BetWithSideBets(bets, side_bets)) => {
+                self.bet(bets, side_bets)
             }
+            (ETableState::PlayerBet, EPlayerAction::NoAction) => self.bet(vec![], vec![]),
             (ETableState::PlayerBuyInsurance, EPlayerAction::BuyInsurance(value)) => {
-                if self.player_hands.len() != 1 {
-                    return Err(EPlayerActionError::HandLengthError);
-                }
-                let hand = self.player_hands.get_mut(0).unwrap();
+                let seat = self.current_seat;
                 if value != 0 {
                     // 排除不买保险的情况
-                    if self.rule.check_insurance(hand.get_bet(), value) {
+                    if self.seats[seat].player_chips <= value {
+                        return Err(EPlayerActionError::ChipsNotEnoughError);
+                    }
+                    // 保险上限按该座位所有spot的总下注计算 记在该座位的第一手牌上
+                    let total_bet = self
+                        .player_hands
+                        .iter()
+                        .filter(|hand| hand.seat == seat)
+                        .map(|hand| hand.get_bet())
+                        .sum();
+                    if self.rule.check_insurance(total_bet, value) {
+                        let hand = self.player_hands.iter_mut().find(|hand| hand.seat == seat).unwrap();
                         hand.insurance(value);
-                        self.player_chips -= value;
+                        self.seats[seat].player_chips -= value;
                     } else {
                         return Err(EPlayerActionError::CheckInsuranceError);
                     }
                 }
-                // 状态转移
-                // self.state = ETableState::DealerCheckInsurance;
-                // self.run();
-
-                // 判断是否blackjack
-                let second_card = self.dealer_hand.hand.cards.get(1).unwrap().clone();
-                if EValue::from(second_card.into()) == EValue::H10 {
-                    // blackjack 直接进入结算状态
-                    // 状态转移
-                    self.state = ETableState::CheckResultAndReset;
-                    let win_insurance_amount = ((self.rule.insurance_pay + 1)
-                        * Fraction::from(value))
-                    .floor()
-                    .to_usize()
-                    .unwrap();
-                    self.player_chips += win_insurance_amount;
-                    println!("保险成功 赢得:{win_insurance_amount}");
-                    Ok(ETableOutputEvent::InsuranceResult {
-                        is_dealer_blackjack: true,
-                    })
-                } else {
-                    // 非blackjack 进入用户操作状态
-                    if self.player_hands.get(0).unwrap().hand.cards.len() != 2 {
-                        return Err(EPlayerActionError::HandLengthError);
-                    }
-                    // 状态转移
-                    if self.player_hands.get(0).unwrap().should_split() {
-                        self.state = ETableState::PlayerSplitOrDoubleDownOrHitOrStand(0);
-                    } else {
-                        self.state = ETableState::PlayerDoubleDownOrHitOrStand(0);
-                    }
-                    println!("保险失败");
-                    Ok(ETableOutputEvent::InsuranceResult {
-                        is_dealer_blackjack: false,
-                    })
+                Ok(self.enter_insurance_state_from(seat + 1))
+            }
+            (ETableState::PlayerBuyInsurance, EPlayerAction::EvenMoney) => {
+                // 该座位所有blackjack的spot均选择even money
+                let seat = self.current_seat;
+                let mut flag_has_blackjack = false;
+                for hand in self
+                    .player_hands
+                    .iter_mut()
+                    .filter(|hand| hand.seat == seat && hand.is_blackjack())
+                {
+                    hand.even_money();
+                    flag_has_blackjack = true;
+                }
+                if !flag_has_blackjack {
+                    return Err(EPlayerActionError::EvenMoneyError);
+                }
+                Ok(self.enter_insurance_state_from(seat + 1))
+            }
+            (ETableState::PlayerSwitch(index), EPlayerAction::Switch) => {
+                let (left, right) = self.player_hands.split_at_mut(index + 1);
+                left[index].switch(&mut right[0]);
+                // 询问下一对手牌是否交换
+                self.enter_switch_state_from(index + 2);
+                Ok(ETableOutputEvent::PlayerSwitch {
+                    hand_index: index,
+                    is_switch: true,
+                })
+            }
+            (ETableState::PlayerSwitch(index), EPlayerAction::NoAction) => {
+                self.enter_switch_state_from(index + 2);
+                Ok(ETableOutputEvent::PlayerSwitch {
+                    hand_index: index,
+                    is_switch: false,
+                })
+            }
+            (ETableState::PlayerSurrender(index), EPlayerAction::Surrender) => {
+                let hand = self.player_hands.get_mut(index).unwrap();
+                if hand.hand.cards.len() != 2 {
+                    return Err(EPlayerActionError::HandLengthError);
                 }
+                hand.surrender();
+                // 询问下一个spot是否投降
+                self.enter_surrender_state_from(index + 1);
+                Ok(ETableOutputEvent::PlayerSurrender { is_surrender: true })
             }
-            (ETableState::PlayerSplitOrDoubleDownOrHitOrStand(index), EPlayerAction::Split) => {
+            (ETableState::PlayerSurrender(index), EPlayerAction::NoAction) => {
+                // 不投降 询问下一个spot是否投降
+                self.enter_surrender_state_from(index + 1);
+                Ok(ETableOutputEvent::PlayerSurrender {
+                    is_surrender: false,
+                })
+            }
+            (
+                ETableState::PlayerSplitOrDoubleDownOrHitOrStand(index)
+                | ETableState::PlayerSplitOrHitOrStand(index)
+                | ETableState::PlayerSplitOrStand(index),
+                EPlayerAction::Split,
+            ) => {
                 if self.player_hands.len() <= index {
                     return Err(EPlayerActionError::HandLengthError);
                 }
+                // 判断是否免费split 免费时新手牌的下注由庄家出资
+                let hand = self.player_hands.get(index).unwrap();
+                let seat = hand.seat;
+                let is_free_split = self.rule.check_free_split(hand.hand.cards[0].value);
                 // 判断chips是否足够
-                let hand = self.player_hands.get_mut(index).unwrap();
-                if self.player_chips <= hand.get_bet() {
+                if !is_free_split && self.seats[seat].player_chips <= hand.get_wager() {
                     return Err(EPlayerActionError::ChipsNotEnoughError);
                 }
 
+                // 判断是否符合split规则
                 let hand = self.player_hands.get(index).unwrap();
-                if hand.hand.cards.len() != 2
-                    || hand.hand.cards.get(0).unwrap().value
-                        != hand.hand.cards.get(1).unwrap().value
-                {
+                if !hand.should_split(&self.rule, self.spot_hands_num(hand.seat, hand.spot)) {
                     return Err(EPlayerActionError::SplitError);
                 }
+                // 先抽牌 牌库用尽时不改变手牌和筹码
+                let card1 = draw_card(self.deck.as_mut())?;
+                let card2 = draw_card(self.deck.as_mut())?;
                 let old_hand = self.player_hands.get_mut(index).unwrap();
-                let mut new_hand = SPlayerHand::from(old_hand.split());
-                new_hand.bet(old_hand.get_bet());
-                self.player_chips -= old_hand.get_bet();
+                let mut new_hand = old_hand.split();
+                match is_free_split {
+                    true => new_hand.free_bet = old_hand.get_wager(),
+                    false => {
+                        new_hand.bet(old_hand.get_wager());
+                        self.seats[seat].player_chips -= old_hand.get_wager();
+                    }
+                }
                 // 发牌
-                let card1 = self.deck.draw().unwrap();
-                let card2 = self.deck.draw().unwrap();
                 old_hand.draw(card1);
                 new_hand.draw(card2);
-                // 新的手牌插入队列
-                self.player_hands.push(new_hand);
-                // 状态转移
-                self.state = ETableState::PlayerDoubleDownOrHitOrStand(index);
+                // 新的手牌插入到当前手牌之后
+                self.player_hands.insert(index + 1, new_hand);
+                // 状态转移 split A只拿一张牌时 手牌可能无需继续操作
+                self.enter_hand_state_from(index);
                 Ok(ETableOutputEvent::PlayerSplitCards { card1, card2 })
             }
             (
                 ETableState::PlayerSplitOrDoubleDownOrHitOrStand(index)
                 | ETableState::PlayerDoubleDownOrHitOrStand(index),
-                EPlayerAction::DoubleDown,
+                action @ (EPlayerAction::DoubleDown | EPlayerAction::DoubleDownForLess(_)),
             ) => {
                 let hand = self.player_hands.get_mut(index).unwrap();
+                // 免费double 追加的下注由庄家出资
+                if matches!(action, EPlayerAction::DoubleDown) && self.rule.check_free_double(hand.value()) {
+                    let card = draw_card(self.deck.as_mut())?;
+                    hand.free_double_down(card);
+                    let is_player_stop = self.enter_hand_state_from(index + 1);
+                    return Ok(ETableOutputEvent::PlayerDrawCard {
+                        card,
+                        hand_index: index,
+                        is_player_stop,
+                    });
+                }
+                // 追加筹码 默认追加与原下注相同的筹码
+                let amount = match action {
+                    EPlayerAction::DoubleDownForLess(amount) => {
+                        if !self.rule.check_double_for_less(hand.get_wager(), amount) {
+                            return Err(EPlayerActionError::DoubleDownError);
+                        }
+                        amount
+                    }
+                    _ => hand.get_wager(),
+                };
                 // 判断chips是否足够
-                if self.player_chips <= hand.get_bet() {
+                let seat = &mut self.seats[hand.seat];
+                if seat.player_chips <= amount {
                     return Err(EPlayerActionError::ChipsNotEnoughError);
                 }
-                self.player_chips -= hand.get_bet();
-                let card = self.deck.draw().unwrap();
-                hand.double_down(card);
-                // 判断是否有下一手牌
-                if index + 1 < self.player_hands.len() {
-                    if self.player_hands.get(index + 1).unwrap().should_split() {
-                        self.state = ETableState::PlayerSplitOrDoubleDownOrHitOrStand(index + 1);
-                    } else {
-                        self.state = ETableState::PlayerDoubleDownOrHitOrStand(index + 1);
-                    }
-                    Ok(ETableOutputEvent::PlayerDrawCard {
+                let card = draw_card(self.deck.as_mut())?;
+                seat.player_chips -= amount;
+                hand.double_down(amount, card);
+                // 允许救援时 未bust且不足21点的手牌需要询问是否救援
+                if self.rule.double_down_rescue
+                    && !hand.is_bust()
+                    && hand.point() < 21
+                    && !hand.is_charlie(&self.rule)
+                {
+                    self.state = ETableState::PlayerRescueOrStand(index);
+                    return Ok(ETableOutputEvent::PlayerDrawCard {
                         card,
                         hand_index: index,
                         is_player_stop: false,
-                    })
-                } else {
-                    // 没有下一手牌 进入dealer行动环节
-                    self.state = ETableState::DealerHitOrStand;
-                    Ok(ETableOutputEvent::PlayerDrawCard {
-                        card,
-                        hand_index: index,
-                        is_player_stop: true,
-                    })
+                    });
                 }
+                // 判断是否有下一手牌 没有下一手牌时进入dealer行动环节
+                let is_player_stop = self.enter_hand_state_from(index + 1);
+                Ok(ETableOutputEvent::PlayerDrawCard {
+                    card,
+                    hand_index: index,
+                    is_player_stop,
+                })
             }
             (
                 ETableState::PlayerSplitOrDoubleDownOrHitOrStand(index)
+                | ETableState::PlayerSplitOrHitOrStand(index)
                 | ETableState::PlayerDoubleDownOrHitOrStand(index)
                 | ETableState::PlayerHitOrStand(index),
                 EPlayerAction::Hit,
             ) => {
                 let hand = self.player_hands.get_mut(index).unwrap();
-                let card = self.deck.draw().unwrap();
+                let card = draw_card(self.deck.as_mut())?;
                 hand.draw(card);
-                // 判断是否bust
-                if hand.is_bust() {
-                    // 当前牌bust 判断是否有下一手牌
-                    if index + 1 < self.player_hands.len() {
-                        if self.player_hands.get(index + 1).unwrap().should_split() {
-                            self.state =
-                                ETableState::PlayerSplitOrDoubleDownOrHitOrStand(index + 1);
-                        } else {
-                            self.state = ETableState::PlayerDoubleDownOrHitOrStand(index + 1);
-                        }
-                        Ok(ETableOutputEvent::PlayerDrawCard {
-                            card,
-                            hand_index: index,
-                            is_player_stop: false,
-                        })
-                    } else {
-                        // 没有下一手牌 进入dealer行动环节
-                        self.state = ETableState::DealerHitOrStand;
-                        Ok(ETableOutputEvent::PlayerDrawCard {
-                            card,
-                            hand_index: index,
-                            is_player_stop: true,
-                        })
-                    }
+                // 判断是否bust或Charlie
+                if hand.is_bust() || hand.is_charlie(&self.rule) {
+                    // 当前牌结束 判断是否有下一手牌 没有下一手牌时进入dealer行动环节
+                    let is_player_stop = self.enter_hand_state_from(index + 1);
+                    Ok(ETableOutputEvent::PlayerDrawCard {
+                        card,
+                        hand_index: index,
+                        is_player_stop,
+                    })
                 } else {
-                    // 当前牌未bust
+                    // 当前牌未结束
                     self.state = ETableState::PlayerHitOrStand(index);
                     Ok(ETableOutputEvent::PlayerDrawCard {
                         card,
@@ -366,62 +580,57 @@ impl STable {
             }
             (
                 ETableState::PlayerSplitOrDoubleDownOrHitOrStand(index)
+                | ETableState::PlayerSplitOrHitOrStand(index)
+                | ETableState::PlayerSplitOrStand(index)
                 | ETableState::PlayerDoubleDownOrHitOrStand(index)
-                | ETableState::PlayerHitOrStand(index),
+                | ETableState::PlayerHitOrStand(index)
+                | ETableState::PlayerRescueOrStand(index),
                 EPlayerAction::Stand,
             ) => {
-                // 判断是否有下一手牌
-                if index + 1 < self.player_hands.len() {
-                    if self.player_hands.get(index + 1).unwrap().should_split() {
-                        self.state = ETableState::PlayerSplitOrDoubleDownOrHitOrStand(index + 1);
-                    } else {
-                        self.state = ETableState::PlayerDoubleDownOrHitOrStand(index + 1);
-                    }
-                    Ok(ETableOutputEvent::PlayerStand {
-                        is_player_stop: false,
-                    })
-                } else {
-                    // 没有下一手牌 进入dealer行动环节
-                    self.state = ETableState::DealerHitOrStand;
-                    Ok(ETableOutputEvent::PlayerStand {
-                        is_player_stop: true,
-                    })
-                }
+                // 判断是否有下一手牌 没有下一手牌时进入dealer行动环节
+                let is_player_stop = self.enter_hand_state_from(index + 1);
+                Ok(ETableOutputEvent::PlayerStand { is_player_stop })
+            }
+            (ETableState::PlayerRescueOrStand(index), EPlayerAction::Surrender) => {
+                self.player_hands.get_mut(index).unwrap().rescue();
+                let is_player_stop = self.enter_hand_state_from(index + 1);
+                Ok(ETableOutputEvent::PlayerRescue {
+                    hand_index: index,
+                    is_player_stop,
+                })
             }
             (table_state, EPlayerAction::WaitNext) => {
-                println!("DEBUG - table_state:\t{table_state:?}");
+                log::debug!("table_state:\t{table_state:?}");
                 match table_state {
                     ETableState::DealerCheckBlackJack => {
                         if self.dealer_hand.hand.cards.len() != 2 {
                             return Err(EPlayerActionError::HandLengthError);
                         }
                         // 判断是否buy insurance
-                        let first_card = self.dealer_hand.hand.cards.get(0).unwrap().clone();
-                        if EValue::from(first_card.into()) == EValue::S11 {
-                            // 状态转移
-                            self.state = ETableState::PlayerBuyInsurance;
-                            Ok(ETableOutputEvent::WaitPlayerBuyInsurance)
+                        let first_card: ECardPoint = self.dealer_hand.hand.cards[0].into();
+                        if first_card == ECardPoint::Ace {
+                            // 状态转移 从第一个座位开始询问保险
+                            Ok(self.enter_insurance_state_from(0))
                         } else {
-                            // 判断是否 Dealer BlackJack
-                            if self.dealer_hand.point() == 21 {
+                            // 明牌为10点时peek 判断是否 Dealer BlackJack
+                            if first_card == ECardPoint::Ten && self.is_dealer_blackjack() {
                                 // blackjack 直接进行结算
                                 self.state = ETableState::CheckResultAndReset;
-                                Ok(ETableOutputEvent::DealerStand {})
+                                Ok(ETableOutputEvent::DealerBlackJack {
+                                    hole_card: self.dealer_hand.hand.cards[1],
+                                })
                             } else {
                                 // 非blackjack 进入用户操作状态
-                                if self.player_hands.len() != 1
-                                    || self.player_hands.get(0).unwrap().hand.cards.len() != 2
-                                {
+                                if self.player_hands.iter().any(|hand| hand.hand.cards.len() != 2) {
                                     return Err(EPlayerActionError::HandLengthError);
                                 }
                                 // 状态转移
-                                if self.player_hands.get(0).unwrap().should_split() {
-                                    self.state =
-                                        ETableState::PlayerSplitOrDoubleDownOrHitOrStand(0);
+                                self.enter_player_turn();
+                                if let ETableState::PlayerSurrender(_) = self.state {
+                                    Ok(ETableOutputEvent::WaitPlayerSurrender)
                                 } else {
-                                    self.state = ETableState::PlayerDoubleDownOrHitOrStand(0);
+                                    Ok(ETableOutputEvent::WaitForPlayerAction)
                                 }
-                                Ok(ETableOutputEvent::WaitForPlayerAction)
                             }
                         }
                     }
@@ -430,23 +639,36 @@ impl STable {
                         // 当Player的所有hand均bust或者black时，无需拿牌
                         let mut flag_is_player_all_bust_or_bj = true;
                         for hand in &self.player_hands {
-                            if !hand.is_blackjack() && !hand.is_bust() {
+                            if !hand.is_blackjack()
+                                && !hand.is_bust()
+                                && !hand.is_surrender()
+                                && !hand.is_even_money()
+                                && !hand.is_charlie(&self.rule)
+                            {
                                 flag_is_player_all_bust_or_bj = false;
                                 break;
                             }
                         }
 
+                        // 无底牌规则下 庄家先补第二张牌 Player全部bust且没有保险时无需补牌
+                        if self.dealer_hand.hand.cards.len() == 1
+                            && self.player_hands.iter().any(|hand| !hand.is_bust() || hand.insurance > 0)
+                        {
+                            let card = draw_card(self.deck.as_mut())?;
+                            self.dealer_hand.draw(card);
+                            return Ok(ETableOutputEvent::DealerHit { card });
+                        }
+
                         if !flag_is_player_all_bust_or_bj {
-                            // 不足17且不爆牌时 继续拿牌
+                            // 不足17且不爆牌时 继续拿牌 软17根据规则决定
                             let hand = &mut self.dealer_hand;
-                            let point = hand.point();
-                            let is_dealer_stop = point >= 17 || point == 1;
+                            let is_dealer_stop = self.rule.is_dealer_stop(hand.value());
                             // 状态转移
                             if is_dealer_stop {
                                 self.state = ETableState::CheckResultAndReset;
                                 Ok(ETableOutputEvent::DealerStand {})
                             } else {
-                                let card = self.deck.draw().unwrap();
+                                let card = draw_card(self.deck.as_mut())?;
                                 hand.draw(card);
                                 Ok(ETableOutputEvent::DealerHit { card })
                             }
@@ -456,6 +678,11 @@ impl STable {
                         }
                     }
                     ETableState::CheckResultAndReset => Ok(self.check_result_and_reset()),
+                    ETableState::DealerShuffle => {
+                        let event = self.shuffle_deck();
+                        self.state = ETableState::PlayerBet;
+                        Ok(event)
+                    }
                     _ => Ok(ETableOutputEvent::WaitForPlayerAction),
                 }
             }
@@ -483,8 +710,35 @@ impl STable {
         self.state.clone()
     }
 
+    /// 为第一个座位购买筹码
     pub fn buy_chips(&mut self, chips: usize) {
-        self.player_chips += chips;
+        self.buy_seat_chips(0, chips);
+    }
+
+    pub fn buy_seat_chips(&mut self, seat: usize, chips: usize) {
+        self.seats[seat].player_chips += chips;
+    }
+
+    /// 新增一个座位 返回座位的index
+    pub fn add_seat(&mut self, player_chips: usize) -> usize {
+        self.seats.push(SSeat::new(player_chips));
+        self.seats.len() - 1
+    }
+
+    /// 获取当前需要行动的座位 庄家行动和结算时为None
+    pub fn get_current_seat(&self) -> Option<usize> {
+        match self.state {
+            ETableState::PlayerBet | ETableState::PlayerBuyInsurance => Some(self.current_seat),
+            ETableState::PlayerSwitch(index)
+            | ETableState::PlayerSurrender(index)
+            | ETableState::PlayerSplitOrDoubleDownOrHitOrStand(index)
+            | ETableState::PlayerSplitOrHitOrStand(index)
+            | ETableState::PlayerSplitOrStand(index)
+            | ETableState::PlayerDoubleDownOrHitOrStand(index)
+            | ETableState::PlayerHitOrStand(index)
+            | ETableState::PlayerRescueOrStand(index) => Some(self.player_hands.get(index).unwrap().seat),
+            _ => None,
+        }
     }
 
     pub fn reset_player_hand(&mut self) {
@@ -502,80 +756,487 @@ impl STable {
     //         && self.player_hands.get(0).unwrap().value() == EValue::S21
     // }
 
+    /// 当前座位下注 bets为空表示本局不参与
+    /// 所有座位下注结束后 按照赌场顺序发牌
+    fn bet(
+        &mut self,
+        bets: Vec<usize>,
+        side_bets: Vec<SSideBetWager>,
+    ) -> Result<ETableOutputEvent, EPlayerActionError> {
+        if self.player_hands.len() != 1 {
+            return Err(EPlayerActionError::HandLengthError);
+        }
+        let seat = self.current_seat;
+        // Blackjack Switch 每个下注发两手牌 两手牌的下注相同 边注下在第一手牌上
+        let (bets, side_bets) = match self.rule.switch {
+            true => (
+                bets.iter().flat_map(|bet| [*bet, *bet]).collect(),
+                side_bets
+                    .into_iter()
+                    .map(|wager| SSideBetWager {
+                        spot: wager.spot * 2,
+                        ..wager
+                    })
+                    .collect(),
+            ),
+            false => (bets, side_bets),
+        };
+        if !bets.is_empty() {
+            // 所有座位的spot总数不能超过上限
+            let spots_num = self.seats.iter().map(|seat| seat.bets.len()).sum::<usize>() + bets.len();
+            if !self.rule.check_spots(bets.len()) || !self.rule.check_spots(spots_num) {
+                return Err(EPlayerActionError::SpotsNumError);
+            }
+            // 下注 主注和边注一起扣除
+            let amount = bets.iter().sum::<usize>()
+                + side_bets.iter().map(|wager| wager.amount).sum::<usize>();
+            if self.seats[seat].player_chips <= amount {
+                return Err(EPlayerActionError::ChipsNotEnoughError);
+            }
+            if !bets.iter().all(|bet| self.rule.check_bet(*bet)) {
+                return Err(EPlayerActionError::CheckBetError);
+            }
+            if !side_bets.iter().all(|wager| {
+                wager.spot < bets.len() && self.rule.check_side_bet(wager.side_bet, wager.amount)
+            }) {
+                return Err(EPlayerActionError::SideBetError);
+            }
+            self.seats[seat].player_chips -= amount;
+            self.seats[seat].bets = bets;
+            self.seats[seat].side_bets = side_bets;
+        } else if !side_bets.is_empty() {
+            // 不下主注时不能下边注
+            return Err(EPlayerActionError::SideBetError);
+        } else if self.seats.len() == 1 {
+            // 只有一个座位时必须下注
+            return Err(EPlayerActionError::SpotsNumError);
+        }
+        // 等待下一个座位下注
+        self.current_seat += 1;
+        if self.current_seat < self.seats.len() {
+            return Ok(ETableOutputEvent::WaitPlayerBet {
+                seat: self.current_seat,
+            });
+        }
+        self.current_seat = 0;
+        // 所有座位均未下注 重新开始下注
+        if self.seats.iter().all(|seat| seat.bets.is_empty()) {
+            return Ok(ETableOutputEvent::WaitPlayerBet { seat: 0 });
+        }
+        self.deal()
+    }
+
+    /// 按照赌场顺序发牌
+    /// 每个spot依次拿第一张牌 庄家拿明牌 每个spot依次拿第二张牌 庄家拿底牌
+    /// 牌库剩余的牌不足以完成发牌时退还下注 返回DeckExhaustedError
+    fn deal(&mut self) -> Result<ETableOutputEvent, EPlayerActionError> {
+        let hands_num: usize = self.seats.iter().map(|seat| seat.bets.len()).sum();
+        let dealer_cards_num = if self.rule.is_no_hole_card() { 1 } else { 2 };
+        if let ECardNum::Some(remain) = self.deck.remain_cards_num() {
+            if remain < hands_num * 2 + dealer_cards_num {
+                self.reset();
+                return Err(EPlayerActionError::DeckExhaustedError);
+            }
+        }
+        self.player_hands.clear();
+        for (seat_index, seat) in self.seats.iter_mut().enumerate() {
+            for (spot, bet) in seat.bets.drain(..).enumerate() {
+                let mut hand = SPlayerHand::new();
+                hand.seat = seat_index;
+                hand.spot = spot;
+                hand.bet(bet);
+                for wager in seat.side_bets.iter().filter(|wager| wager.spot == spot) {
+                    hand.side_bet(wager.side_bet, wager.amount);
+                }
+                self.player_hands.push(hand);
+            }
+            seat.side_bets.clear();
+        }
+        // 抽牌
+        for hand in self.player_hands.iter_mut() {
+            hand.draw(draw_card(self.deck.as_mut())?);
+        }
+        let up_card = draw_card(self.deck.as_mut())?;
+        self.dealer_hand.draw(up_card);
+        for hand in self.player_hands.iter_mut() {
+            hand.draw(draw_card(self.deck.as_mut())?);
+        }
+        let mut dealer_cards = vec![up_card];
+        // 无底牌规则下 庄家在玩家行动结束后才拿第二张牌
+        if !self.rule.is_no_hole_card() {
+            let hole_card = draw_card(self.deck.as_mut())?;
+            self.dealer_hand.draw(hole_card);
+            dealer_cards.push(hole_card);
+        }
+        // 状态转移 Blackjack Switch需要先询问是否交换
+        match self.rule.switch {
+            true => self.enter_switch_state_from(0),
+            false => self.enter_dealt_state(),
+        }
+        let player_cards = self
+            .player_hands
+            .iter()
+            .map(|hand| [hand.hand.cards[0], hand.hand.cards[1]])
+            .collect();
+        let side_bet_results = self.settle_side_bets(&dealer_cards);
+        Ok(ETableOutputEvent::InitGameWithCards {
+            player_cards,
+            dealer_cards,
+            side_bet_results,
+        })
+    }
+
+    /// 发牌后立即结算所有边注 筹码直接返还给座位
+    fn settle_side_bets(&mut self, dealer_cards: &[ECard]) -> Vec<SSideBetSettlement> {
+        let mut settlements = Vec::new();
+        for hand in self.player_hands.iter() {
+            let player_cards = [hand.hand.cards[0], hand.hand.cards[1]];
+            for (side_bet, amount) in hand.side_bets.iter() {
+                let result = side_bet.settle(&player_cards, dealer_cards);
+                let win_chips = match self.rule.get_side_bet_rule(*side_bet) {
+                    Some(side_bet_rule) => side_bet_rule.get_win(*amount, result),
+                    None => 0,
+                };
+                self.seats[hand.seat].player_chips += win_chips;
+                settlements.push(SSideBetSettlement {
+                    seat: hand.seat,
+                    spot: hand.spot,
+                    side_bet: *side_bet,
+                    amount: *amount,
+                    result,
+                    win_chips,
+                });
+            }
+        }
+        settlements
+    }
+
+    /// 发牌(以及交换)结束后的状态转移 早投降需要在庄家检查blackjack之前询问
+    /// 庄家不peek时 明牌为A依然询问保险 保险在结算时赔付
+    fn enter_dealt_state(&mut self) {
+        if self.rule.surrender == ESurrenderRule::Early {
+            self.enter_surrender_state_from(0);
+        } else if !self.rule.is_dealer_peek() {
+            match self.is_dealer_up_card_ace() {
+                true => {
+                    self.enter_insurance_state_from(0);
+                }
+                false => self.enter_player_turn(),
+            }
+        } else {
+            self.state = ETableState::DealerCheckBlackJack;
+        }
+    }
+
+    /// 从第index手牌开始 询问player是否交换每对手牌的第二张牌
+    /// 每个下注的两手牌相邻 index为偶数时指向一对手牌的第一手
+    fn enter_switch_state_from(&mut self, index: usize) {
+        if index + 1 < self.player_hands.len() {
+            self.state = ETableState::PlayerSwitch(index);
+        } else {
+            self.enter_dealt_state();
+        }
+    }
+
+    /// 从第seat个座位开始 询问有下注的座位是否购买保险
+    /// 所有座位询问结束后 庄家peek并公布保险结果 庄家不peek时直接进入用户操作状态
+    fn enter_insurance_state_from(&mut self, seat: usize) -> ETableOutputEvent {
+        for i in seat..self.seats.len() {
+            if self.player_hands.iter().any(|hand| hand.seat == i) {
+                self.current_seat = i;
+                self.state = ETableState::PlayerBuyInsurance;
+                return ETableOutputEvent::WaitPlayerBuyInsurance;
+            }
+        }
+        self.current_seat = 0;
+        if !self.rule.is_dealer_peek() {
+            self.enter_player_turn();
+            return match self.state {
+                ETableState::PlayerSurrender(_) => ETableOutputEvent::WaitPlayerSurrender,
+                _ => ETableOutputEvent::WaitForPlayerAction,
+            };
+        }
+        // 判断是否blackjack
+        let is_dealer_blackjack = self.is_dealer_blackjack();
+        if is_dealer_blackjack {
+            // blackjack 直接进入结算状态
+            self.state = ETableState::CheckResultAndReset;
+        } else {
+            // 非blackjack 进入用户操作状态
+            self.enter_player_turn();
+        }
+        let insurance_chips = self.player_hands.iter().map(|hand| hand.insurance).sum();
+        ETableOutputEvent::InsuranceResult {
+            is_dealer_blackjack,
+            insurance_chips,
+            insurance_win_chips: self.get_insurance_win(insurance_chips, is_dealer_blackjack),
+        }
+    }
+
+    /// 第seat个座位第spot个spot当前的手牌数量(包括split产生的手牌)
+    fn spot_hands_num(&self, seat: usize, spot: usize) -> usize {
+        self.player_hands
+            .iter()
+            .filter(|hand| hand.seat == seat && hand.spot == spot)
+            .count()
+    }
+
+    /// 根据规则 获取第index手牌的初始操作状态
+    /// 返回None表示该手牌无需操作(split A之后只能拿一张牌、blackjack、已投降、even money、Charlie)
+    fn hand_state(&self, index: usize) -> Option<ETableState> {
+        let hand = self.player_hands.get(index).unwrap();
+        if hand.is_blackjack()
+            || hand.is_surrender()
+            || hand.is_even_money()
+            || hand.is_charlie(&self.rule)
+        {
+            return None;
+        }
+        let can_split = hand.should_split(&self.rule, self.spot_hands_num(hand.seat, hand.spot));
+        if hand.is_split_ace() && self.rule.split_aces_one_card {
+            return match can_split {
+                true => Some(ETableState::PlayerSplitOrStand(index)),
+                false => None,
+            };
+        }
+        let can_double_down = hand.can_double_down(&self.rule);
+        Some(match (can_split, can_double_down) {
+            (true, true) => ETableState::PlayerSplitOrDoubleDownOrHitOrStand(index),
+            (true, false) => ETableState::PlayerSplitOrHitOrStand(index),
+            (false, true) => ETableState::PlayerDoubleDownOrHitOrStand(index),
+            (false, false) => ETableState::PlayerHitOrStand(index),
+        })
+    }
+
+    /// 从第index手牌开始 寻找下一手需要操作的手牌并进行状态转移
+    /// 没有需要操作的手牌时 进入dealer行动环节
+    /// return: bool true表示player行动结束
+    fn enter_hand_state_from(&mut self, index: usize) -> bool {
+        for i in index..self.player_hands.len() {
+            if let Some(state) = self.hand_state(i) {
+                self.state = state;
+                return false;
+            }
+        }
+        self.enter_dealer_turn();
+        true
+    }
+
+    /// player行动结束 进入dealer行动环节
+    /// 所有spot均已投降时直接结算 但庄家不peek时晚投降需要确认庄家是否blackjack
+    fn enter_dealer_turn(&mut self) {
+        let is_all_surrender = self.player_hands.iter().all(|hand| hand.is_surrender());
+        let need_check_blackjack =
+            !self.rule.is_dealer_peek() && self.rule.surrender == ESurrenderRule::Late;
+        if is_all_surrender && !need_check_blackjack {
+            self.state = ETableState::CheckResultAndReset;
+        } else {
+            self.state = ETableState::DealerHitOrStand;
+        }
+    }
+
+    /// 从第index个spot开始 询问player是否投降
+    /// 所有spot询问结束后 早投降继续检查blackjack 晚投降进入用户操作状态
+    fn enter_surrender_state_from(&mut self, index: usize) {
+        for i in index..self.player_hands.len() {
+            if !self.player_hands.get(i).unwrap().is_blackjack() {
+                self.state = ETableState::PlayerSurrender(i);
+                return;
+            }
+        }
+        // 庄家不peek时无需检查blackjack
+        let is_all_surrender = self.player_hands.iter().all(|hand| hand.is_surrender());
+        if self.rule.surrender == ESurrenderRule::Early
+            && self.rule.is_dealer_peek()
+            && !is_all_surrender
+        {
+            self.state = ETableState::DealerCheckBlackJack;
+        } else {
+            self.enter_hand_state_from(0);
+        }
+    }
+
+    /// 庄家非blackjack 进入player行动环节
+    /// 晚投降规则下先询问player是否投降
+    fn enter_player_turn(&mut self) {
+        if self.rule.surrender == ESurrenderRule::Late {
+            self.enter_surrender_state_from(0);
+        } else {
+            self.enter_hand_state_from(0);
+        }
+    }
+
+    fn is_dealer_up_card_ace(&self) -> bool {
+        let up_card: ECardPoint = self.dealer_hand.hand.cards[0].value.into();
+        up_card == ECardPoint::Ace
+    }
+
     #[inline]
     fn is_dealer_blackjack(&self) -> bool {
         self.dealer_hand.is_blackjack()
     }
 
+    /// 保险赢得的筹码(包括退还的保险本金) 庄家非blackjack时为0
+    fn get_insurance_win(&self, insurance: usize, is_dealer_blackjack: bool) -> usize {
+        match is_dealer_blackjack {
+            true => ((self.rule.insurance_pay + 1) * Fraction::from(insurance))
+                .floor()
+                .to_usize()
+                .unwrap(),
+            false => 0,
+        }
+    }
+
     fn check_result_and_reset(&mut self) -> ETableOutputEvent {
-        // 判断结果
-        let mut win_chips_amount = 0;
-        let mut bet_chips_amount = 0;
+        // 每个座位分别结算
+        let mut settlements = vec![SSeatSettlement::default(); self.seats.len()];
 
         let dealer_point = self.dealer_hand.point();
         let is_dealer_blackjack = self.is_dealer_blackjack();
+        // 庄家22点平局规则
+        let is_dealer_22_push = self.rule.dealer_22_push && self.dealer_hand.is_bust_22();
 
         // 计算输赢
-        for player_hand in &mut self.player_hands {
-            // 计算下注总筹码量(包括下注数量和保险)
-            bet_chips_amount += player_hand.betting_box + player_hand.insurance;
-
-            let is_player_blackjack = player_hand.is_blackjack();
-            let is_player_bust = player_hand.is_bust();
-            let player_point = player_hand.point();
-
-            if is_player_bust || dealer_point > player_point {
-                // 玩家失败情况
-                if is_dealer_blackjack {
-                    // 计算保险
-                    win_chips_amount += ((self.rule.insurance_pay)
-                        * Fraction::from(player_hand.insurance))
-                    .floor()
-                    .to_usize()
-                    .unwrap();
-                }
-            } else if dealer_point == player_point {
-                // 平局情况
-                win_chips_amount += player_hand.get_bet();
-            } else {
-                // 玩家获胜情况
-                win_chips_amount += match is_player_blackjack {
-                    true => {
-                        ((self.rule.blackjack_pay) * Fraction::from(player_hand.get_bet()))
-                            .floor()
-                            .to_usize()
-                            .unwrap()
-                            + player_hand.get_bet()
-                    }
-                    false => player_hand.get_bet() * 2,
-                };
-            }
+        let mut hands = Vec::with_capacity(self.player_hands.len());
+        for (index, player_hand) in self.player_hands.iter().enumerate() {
+            // 分别计算主注和保险的筹码量
+            let settlement = &mut settlements[player_hand.seat];
+            settlement.bet_chips += player_hand.betting_box;
+            settlement.insurance_chips += player_hand.insurance;
+            settlement.insurance_win_chips +=
+                self.get_insurance_win(player_hand.insurance, is_dealer_blackjack);
+            let (outcome, payout) =
+                self.settle_hand(index, dealer_point, is_dealer_blackjack, is_dealer_22_push);
+            settlement.win_chips += payout;
+            hands.push(SHandSettlement {
+                seat: player_hand.seat,
+                spot: player_hand.spot,
+                outcome,
+                wager: player_hand.get_bet(),
+                payout,
+                value: player_hand.value(),
+                cards: player_hand.hand.cards.clone(),
+            });
         }
-        self.player_chips += win_chips_amount;
+        for (seat, settlement) in self.seats.iter_mut().zip(settlements.iter_mut()) {
+            seat.player_chips += settlement.win_chips + settlement.insurance_win_chips;
+            settlement.player_chips = seat.player_chips;
+        }
+
+        let dealer_cards = self.dealer_hand.hand.cards.clone();
+        let dealer_value = self.dealer_hand.value();
 
         // 重置状态
         self.reset_dealer_hand();
         self.reset_player_hand();
+        self.deck.recycle();
 
-        // 判断是否shuffle
-        let cards_num = match self.deck.cards_num() {
-            ECardNum::Some(num) => num,
-            ECardNum::Infinite => 1,
+        // 状态转移 切牌卡已发出时 本局结束后洗牌
+        self.state = match self.is_cut_card_out() {
+            true => ETableState::DealerShuffle,
+            false => ETableState::PlayerBet,
         };
-        let cards_remain = match self.deck.remain_cards_num() {
-            ECardNum::Some(num) => num,
-            ECardNum::Infinite => 1,
-        };
-        if self.rule.check_threshold(cards_num, cards_remain) {
-            self.deck.shuffle();
-        }
-        
-        // 状态转移
-        self.state = ETableState::PlayerBet;
         ETableOutputEvent::GameOver {
-            bet_chips: bet_chips_amount,
-            win_chips: win_chips_amount,
-            player_chips: self.player_chips,
+            seats: settlements,
+            hands,
+            dealer_cards,
+            dealer_value,
+        }
+    }
+
+    /// 结算第index手牌 返回结算结果和返还给玩家的筹码
+    fn settle_hand(
+        &self,
+        index: usize,
+        dealer_point: u8,
+        is_dealer_blackjack: bool,
+        is_dealer_22_push: bool,
+    ) -> (EHandOutcome, usize) {
+        let player_hand = &self.player_hands[index];
+        if player_hand.is_even_money() {
+            // even money 按1:1赔付
+            return (EHandOutcome::EvenMoney, player_hand.get_bet() * 2);
+        }
+
+        if player_hand.is_rescue() {
+            // 救援 输掉原始下注 退还double追加的筹码
+            let payout = match is_dealer_blackjack {
+                true => 0,
+                false => player_hand.double_down_amount,
+            };
+            return (EHandOutcome::Rescue, payout);
+        }
+
+        if player_hand.is_surrender() {
+            // 投降 退还一半下注 晚投降时庄家blackjack则输掉全部下注
+            let payout = match self.rule.surrender == ESurrenderRule::Late && is_dealer_blackjack {
+                true => 0,
+                false => Fraction::new(player_hand.get_bet() as u64, 2u64)
+                    .floor()
+                    .to_usize()
+                    .unwrap(),
+            };
+            return (EHandOutcome::Surrender, payout);
+        }
+
+        let is_player_blackjack = player_hand.is_blackjack();
+        let is_player_bust = player_hand.is_bust();
+        let player_point = player_hand.point();
+        // 规则允许时 玩家的21点总是获胜
+        let is_player_21_win =
+            self.rule.player_21_always_wins && !is_player_blackjack && player_point == 21;
+        // Charlie自动获胜 只输给庄家blackjack
+        let is_player_charlie = player_hand.is_charlie(&self.rule);
+        let lose_outcome = match is_player_bust {
+            true => EHandOutcome::Bust,
+            false => EHandOutcome::Lose,
+        };
+
+        if is_dealer_blackjack && !is_player_blackjack && self.rule.is_original_bets_only() {
+            // 无底牌OBO规则 每个spot只输掉原始下注 退还double和split追加的筹码
+            let is_spot_first_hand = index == 0
+                || self.player_hands[index - 1].seat != player_hand.seat
+                || self.player_hands[index - 1].spot != player_hand.spot;
+            let payout = match is_spot_first_hand {
+                true => player_hand.get_bet() - player_hand.get_original_bet(),
+                false => player_hand.get_bet(),
+            };
+            (lose_outcome, payout)
+        } else if (is_dealer_blackjack && !is_player_blackjack)
+            || (!is_player_charlie && (is_player_bust || dealer_point > player_point))
+        {
+            // 玩家失败情况
+            (lose_outcome, 0)
+        } else if !is_player_charlie
+            && ((dealer_point == player_point && !is_player_21_win)
+                || (is_dealer_22_push && !is_player_blackjack))
+        {
+            // 平局情况 免费下注不退还
+            (EHandOutcome::Push, player_hand.get_bet())
+        } else {
+            // 玩家获胜情况
+            // Charlie和21点奖金代替1:1赔付
+            let pay = match (is_player_blackjack, is_player_charlie) {
+                (true, _) => Some(self.rule.blackjack_pay),
+                (false, true) => Some(self.rule.charlie_pay),
+                (false, false) => player_hand.get_bonus_21_pay(&self.rule),
+            };
+            let payout = match pay {
+                Some(pay) => {
+                    (pay * Fraction::from(player_hand.get_bet()))
+                        .floor()
+                        .to_usize()
+                        .unwrap()
+                        + player_hand.get_bet()
+                }
+                None => player_hand.get_bet() * 2,
+            };
+            // 免费下注获胜时按1:1赔付
+            let outcome = match is_player_blackjack {
+                true => EHandOutcome::BlackJack,
+                false => EHandOutcome::Win,
+            };
+            (outcome, payout + player_hand.free_bet)
         }
     }
 
@@ -583,3 +1244,8 @@ impl STable {
         self.deck.remain_cards_num()
     }
 }
+
+// 从牌库抽一张牌 牌库用尽时返回DeckExhaustedError
+fn draw_card(deck: &mut (dyn TDeck + Sync + Send)) -> Result<ECard, EPlayerActionError> {
+    deck.draw().ok_or(EPlayerActionError::DeckExhaustedError)
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::server::history::{load, SHandHistoryRecorder};
+    use crate::server::player::static_strategy_player::SStaticStrategyPlayer;
+    use crate::server::preset::ERulePreset;
+    use crate::server::replay::replay_file;
+    use crate::server::rule::SGameRule;
+    use crate::server::table::{ETableOutputEvent, STable};
+
+    const GOLDEN_ROUNDS: usize = 10;
+
+    fn golden_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/server/tests/golden").join(name)
+    }
+
+    /// 每个golden文件对应的牌桌 两个座位 固定种子
+    fn golden_tables() -> Vec<(&'static str, STable)> {
+        let rule = SGameRule {
+            cut_card_jitter: 3,
+            burn_cards: 1,
+            ..SGameRule::default()
+        };
+        let mut tables = vec![
+            ("single_deck.jsonl", STable::new_queue_deck(rule, 1)),
+            ("spanish21.jsonl", STable::from_preset(ERulePreset::Spanish21, 2)),
+            ("free_bet.jsonl", STable::from_preset(ERulePreset::FreeBet6D, 3)),
+            ("switch.jsonl", STable::from_preset(ERulePreset::BlackjackSwitch6D, 4)),
+        ];
+        for (_, table) in tables.iter_mut() {
+            table.buy_chips(10000);
+            table.add_seat(10000);
+        }
+        tables
+    }
+
+    /// 状态机的行为有意修改后 运行
+    /// cargo test update_golden_histories -- --ignored
+    /// 重新生成golden文件
+    #[test]
+    #[ignore]
+    fn update_golden_histories() {
+        let player = SStaticStrategyPlayer::new();
+        for (name, mut table) in golden_tables() {
+            let path = golden_path(name);
+            let _ = std::fs::remove_file(&path);
+            table.history_recorder = Some(SHandHistoryRecorder::open(&path).unwrap());
+            let mut round = 0;
+            while round < GOLDEN_ROUNDS {
+                let events = table.receive_player_action(player.action(&table)).unwrap();
+                if events.iter().any(|event| matches!(event, ETableOutputEvent::GameOver { .. })) {
+                    round += 1;
+                }
+            }
+        }
+    }
+
+    /// 回放golden文件 每次操作的状态和输出事件都需要与记录一致
+    #[test]
+    fn test_golden_histories() {
+        for (name, _) in golden_tables() {
+            let path = golden_path(name);
+            assert_eq!(load(&path).unwrap().len(), GOLDEN_ROUNDS, "{name}");
+            let errors = replay_file(&path).unwrap();
+            for error in errors.iter() {
+                println!("{name}: {error}");
+            }
+            assert!(errors.is_empty(), "{name} regressed");
+        }
+    }
+}
@@ -1 +1,2 @@
-mod table_tests;
\ No newline at end of file
+mod table_tests;
+mod golden_tests;
\ No newline at end of file
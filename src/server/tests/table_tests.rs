@@ -2,7 +2,25 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::server::{card::{ECard, ECardColor, ECardNumber}, player::EPlayerAction, table::STable};
+    use crate::server::{card::{ECard, ECardColor, ECardNumber, ECardPoint}, player::EPlayerAction, table::STable};
+    use crate::server::{side_bet::{ESideBet, ESideBetResult, SSideBetRule, SSideBetWager}, table::EPlayerActionError};
+    use crate::server::seat::EHandOutcome;
+    use strum::IntoEnumIterator;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha12Rng;
+    use crate::server::preset::ERulePreset;
+    use crate::server::deck::ECardNum;
+    use crate::server::deck::shuffle::{EShuffleProcedure, SDealerShuffle};
+    use crate::server::player::static_strategy_player::SStaticStrategyPlayer;
+    use crate::server::{rule::{EDealerSoft17Rule, SGameRule, EDoubleDownRule, EHoleCardRule, ESurrenderRule}, table::{ETableOutputEvent, ETableState}, value::EValue};
+
+    /// 取出一次操作产生的结算事件
+    fn game_over(events: Vec<ETableOutputEvent>) -> ETableOutputEvent {
+        events
+            .into_iter()
+            .find(|event| matches!(event, ETableOutputEvent::GameOver { .. }))
+            .expect("no GameOver event")
+    }
 
     #[tokio::test]
     async fn test_diy_deck() {
@@ -45,16 +63,12 @@ mod tests {
             },
         ];
         // let mut deck = SDiyDeck::from(v);
-        let mut table = STable::new_diy_deck(cards);
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
         table.buy_chips(10000);
         println!("player action: bet 2");
         let result = table.receive_player_action(EPlayerAction::Bet(2));
         println!("player bet result:{result:?}");
 
-        println!("player action: wait");
-        let result = table.receive_player_action(EPlayerAction::WaitNext);
-        println!("player wait result:{result:?}");
-
         // println!("player action: buy insurance");
         // let result = table.receive_player_action(EPlayerAction::BuyInsurance(0));
         // println!("player buy insurance result:{result:?}");
@@ -63,14 +77,6 @@ mod tests {
         let result = table.receive_player_action(EPlayerAction::Stand);
         println!("player stand result:{result:?}");
 
-        println!("player action: wait");
-        let result = table.receive_player_action(EPlayerAction::WaitNext);
-        println!("player wait result:{result:?}");
-
-        println!("player action: wait");
-        let result = table.receive_player_action(EPlayerAction::WaitNext);
-        println!("player wait result:{result:?}");
-
     }
 
     
@@ -115,16 +121,12 @@ mod tests {
             },
         ];
         // let mut deck = SDiyDeck::from(v);
-        let mut table = STable::new_diy_deck(cards);
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
         table.buy_chips(10000);
         println!("player action: bet 2");
         let result = table.receive_player_action(EPlayerAction::Bet(2));
         println!("player bet result:{result:?}");
 
-        println!("player action: wait");
-        let result = table.receive_player_action(EPlayerAction::WaitNext);
-        println!("player wait result:{result:?}");
-
         // println!("player action: buy insurance");
         // let result = table.receive_player_action(EPlayerAction::BuyInsurance(0));
         // println!("player buy insurance result:{result:?}");
@@ -133,14 +135,6 @@ mod tests {
         let result = table.receive_player_action(EPlayerAction::Hit);
         println!("player hit result:{result:?}");
 
-        println!("player action: wait");
-        let result = table.receive_player_action(EPlayerAction::WaitNext);
-        println!("player wait result:{result:?}");
-
-        println!("player action: wait");
-        let result = table.receive_player_action(EPlayerAction::WaitNext);
-        println!("player wait result:{result:?}");
-
     }
     
     #[tokio::test]
@@ -184,16 +178,12 @@ mod tests {
             },
         ];
         // let mut deck = SDiyDeck::from(v);
-        let mut table = STable::new_diy_deck(cards);
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
         table.buy_chips(10000);
         println!("player action: bet 2");
         let result = table.receive_player_action(EPlayerAction::Bet(2));
         println!("player bet result:{result:?}");
 
-        println!("player action: wait");
-        let result = table.receive_player_action(EPlayerAction::WaitNext);
-        println!("player wait result:{result:?}");
-
         // println!("player action: buy insurance");
         // let result = table.receive_player_action(EPlayerAction::BuyInsurance(0));
         // println!("player buy insurance result:{result:?}");
@@ -202,13 +192,1429 @@ mod tests {
         let result = table.receive_player_action(EPlayerAction::Stand);
         println!("player stand result:{result:?}");
 
-        println!("player action: wait");
-        let result = table.receive_player_action(EPlayerAction::WaitNext);
-        println!("player wait result:{result:?}");
+    }
+
+    /// 庄家软17 H17规则下继续拿牌 S17规则下停牌
+    #[tokio::test]
+    async fn test_diy_deck_dealer_soft_17() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ace,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Two,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+        ];
+        for (soft_17_rule, dealer_final_value) in [
+            (EDealerSoft17Rule::Stand, EValue::S17),
+            (EDealerSoft17Rule::Hit, EValue::S19),
+        ] {
+            let mut table = STable::new_diy_deck(cards.clone(), SGameRule::default(), 0);
+            table.rule.dealer_soft_17 = soft_17_rule;
+            table.buy_chips(10000);
+            table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+            // 玩家停牌后庄家自动拿牌直到停牌
+            let events = table.receive_player_action(EPlayerAction::Stand).unwrap();
+            println!("player stand result:{events:?}");
+            assert!(events.iter().any(|event| matches!(event, ETableOutputEvent::DealerStand {})));
+            let ETableOutputEvent::GameOver { dealer_value, .. } = game_over(events) else {
+                unreachable!()
+            };
+            assert_eq!(dealer_value, dealer_final_value);
+        }
+    }
+
+    /// 晚投降 庄家检查blackjack之后投降 退还一半下注
+    #[tokio::test]
+    async fn test_diy_deck_late_surrender() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Nine,
+            },
+        ];
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.rule.surrender = ESurrenderRule::Late;
+        table.buy_chips(10000);
+        // 发牌后庄家自动检查blackjack 再询问是否投降
+        let result = table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+        println!("player bet result:{result:?}");
+        assert!(matches!(result[0], ETableOutputEvent::InitGameWithCards { .. }));
+        assert_eq!(table.get_state(), ETableState::PlayerSurrender(0));
+        let result = table.receive_player_action(EPlayerAction::Surrender).unwrap();
+        println!("player surrender result:{result:?}");
+        let ETableOutputEvent::GameOver { hands, .. } = game_over(result) else {
+            unreachable!()
+        };
+        assert_eq!(hands[0].outcome, EHandOutcome::Surrender);
+        assert_eq!(hands[0].payout, 2);
+        assert_eq!(table.seats[0].player_chips, 10000 - 2);
+    }
+
+    /// 早投降 庄家blackjack时依然只损失一半下注
+    #[tokio::test]
+    async fn test_diy_deck_early_surrender() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ace,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::King,
+            },
+        ];
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.rule.surrender = ESurrenderRule::Early;
+        table.buy_chips(10000);
+        table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerSurrender(0));
+        let result = table.receive_player_action(EPlayerAction::Surrender).unwrap();
+        println!("player surrender result:{result:?}");
+        assert_eq!(table.seats[0].player_chips, 10000 - 2);
+    }
+
+    /// 不投降时继续正常流程
+    #[tokio::test]
+    async fn test_diy_deck_decline_surrender() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Nine,
+            },
+        ];
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.rule.surrender = ESurrenderRule::Early;
+        table.buy_chips(10000);
+        table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+        // 不投降后庄家自动检查blackjack
+        let result = table.receive_player_action(EPlayerAction::NoAction).unwrap();
+        assert!(matches!(
+            result[..],
+            [ETableOutputEvent::PlayerSurrender { is_surrender: false }, ..]
+        ));
+        assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(0));
+    }
+
+    /// split A之后每手牌只拿一张牌 且A+10不算blackjack
+    #[tokio::test]
+    async fn test_diy_deck_split_aces() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ace,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Nine,
+            },
+            ECard {
+                color: ECardColor::Spades,
+                value: ECardNumber::Ace,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Seven,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::King,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Five,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+        ];
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.rule.resplit_aces = false;
+        table.rule.split_aces_one_card = true;
+        table.buy_chips(10000);
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerSplitOrDoubleDownOrHitOrStand(0));
+        let result = table.receive_player_action(EPlayerAction::Split).unwrap();
+        println!("player split result:{result:?}");
+        // 两手牌均只能拿一张牌 直接进入dealer行动环节并结算
+        let ETableOutputEvent::GameOver { hands, .. } = game_over(result) else {
+            unreachable!()
+        };
+        // 庄家bust 两手牌均按1:1赔付 A+10不算blackjack
+        assert!(hands.iter().all(|hand| hand.outcome == EHandOutcome::Win));
+        assert_eq!(table.seats[0].player_chips, 10000 + 4);
+    }
+
+    /// split手牌数量上限以及split之后是否允许double down
+    #[tokio::test]
+    async fn test_diy_deck_split_rules() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Eight,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Nine,
+            },
+            ECard {
+                color: ECardColor::Spades,
+                value: ECardNumber::Eight,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Seven,
+            },
+            ECard {
+                color: ECardColor::Clubs,
+                value: ECardNumber::Eight,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Three,
+            },
+        ];
+        for (split_max_hands, double_after_split, state) in [
+            (4, true, ETableState::PlayerSplitOrDoubleDownOrHitOrStand(0)),
+            (4, false, ETableState::PlayerSplitOrHitOrStand(0)),
+            (2, true, ETableState::PlayerDoubleDownOrHitOrStand(0)),
+            (2, false, ETableState::PlayerHitOrStand(0)),
+        ] {
+            let mut table = STable::new_diy_deck(cards.clone(), SGameRule::default(), 0);
+            table.rule.split_max_hands = split_max_hands;
+            table.rule.double_after_split = double_after_split;
+            table.buy_chips(10000);
+            table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+            table.receive_player_action(EPlayerAction::Split).unwrap();
+            assert_eq!(table.player_hands.len(), 2);
+            assert_eq!(table.player_hands.get(0).unwrap().value(), EValue::H16);
+            assert_eq!(table.get_state(), state);
+        }
+    }
+
+    /// double down点数限制以及double for less
+    #[tokio::test]
+    async fn test_diy_deck_double_down_rules() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Four,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Seven,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+        ];
+        // 10点 9-11规则下允许double 10-11规则下允许double
+        for (double_down_rule, state) in [
+            (EDoubleDownRule::Any, ETableState::PlayerDoubleDownOrHitOrStand(0)),
+            (EDoubleDownRule::NineToEleven, ETableState::PlayerDoubleDownOrHitOrStand(0)),
+            (EDoubleDownRule::TenToEleven, ETableState::PlayerDoubleDownOrHitOrStand(0)),
+        ] {
+            let mut table = STable::new_diy_deck(cards.clone(), SGameRule::default(), 0);
+            table.rule.double_down = double_down_rule;
+            table.buy_chips(10000);
+            table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+            assert_eq!(table.get_state(), state);
+        }
+
+        // double for less 追加1个筹码
+        let mut table = STable::new_diy_deck(cards.clone(), SGameRule::default(), 0);
+        table.rule.double_for_less = true;
+        table.buy_chips(10000);
+        table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+        assert!(table
+            .receive_player_action(EPlayerAction::DoubleDownForLess(5))
+            .is_err());
+        let result = table
+            .receive_player_action(EPlayerAction::DoubleDownForLess(1))
+            .unwrap();
+        // 玩家20点 庄家17点
+        let ETableOutputEvent::GameOver { hands, .. } = game_over(result) else {
+            unreachable!()
+        };
+        assert_eq!(hands[0].wager, 5);
+        assert_eq!(hands[0].outcome, EHandOutcome::Win);
+        assert_eq!(table.seats[0].player_chips, 10000 + 5);
+    }
 
-        println!("player action: wait");
-        let result = table.receive_player_action(EPlayerAction::WaitNext);
-        println!("player wait result:{result:?}");
+    /// 不符合double down点数限制时 只能hit或stand
+    #[tokio::test]
+    async fn test_diy_deck_double_down_restricted() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Seven,
+            },
+        ];
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.rule.double_down = EDoubleDownRule::NineToEleven;
+        table.buy_chips(10000);
+        table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerHitOrStand(0));
+        assert!(table.receive_player_action(EPlayerAction::DoubleDown).is_err());
+    }
+
+    /// 无底牌规则 庄家在玩家行动结束后才拿第二张牌
+    /// 庄家blackjack时 NoHoleCard输掉double的全部下注 NoHoleCardOBO只输掉原始下注
+    #[tokio::test]
+    async fn test_diy_deck_no_hole_card() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Five,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Nine,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ace,
+            },
+        ];
+        for (hole_card_rule, player_chips) in [
+            (EHoleCardRule::NoHoleCard, 10000 - 8),
+            (EHoleCardRule::NoHoleCardOBO, 10000 - 4),
+        ] {
+            let mut table = STable::new_diy_deck(cards.clone(), SGameRule::default(), 0);
+            table.rule.hole_card = hole_card_rule;
+            table.buy_chips(10000);
+            let result = table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+            println!("player bet result:{result:?}");
+            assert_eq!(table.dealer_hand.hand.cards.len(), 1);
+            assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(0));
+            let result = table.receive_player_action(EPlayerAction::DoubleDown).unwrap();
+            println!("player double down result:{result:?}");
+            // 庄家补第二张牌后为blackjack
+            let ETableOutputEvent::GameOver {
+                dealer_cards,
+                dealer_value,
+                ..
+            } = game_over(result)
+            else {
+                unreachable!()
+            };
+            assert_eq!(dealer_cards.len(), 2);
+            assert_eq!(dealer_value, EValue::S21);
+            assert_eq!(table.seats[0].player_chips, player_chips);
+        }
+    }
+
+    /// 无底牌规则下明牌为A时依然询问保险 庄家补第二张牌后结算保险
+    #[tokio::test]
+    async fn test_diy_deck_no_hole_card_insurance() {
+        for (second_card, insurance_win_chips, player_chips) in [
+            (ECardNumber::King, 6, 10000 - 4 - 2 + 6),
+            (ECardNumber::Seven, 0, 10000 - 2 + 4),
+        ] {
+            let cards = [ECardNumber::Ten, ECardNumber::Ace, ECardNumber::Nine, second_card]
+                .into_iter()
+                .map(|value| ECard {
+                    color: ECardColor::Hearts,
+                    value,
+                })
+                .collect();
+            let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+            table.rule.hole_card = EHoleCardRule::NoHoleCard;
+            table.buy_chips(10000);
+            table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+            assert_eq!(table.dealer_hand.hand.cards.len(), 1);
+            assert_eq!(table.get_state(), ETableState::PlayerBuyInsurance);
+            // 庄家不peek 买保险后直接进入用户操作状态
+            let result = table.receive_player_action(EPlayerAction::BuyInsurance(2)).unwrap();
+            assert!(matches!(result[..], [ETableOutputEvent::WaitForPlayerAction]));
+            assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(0));
+            let result = table.receive_player_action(EPlayerAction::Stand).unwrap();
+            println!("player stand result:{result:?}");
+            let ETableOutputEvent::GameOver { seats, .. } = game_over(result) else {
+                unreachable!()
+            };
+            assert_eq!(seats[0].insurance_chips, 2);
+            assert_eq!(seats[0].insurance_win_chips, insurance_win_chips);
+            assert_eq!(table.seats[0].player_chips, player_chips);
+        }
+    }
+
+    /// 庄家peek 遍历所有明牌与底牌的组合
+    /// 明牌为A时先询问保险 明牌为10点且底牌为A时直接结算
+    #[tokio::test]
+    async fn test_diy_deck_dealer_peek() {
+        for up_card in ECardNumber::iter() {
+            for hole_card in ECardNumber::iter() {
+                let cards = vec![
+                    ECard {
+                        color: ECardColor::Hearts,
+                        value: ECardNumber::Ten,
+                    },
+                    ECard {
+                        color: ECardColor::Spades,
+                        value: up_card,
+                    },
+                    ECard {
+                        color: ECardColor::Hearts,
+                        value: ECardNumber::Seven,
+                    },
+                    ECard {
+                        color: ECardColor::Spades,
+                        value: hole_card,
+                    },
+                ];
+                let up_card_point: ECardPoint = up_card.into();
+                let hole_card_point: ECardPoint = hole_card.into();
+                let is_dealer_blackjack = matches!(
+                    (up_card_point, hole_card_point),
+                    (ECardPoint::Ace, ECardPoint::Ten) | (ECardPoint::Ten, ECardPoint::Ace)
+                );
+
+                let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+                table.buy_chips(10000);
+                // 发牌后庄家自动peek
+                let result = table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+                println!("up card:{up_card:?}\thole card:{hole_card:?}\tresult:{result:?}");
+                let result = match up_card_point {
+                    ECardPoint::Ace => {
+                        assert_eq!(table.get_state(), ETableState::PlayerBuyInsurance);
+                        let result = table.receive_player_action(EPlayerAction::BuyInsurance(0)).unwrap();
+                        assert!(matches!(
+                            result[0],
+                            ETableOutputEvent::InsuranceResult { is_dealer_blackjack: x, .. } if x == is_dealer_blackjack
+                        ));
+                        result
+                    }
+                    _ => result,
+                };
+                if is_dealer_blackjack {
+                    if up_card_point == ECardPoint::Ten {
+                        assert!(result.iter().any(|event| matches!(
+                            event,
+                            ETableOutputEvent::DealerBlackJack { hole_card: x } if x.value == hole_card
+                        )));
+                    }
+                    // 庄家blackjack时直接结算
+                    game_over(result);
+                    assert_eq!(table.seats[0].player_chips, 10000 - 4);
+                } else {
+                    assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(0));
+                }
+            }
+        }
+    }
+
+    /// 关闭peek 庄家blackjack在玩家行动之后才翻开 double的下注全部输掉
+    #[tokio::test]
+    async fn test_diy_deck_dealer_no_peek() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::King,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Five,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ace,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+        ];
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.rule.dealer_peek = false;
+        table.buy_chips(10000);
+        table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(0));
+        let result = table.receive_player_action(EPlayerAction::DoubleDown).unwrap();
+        println!("player double down result:{result:?}");
+        let ETableOutputEvent::GameOver { hands, .. } = game_over(result) else {
+            unreachable!()
+        };
+        assert_eq!(hands[0].outcome, EHandOutcome::Lose);
+        assert_eq!(table.seats[0].player_chips, 10000 - 8);
+    }
+
+    /// 部分保险 保险与主注分别结算
+    #[tokio::test]
+    async fn test_diy_deck_partial_insurance() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ace,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Seven,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Queen,
+            },
+        ];
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.buy_chips(10000);
+        table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerBuyInsurance);
+        // 超过insurance_max
+        assert!(table.receive_player_action(EPlayerAction::BuyInsurance(3)).is_err());
+        // 玩家不是blackjack 不能even money
+        assert!(table.receive_player_action(EPlayerAction::EvenMoney).is_err());
+        let result = table.receive_player_action(EPlayerAction::BuyInsurance(1)).unwrap();
+        println!("player insurance result:{result:?}");
+        assert!(matches!(
+            result[0],
+            ETableOutputEvent::InsuranceResult {
+                is_dealer_blackjack: true,
+                insurance_chips: 1,
+                insurance_win_chips: 3,
+            }
+        ));
+        let ETableOutputEvent::GameOver { seats, .. } = game_over(result) else {
+            unreachable!()
+        };
+        assert_eq!(seats[0].bet_chips, 4);
+        assert_eq!(seats[0].win_chips, 0);
+        assert_eq!(seats[0].insurance_chips, 1);
+        assert_eq!(seats[0].insurance_win_chips, 3);
+        assert_eq!(table.seats[0].player_chips, 10000 - 4 - 1 + 3);
+    }
+
+    /// 玩家blackjack 庄家明牌为A时选择even money
+    #[tokio::test]
+    async fn test_diy_deck_even_money() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ace,
+            },
+            ECard {
+                color: ECardColor::Spades,
+                value: ECardNumber::Ace,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::King,
+            },
+            ECard {
+                color: ECardColor::Spades,
+                value: ECardNumber::King,
+            },
+        ];
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.buy_chips(10000);
+        table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerBuyInsurance);
+        let result = table.receive_player_action(EPlayerAction::EvenMoney).unwrap();
+        println!("player even money result:{result:?}");
+        // 庄家blackjack 依然按1:1赔付
+        let ETableOutputEvent::GameOver { hands, .. } = game_over(result) else {
+            unreachable!()
+        };
+        assert_eq!(hands[0].outcome, EHandOutcome::EvenMoney);
+        assert_eq!(table.seats[0].player_chips, 10000 + 4);
+    }
+
+    /// 多个spot同时下注 按赌场顺序发牌 依次行动并分别结算
+    #[tokio::test]
+    async fn test_diy_deck_multi_spots() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Five,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Eight,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Nine,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+        ];
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.buy_chips(10000);
+        assert!(table
+            .receive_player_action(EPlayerAction::BetSpots(vec![1; 8]))
+            .is_err());
+        let result = table
+            .receive_player_action(EPlayerAction::BetSpots(vec![2, 4]))
+            .unwrap();
+        println!("player bet result:{result:?}");
+        if let ETableOutputEvent::InitGameWithCards {
+            player_cards,
+            dealer_cards,
+            ..
+        } = &result[0]
+        {
+            assert_eq!(player_cards.len(), 2);
+            assert_eq!(player_cards[0][1].value, ECardNumber::Eight);
+            assert_eq!(player_cards[1][0].value, ECardNumber::Five);
+            assert_eq!(dealer_cards[0].value, ECardNumber::Six);
+        } else {
+            panic!("unexpected result:{result:?}");
+        }
+        assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(0));
+        table.receive_player_action(EPlayerAction::Stand).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(1));
+        let result = table.receive_player_action(EPlayerAction::DoubleDown).unwrap();
+        println!("player double down result:{result:?}");
+        // 最后一手牌行动结束后 庄家依次补牌并结算
+        let ETableOutputEvent::GameOver { hands, .. } = game_over(result) else {
+            unreachable!()
+        };
+        assert_eq!(hands.len(), 2);
+        // 庄家bust 第一个spot赢2 第二个spot double后赢8
+        assert_eq!(table.seats[0].player_chips, 10000 + 2 + 8);
+    }
+
+    /// 多个spot时 split产生的手牌紧跟在原手牌之后
+    #[tokio::test]
+    async fn test_diy_deck_multi_spots_split() {
+        let cards = vec![
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Eight,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Six,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Eight,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Seven,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Ten,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Two,
+            },
+            ECard {
+                color: ECardColor::Hearts,
+                value: ECardNumber::Three,
+            },
+        ];
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.buy_chips(10000);
+        table
+            .receive_player_action(EPlayerAction::BetSpots(vec![2, 2]))
+            .unwrap();
+        assert_eq!(
+            table.get_state(),
+            ETableState::PlayerSplitOrDoubleDownOrHitOrStand(0)
+        );
+        table.receive_player_action(EPlayerAction::Split).unwrap();
+        assert_eq!(table.player_hands.len(), 3);
+        assert_eq!(table.player_hands[1].spot, 0);
+        assert_eq!(table.player_hands[1].value(), EValue::H11);
+        assert_eq!(table.player_hands[2].spot, 1);
+        assert_eq!(table.player_hands[2].value(), EValue::H17);
+        assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(0));
+    }
+
+    /// split后的手牌分别结算 GameOver中包含每手牌的结果和庄家的最终手牌
+    #[tokio::test]
+    async fn test_diy_deck_hand_settlements() {
+        let cards: Vec<ECard> = [
+            ECardNumber::Eight,
+            ECardNumber::Ten,
+            ECardNumber::Ten,
+            ECardNumber::Eight,
+            ECardNumber::Nine,
+            ECardNumber::Nine,
+            ECardNumber::Three,
+            ECardNumber::Ten,
+            ECardNumber::Five,
+        ]
+        .into_iter()
+        .map(|value| ECard {
+            color: ECardColor::Hearts,
+            value,
+        })
+        .collect();
+        let mut table = STable::new_diy_deck(cards.clone(), SGameRule::default(), 0);
+        table.buy_chips(10000);
+        table
+            .receive_player_action(EPlayerAction::BetSpots(vec![2, 2]))
+            .unwrap();
+        // spot 0的8,8 split为8,3和8,10 第一手11点停牌 第二手拿5 bust spot 1的19点停牌
+        table.receive_player_action(EPlayerAction::Split).unwrap();
+        table.receive_player_action(EPlayerAction::Stand).unwrap();
+        table.receive_player_action(EPlayerAction::Hit).unwrap();
+        let result = table.receive_player_action(EPlayerAction::Stand).unwrap();
+        println!("player stand result:{result:?}");
+        let ETableOutputEvent::GameOver {
+            seats,
+            hands,
+            dealer_cards,
+            dealer_value,
+        } = game_over(result)
+        else {
+            unreachable!()
+        };
+        assert_eq!(dealer_cards, vec![cards[2], cards[5]]);
+        assert_eq!(dealer_value, EValue::H19);
+        let outcomes: Vec<_> = hands
+            .iter()
+            .map(|hand| (hand.spot, hand.outcome, hand.wager, hand.payout, hand.value))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                (0, EHandOutcome::Lose, 2, 0, EValue::H11),
+                (0, EHandOutcome::Bust, 2, 0, EValue::Bust),
+                (1, EHandOutcome::Push, 2, 2, EValue::H19),
+            ]
+        );
+        assert_eq!(hands[1].cards, vec![cards[3], cards[7], cards[8]]);
+        assert_eq!(seats[0].bet_chips, 6);
+        assert_eq!(seats[0].win_chips, 2);
+    }
+
+    /// 多个座位 各自下注 按座位顺序行动并分别结算
+    #[tokio::test]
+    async fn test_diy_deck_multi_seats() {
+        let cards = [
+            ECardNumber::Ten,
+            ECardNumber::Nine,
+            ECardNumber::Ten,
+            ECardNumber::Eight,
+            ECardNumber::Five,
+            ECardNumber::Seven,
+            ECardNumber::Two,
+        ]
+        .into_iter()
+        .map(|value| ECard {
+            color: ECardColor::Hearts,
+            value,
+        })
+        .collect();
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.buy_chips(10000);
+        assert_eq!(table.add_seat(10000), 1);
+        assert_eq!(table.add_seat(10000), 2);
+
+        // 座位0和座位1下注 座位2不参与
+        let result = table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        assert!(matches!(result[..], [ETableOutputEvent::WaitPlayerBet { seat: 1 }]));
+        assert_eq!(table.get_current_seat(), Some(1));
+        table.receive_player_action(EPlayerAction::Bet(4)).unwrap();
+        assert_eq!(table.get_current_seat(), Some(2));
+        let result = table.receive_player_action(EPlayerAction::NoAction).unwrap();
+        println!("player bet result:{result:?}");
+        assert_eq!(table.player_hands.len(), 2);
+        assert_eq!(table.player_hands[0].seat, 0);
+        assert_eq!(table.player_hands[0].value(), EValue::H18);
+        assert_eq!(table.player_hands[1].seat, 1);
+        assert_eq!(table.player_hands[1].value(), EValue::H14);
+
+        assert_eq!(table.get_current_seat(), Some(0));
+        table.receive_player_action(EPlayerAction::Stand).unwrap();
+        assert_eq!(table.get_current_seat(), Some(1));
+        table.receive_player_action(EPlayerAction::Hit).unwrap();
+        let result = table.receive_player_action(EPlayerAction::Stand).unwrap();
+        println!("player stand result:{result:?}");
+        // 结算后回到座位0下注
+        assert_eq!(table.get_state(), ETableState::PlayerBet);
+        assert_eq!(table.get_current_seat(), Some(0));
+        let ETableOutputEvent::GameOver { seats, .. } = game_over(result) else {
+            unreachable!()
+        };
+        assert_eq!(seats.len(), 3);
+        assert_eq!(seats[0].win_chips, 4);
+        assert_eq!(seats[1].win_chips, 0);
+        assert_eq!(seats[2].bet_chips, 0);
+        assert_eq!(table.seats[0].player_chips, 10000 + 2);
+        assert_eq!(table.seats[1].player_chips, 10000 - 4);
+        assert_eq!(table.seats[2].player_chips, 10000);
+    }
+
+    /// 不同座位的spot编号相同 split的手牌数量上限按座位分别计算
+    #[tokio::test]
+    async fn test_diy_deck_multi_seats_split() {
+        let cards = [
+            ECardNumber::Eight,
+            ECardNumber::Eight,
+            ECardNumber::Ten,
+            ECardNumber::Eight,
+            ECardNumber::Eight,
+            ECardNumber::Nine,
+            ECardNumber::Three,
+            ECardNumber::Ten,
+        ]
+        .into_iter()
+        .map(|value| ECard {
+            color: ECardColor::Hearts,
+            value,
+        })
+        .collect();
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.rule.split_max_hands = 2;
+        table.buy_chips(10000);
+        assert_eq!(table.add_seat(10000), 1);
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        assert_eq!(table.player_hands.len(), 2);
+        assert_eq!(table.player_hands[1].seat, 1);
+        assert_eq!(table.player_hands[1].spot, 0);
+        assert_eq!(
+            table.get_state(),
+            ETableState::PlayerSplitOrDoubleDownOrHitOrStand(0)
+        );
+        table.receive_player_action(EPlayerAction::Split).unwrap();
+        assert_eq!(table.player_hands.len(), 3);
+        assert_eq!(table.player_hands[0].value(), EValue::H11);
+        assert_eq!(table.player_hands[1].value(), EValue::H18);
+        // 座位0已达到split上限 不影响座位1
+        assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(0));
+        table.receive_player_action(EPlayerAction::Stand).unwrap();
+        table.receive_player_action(EPlayerAction::Stand).unwrap();
+        assert_eq!(table.get_current_seat(), Some(1));
+        assert_eq!(
+            table.get_state(),
+            ETableState::PlayerSplitOrDoubleDownOrHitOrStand(2)
+        );
+    }
+
+    /// 边注在发牌后立即结算 未开放的边注和不存在的spot不能下注
+    #[tokio::test]
+    async fn test_diy_deck_side_bets() {
+        let cards = [
+            (ECardColor::Hearts, ECardNumber::Eight),
+            (ECardColor::Hearts, ECardNumber::Nine),
+            (ECardColor::Diamonds, ECardNumber::Eight),
+            (ECardColor::Spades, ECardNumber::Ten),
+        ]
+        .into_iter()
+        .map(|(color, value)| ECard { color, value })
+        .collect();
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.rule.side_bets = ESideBet::iter().map(SSideBetRule::new).collect();
+        table.buy_chips(10000);
+        let wager = |spot, side_bet, amount| SSideBetWager {
+            spot,
+            side_bet,
+            amount,
+        };
+        assert!(matches!(
+            table.receive_player_action(EPlayerAction::BetWithSideBets(
+                vec![2],
+                vec![wager(1, ESideBet::PerfectPairs, 1)]
+            )),
+            Err(EPlayerActionError::SideBetError)
+        ));
+        table.rule.side_bets.retain(|rule| rule.side_bet != ESideBet::LuckyLadies);
+        assert!(matches!(
+            table.receive_player_action(EPlayerAction::BetWithSideBets(
+                vec![2],
+                vec![wager(0, ESideBet::LuckyLadies, 1)]
+            )),
+            Err(EPlayerActionError::SideBetError)
+        ));
+        assert_eq!(table.seats[0].player_chips, 10000);
+
+        // 玩家8-8(红桃 方块) 庄家明牌9
+        let result = table
+            .receive_player_action(EPlayerAction::BetWithSideBets(
+                vec![2],
+                vec![
+                    wager(0, ESideBet::PerfectPairs, 1),
+                    wager(0, ESideBet::TwentyOnePlusThree, 2),
+                ],
+            ))
+            .unwrap();
+        println!("player bet result:{result:?}");
+        let ETableOutputEvent::InitGameWithCards { side_bet_results, .. } = &result[0] else {
+            panic!("unexpected result: {result:?}");
+        };
+        assert_eq!(side_bet_results.len(), 2);
+        assert_eq!(side_bet_results[0].result, Some(ESideBetResult::ColoredPair));
+        assert_eq!(side_bet_results[0].win_chips, 13);
+        assert_eq!(side_bet_results[1].result, None);
+        assert_eq!(side_bet_results[1].win_chips, 0);
+        assert_eq!(table.seats[0].player_chips, 10000 - 2 - 1 - 2 + 13);
+    }
+
+    /// 西班牙21点 玩家21点总是获胜并支付6-7-8奖金 double之后可以救援
+    #[tokio::test]
+    async fn test_diy_deck_spanish_21() {
+        let cards = [
+            (ECardColor::Hearts, ECardNumber::Six),
+            (ECardColor::Clubs, ECardNumber::Five),
+            (ECardColor::Spades, ECardNumber::Seven),
+            (ECardColor::Clubs, ECardNumber::Six),
+            (ECardColor::Diamonds, ECardNumber::Eight),
+            (ECardColor::Clubs, ECardNumber::King),
+            // 第二局
+            (ECardColor::Hearts, ECardNumber::Five),
+            (ECardColor::Clubs, ECardNumber::Nine),
+            (ECardColor::Spades, ECardNumber::Six),
+            (ECardColor::Diamonds, ECardNumber::Nine),
+            (ECardColor::Clubs, ECardNumber::Two),
+        ]
+        .into_iter()
+        .map(|(color, value)| ECard { color, value })
+        .collect();
+        let mut table = STable::new_diy_deck(cards, ERulePreset::Spanish21.rule(), 0);
+        table.buy_chips(10000);
+
+        // 玩家6-7-8(不同花色) 庄家5-6-K 同为21点
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerSurrender(0));
+        table.receive_player_action(EPlayerAction::NoAction).unwrap();
+        table.receive_player_action(EPlayerAction::Hit).unwrap();
+        assert_eq!(table.player_hands[0].value(), EValue::H21);
+        let result = table.receive_player_action(EPlayerAction::Stand).unwrap();
+        println!("player action result:{result:?}");
+        let ETableOutputEvent::GameOver { seats, .. } = game_over(result) else {
+            unreachable!()
+        };
+        // 奖金按3:2赔付
+        assert_eq!(seats[0].win_chips, 2 + 3);
+        assert_eq!(table.seats[0].player_chips, 10000 + 3);
+
+        // 玩家5-6 double拿到2 救援后只输掉原始下注
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        table.receive_player_action(EPlayerAction::NoAction).unwrap();
+        table.receive_player_action(EPlayerAction::DoubleDown).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerRescueOrStand(0));
+        assert_eq!(table.get_current_seat(), Some(0));
+        let result = table.receive_player_action(EPlayerAction::Surrender).unwrap();
+        assert!(matches!(
+            result[0],
+            ETableOutputEvent::PlayerRescue {
+                hand_index: 0,
+                is_player_stop: true
+            }
+        ));
+        let ETableOutputEvent::GameOver { seats, .. } = game_over(result) else {
+            unreachable!()
+        };
+        assert_eq!(seats[0].bet_chips, 4);
+        assert_eq!(seats[0].win_chips, 2);
+        assert_eq!(table.seats[0].player_chips, 10000 + 3 - 2);
+    }
+
+    #[tokio::test]
+    /// Free Bet 免费split和免费double 庄家22点时平局
+    async fn test_diy_deck_free_bet() {
+        let cards = [
+            (ECardColor::Hearts, ECardNumber::Eight),
+            (ECardColor::Clubs, ECardNumber::Ten),
+            (ECardColor::Spades, ECardNumber::Eight),
+            (ECardColor::Clubs, ECardNumber::Six),
+            // split后的两手牌
+            (ECardColor::Diamonds, ECardNumber::Two),
+            (ECardColor::Clubs, ECardNumber::Three),
+            // 第一手牌8-2 免费double
+            (ECardColor::Hearts, ECardNumber::Nine),
+            // 第二手牌8-3停牌
+            // 庄家10-6拿牌到22点
+            (ECardColor::Spades, ECardNumber::Six),
+            // 第二局 玩家9-2 免费double 庄家10-7
+            (ECardColor::Hearts, ECardNumber::Nine),
+            (ECardColor::Clubs, ECardNumber::Ten),
+            (ECardColor::Spades, ECardNumber::Two),
+            (ECardColor::Clubs, ECardNumber::Seven),
+            (ECardColor::Diamonds, ECardNumber::Ten),
+        ]
+        .into_iter()
+        .map(|(color, value)| ECard { color, value })
+        .collect();
+        let mut table = STable::new_diy_deck(cards, ERulePreset::FreeBet6D.rule(), 0);
+        table.buy_chips(10000);
+
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerSplitOrDoubleDownOrHitOrStand(0));
+        // 免费split 不扣除筹码
+        table.receive_player_action(EPlayerAction::Split).unwrap();
+        assert_eq!(table.seats[0].player_chips, 10000 - 2);
+        assert_eq!(table.player_hands[1].get_bet(), 0);
+        assert_eq!(table.player_hands[1].get_wager(), 2);
+        // 硬10点免费double
+        table.receive_player_action(EPlayerAction::DoubleDown).unwrap();
+        assert_eq!(table.seats[0].player_chips, 10000 - 2);
+        assert_eq!(table.player_hands[0].get_wager(), 4);
+        assert_eq!(table.player_hands[0].value(), EValue::H19);
+        let result = table.receive_player_action(EPlayerAction::Stand).unwrap();
+        println!("player action result:{result:?}");
+        let ETableOutputEvent::GameOver { seats, .. } = game_over(result) else {
+            unreachable!()
+        };
+        // 庄家22点 两手牌均平局 只退还自有下注 免费下注不退还
+        assert_eq!(seats[0].bet_chips, 2);
+        assert_eq!(seats[0].win_chips, 2);
+        assert_eq!(table.seats[0].player_chips, 10000);
+
+        // 免费double获胜 自有下注和免费下注均按1:1赔付
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        let result = table.receive_player_action(EPlayerAction::DoubleDown).unwrap();
+        println!("player action result:{result:?}");
+        let ETableOutputEvent::GameOver { seats, hands, .. } = game_over(result) else {
+            unreachable!()
+        };
+        assert_eq!(hands[0].value, EValue::H21);
+        assert_eq!(seats[0].bet_chips, 2);
+        assert_eq!(seats[0].win_chips, 2 + 2 + 2);
+        assert_eq!(table.seats[0].player_chips, 10000 + 4);
+    }
+
+    #[tokio::test]
+    /// Blackjack Switch 交换两手牌的第二张牌 blackjack按1:1赔付 庄家22点平局
+    async fn test_diy_deck_blackjack_switch() {
+        let cards = [
+            (ECardColor::Hearts, ECardNumber::Ten),
+            (ECardColor::Clubs, ECardNumber::Six),
+            (ECardColor::Spades, ECardNumber::Seven),
+            (ECardColor::Clubs, ECardNumber::Six),
+            (ECardColor::Diamonds, ECardNumber::Queen),
+            (ECardColor::Clubs, ECardNumber::King),
+            // 第二局
+            (ECardColor::Hearts, ECardNumber::Ace),
+            (ECardColor::Clubs, ECardNumber::Ten),
+            (ECardColor::Spades, ECardNumber::Jack),
+            (ECardColor::Diamonds, ECardNumber::Ten),
+            (ECardColor::Clubs, ECardNumber::Six),
+            (ECardColor::Hearts, ECardNumber::Six),
+            (ECardColor::Spades, ECardNumber::Six),
+        ]
+        .into_iter()
+        .map(|(color, value)| ECard { color, value })
+        .collect();
+        let mut table = STable::new_diy_deck(cards, ERulePreset::BlackjackSwitch6D.rule(), 0);
+        table.buy_chips(10000);
+
+        // 一个下注发两手牌 10-6和6-Q 交换后为10-Q和6-6
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        assert_eq!(table.seats[0].player_chips, 10000 - 4);
+        assert_eq!(table.player_hands.len(), 2);
+        assert_eq!(table.get_state(), ETableState::PlayerSwitch(0));
+        assert_eq!(table.get_current_seat(), Some(0));
+        let result = table.receive_player_action(EPlayerAction::Switch).unwrap();
+        assert!(matches!(
+            result[0],
+            ETableOutputEvent::PlayerSwitch {
+                hand_index: 0,
+                is_switch: true
+            }
+        ));
+        assert_eq!(table.player_hands[0].value(), EValue::H20);
+        assert_eq!(table.player_hands[1].value(), EValue::H12);
+        // 交换后庄家自动peek
+        assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(0));
+        table.receive_player_action(EPlayerAction::Stand).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerSplitOrDoubleDownOrHitOrStand(1));
+        let result = table.receive_player_action(EPlayerAction::Stand).unwrap();
+        println!("player action result:{result:?}");
+        let ETableOutputEvent::GameOver { seats, .. } = game_over(result) else {
+            unreachable!()
+        };
+        // 20点获胜 12点失败
+        assert_eq!(seats[0].bet_chips, 4);
+        assert_eq!(seats[0].win_chips, 4);
+        assert_eq!(table.seats[0].player_chips, 10000);
+
+        // 不交换 A-10为blackjack 10-6停牌 庄家J-6拿牌到22点
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        let result = table.receive_player_action(EPlayerAction::NoAction).unwrap();
+        assert!(matches!(
+            result[0],
+            ETableOutputEvent::PlayerSwitch {
+                hand_index: 0,
+                is_switch: false
+            }
+        ));
+        assert!(table.player_hands[0].is_blackjack());
+        // 第一手牌blackjack 跳过
+        assert_eq!(table.get_state(), ETableState::PlayerDoubleDownOrHitOrStand(1));
+        let result = table.receive_player_action(EPlayerAction::Stand).unwrap();
+        println!("player action result:{result:?}");
+        let ETableOutputEvent::GameOver { seats, .. } = game_over(result) else {
+            unreachable!()
+        };
+        // blackjack按1:1赔付 庄家22点时16点平局
+        assert_eq!(seats[0].win_chips, 4 + 2);
+        assert_eq!(table.seats[0].player_chips, 10000 + 2);
+    }
+
+    /// 交换后得到的A+10只算21点 不算blackjack 需要继续操作 庄家21点时平局
+    #[tokio::test]
+    async fn test_diy_deck_blackjack_switch_ace_ten() {
+        let cards = [
+            (ECardColor::Hearts, ECardNumber::Ace),
+            (ECardColor::Clubs, ECardNumber::Nine),
+            (ECardColor::Spades, ECardNumber::Ten),
+            (ECardColor::Clubs, ECardNumber::Five),
+            (ECardColor::Diamonds, ECardNumber::King),
+            (ECardColor::Hearts, ECardNumber::Five),
+            (ECardColor::Spades, ECardNumber::Six),
+        ]
+        .into_iter()
+        .map(|(color, value)| ECard { color, value })
+        .collect();
+        let mut table = STable::new_diy_deck(cards, ERulePreset::BlackjackSwitch6D.rule(), 0);
+        table.buy_chips(10000);
+
+        // A-5和9-K 交换后为A-K和9-5
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        table.receive_player_action(EPlayerAction::Switch).unwrap();
+        assert_eq!(table.player_hands[0].value(), EValue::S21);
+        assert!(!table.player_hands[0].is_blackjack());
+        assert!(matches!(
+            table.get_state(),
+            ETableState::PlayerDoubleDownOrHitOrStand(0) | ETableState::PlayerHitOrStand(0)
+        ));
+        table.receive_player_action(EPlayerAction::Stand).unwrap();
+        let result = table.receive_player_action(EPlayerAction::Stand).unwrap();
+        println!("player action result:{result:?}");
+        let ETableOutputEvent::GameOver {
+            seats,
+            hands,
+            dealer_value,
+            ..
+        } = game_over(result)
+        else {
+            unreachable!()
+        };
+        // 庄家10-5拿6到21点 交换得到的21点平局 14点失败
+        assert_eq!(dealer_value, EValue::H21);
+        assert_eq!(hands[0].outcome, EHandOutcome::Push);
+        assert_eq!(hands[1].outcome, EHandOutcome::Lose);
+        assert_eq!(seats[0].win_chips, 2);
+        assert_eq!(table.seats[0].player_chips, 10000 - 2);
+    }
+
+    #[tokio::test]
+    /// 五张牌Charlie 第五张牌未bust时立即结束该手牌 即使点数小于庄家也获胜
+    async fn test_diy_deck_five_card_charlie() {
+        let cards = [
+            (ECardColor::Hearts, ECardNumber::Two),
+            (ECardColor::Clubs, ECardNumber::Ten),
+            (ECardColor::Spades, ECardNumber::Three),
+            (ECardColor::Clubs, ECardNumber::Queen),
+            (ECardColor::Diamonds, ECardNumber::Two),
+            (ECardColor::Clubs, ECardNumber::Four),
+            (ECardColor::Hearts, ECardNumber::Five),
+        ]
+        .into_iter()
+        .map(|(color, value)| ECard { color, value })
+        .collect();
+        let mut rule = SGameRule::default();
+        rule.charlie_cards = Some(5);
+        let mut table = STable::new_diy_deck(cards, rule, 0);
+        table.buy_chips(10000);
+
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        table.receive_player_action(EPlayerAction::Hit).unwrap();
+        table.receive_player_action(EPlayerAction::Hit).unwrap();
+        assert_eq!(table.get_state(), ETableState::PlayerHitOrStand(0));
+        let result = table.receive_player_action(EPlayerAction::Hit).unwrap();
+        assert!(matches!(
+            result[0],
+            ETableOutputEvent::PlayerDrawCard {
+                hand_index: 0,
+                is_player_stop: true,
+                ..
+            }
+        ));
+        let ETableOutputEvent::GameOver { seats, hands, .. } = game_over(result) else {
+            unreachable!()
+        };
+        assert_eq!(hands[0].value, EValue::H16);
+        // 16点对庄家20点 Charlie按1:1获胜
+        assert_eq!(seats[0].win_chips, 4);
+        assert_eq!(table.seats[0].player_chips, 10000 + 2);
+    }
+
+    #[tokio::test]
+    async fn test_diy_deck_cut_card() {
+        let cards = [
+            (ECardColor::Hearts, ECardNumber::Ten),
+            (ECardColor::Clubs, ECardNumber::Seven),
+            (ECardColor::Spades, ECardNumber::Nine),
+            (ECardColor::Clubs, ECardNumber::Queen),
+            (ECardColor::Diamonds, ECardNumber::Two),
+            (ECardColor::Clubs, ECardNumber::Four),
+            (ECardColor::Hearts, ECardNumber::Five),
+            (ECardColor::Spades, ECardNumber::Six),
+        ]
+        .into_iter()
+        .map(|(color, value)| ECard { color, value })
+        .collect();
+        let mut rule = SGameRule::default();
+        rule.cut_card_cards = Some(4);
+        rule.burn_cards = 1;
+        let mut table = STable::new_diy_deck(cards, rule, 0);
+        table.buy_chips(10000);
+        assert_eq!(table.cut_card_remain, 4);
+        assert!(!table.is_cut_card_out());
+
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        let result = table.receive_player_action(EPlayerAction::Stand).unwrap();
+        println!("player action result:{result:?}");
+        // 19点对庄家17点 本局发出4张牌后切牌卡已发出 结算后自动洗牌并烧掉一张牌
+        let [.., ETableOutputEvent::GameOver { seats, .. }, ETableOutputEvent::Shuffle {
+            burn_cards_num: 1,
+            cut_card_remain: 4,
+        }] = &result[..]
+        else {
+            panic!("unexpected result: {result:?}");
+        };
+        assert_eq!(seats[0].win_chips, 4);
+        assert_eq!(table.get_state(), ETableState::PlayerBet);
+        assert_eq!(table.deck.remain_cards_num(), ECardNum::Some(7));
+        assert!(!table.is_cut_card_out());
+    }
+
+    #[tokio::test]
+    async fn test_diy_deck_exhausted() {
+        let cards = [
+            (ECardColor::Hearts, ECardNumber::Two),
+            (ECardColor::Clubs, ECardNumber::Ten),
+            (ECardColor::Spades, ECardNumber::Three),
+            (ECardColor::Clubs, ECardNumber::Seven),
+            (ECardColor::Diamonds, ECardNumber::Four),
+        ]
+        .into_iter()
+        .map(|(color, value)| ECard { color, value })
+        .collect();
+        let mut table = STable::new_diy_deck(cards, SGameRule::default(), 0);
+        table.buy_chips(10000);
+        table.receive_player_action(EPlayerAction::Bet(2)).unwrap();
+        table.receive_player_action(EPlayerAction::Hit).unwrap();
+        // 牌库用尽时不会在牌局中途洗牌
+        assert!(matches!(
+            table.receive_player_action(EPlayerAction::Hit),
+            Err(EPlayerActionError::DeckExhaustedError)
+        ));
+        assert_eq!(table.player_hands[0].hand.cards.len(), 3);
+        assert_eq!(table.deck.remain_cards_num(), ECardNum::Some(0));
+
+        // 剩余的牌不足以发牌时退还下注
+        table.shuffle();
+        for _ in 0..3 {
+            table.deck.draw().unwrap();
+        }
+        let chips = table.seats[0].player_chips;
+        assert!(matches!(
+            table.receive_player_action(EPlayerAction::Bet(2)),
+            Err(EPlayerActionError::DeckExhaustedError)
+        ));
+        assert_eq!(table.seats[0].player_chips, chips);
+        assert_eq!(table.get_state(), ETableState::PlayerBet);
+        assert_eq!(table.deck.remain_cards_num(), ECardNum::Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_queue_deck_shuffle_procedure() {
+        // 相同种子的牌库 按规则的洗牌方式洗牌后牌序不同
+        let draw_after_shuffle = |shuffle_procedure: EShuffleProcedure| {
+            let rule = SGameRule {
+                shuffle_procedure,
+                ..Default::default()
+            };
+            let mut table = STable::new_queue_deck(rule, 3);
+            table.shuffle();
+            (0..52).map(|_| table.deck.draw().unwrap()).collect::<Vec<_>>()
+        };
+        let dealer = EShuffleProcedure::Dealer(SDealerShuffle::default());
+        assert_eq!(draw_after_shuffle(dealer), draw_after_shuffle(dealer));
+        assert_ne!(draw_after_shuffle(dealer), draw_after_shuffle(EShuffleProcedure::Uniform));
+    }
+
+    #[tokio::test]
+    async fn test_csm_deck() {
+        let player = SStaticStrategyPlayer::new();
+        let mut table = STable::new_csm_deck(10, SGameRule::default(), 0);
+        table.buy_chips(10000);
+        assert_eq!(table.deck.remain_cards_num(), ECardNum::Some(42));
+
+        for _ in 0..20 {
+            let mut result = table.receive_player_action(player.action(&table)).unwrap();
+            while !result.iter().any(|event| matches!(event, ETableOutputEvent::GameOver { .. })) {
+                result = table.receive_player_action(player.action(&table)).unwrap();
+            }
+            // 结算后弃牌放回机器 不需要洗牌
+            assert_eq!(table.deck.remain_cards_num(), ECardNum::Some(42));
+            assert_eq!(table.get_state(), ETableState::PlayerBet);
+        }
+
+        // 所有牌都留在机器外时 机器内仍保留足够的牌发牌
+        // 连续洗牌机没有切牌卡 机器内的牌数少于切牌卡位置时也不洗牌和烧牌
+        let rule = SGameRule {
+            cut_card_cards: Some(20),
+            burn_cards: 1,
+            ..Default::default()
+        };
+        let mut table = STable::new_csm_deck(52, rule, 0);
+        table.buy_chips(10000);
+        assert_eq!(table.cut_card_remain, 0);
+        for _ in 0..20 {
+            let mut result = table.receive_player_action(player.action(&table)).unwrap();
+            while !result.iter().any(|event| matches!(event, ETableOutputEvent::GameOver { .. })) {
+                result = table.receive_player_action(player.action(&table)).unwrap();
+            }
+            assert!(!result.iter().any(|event| matches!(event, ETableOutputEvent::Shuffle { .. })));
+            assert!(!table.is_cut_card_out());
+            assert_eq!(table.get_state(), ETableState::PlayerBet);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_seed_reproducible() {
+        let player = SStaticStrategyPlayer::new();
+        let mut rule = SGameRule::default();
+        rule.cut_card_jitter = 5;
+        // 记录每局结束前的所有手牌
+        let play = |seed: u64| {
+            let mut table = STable::new_queue_deck(rule.clone(), seed);
+            table.buy_chips(10000);
+            assert_eq!(table.seed, seed);
+            let mut cards = Vec::new();
+            for _ in 0..30 {
+                let mut result = table.receive_player_action(player.action(&table)).unwrap();
+                while !result.iter().any(|event| matches!(event, ETableOutputEvent::GameOver { .. })) {
+                    result = table.receive_player_action(player.action(&table)).unwrap();
+                }
+                let ETableOutputEvent::GameOver {
+                    hands, dealer_cards, ..
+                } = game_over(result)
+                else {
+                    unreachable!()
+                };
+                cards.push(dealer_cards);
+                cards.extend(hands.into_iter().map(|hand| hand.cards));
+            }
+            (cards, table.seats[0].player_chips)
+        };
+        assert_eq!(play(42), play(42));
+        assert_ne!(play(42).0, play(43).0);
 
+        // 牌桌的随机数流与牌库的洗牌随机数流相互独立
+        let mut table = STable::new_queue_deck(rule, 42);
+        assert_ne!(table.rng.gen::<u64>(), ChaCha12Rng::seed_from_u64(42).gen::<u64>());
     }
-}
\ No newline at end of file
+}
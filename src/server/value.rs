@@ -1,7 +1,8 @@
+use serde::{Deserialize, Serialize};
 use std::ops::Add;
 use strum_macros::{Display, EnumIter};
 
-#[derive(Debug, Copy, Clone, PartialEq, EnumIter, Hash, Display, Eq, Default)]
+#[derive(Debug, Copy, Clone, PartialEq, EnumIter, Hash, Display, Eq, Default, Serialize, Deserialize)]
 pub enum EValue {
     #[default]
     None,
@@ -40,6 +41,24 @@ pub enum EValue {
 }
 
 impl EValue {
+    /// 是否为软牌(A计为11点)
+    pub fn is_soft(&self) -> bool {
+        matches!(
+            self,
+            EValue::S11
+                | EValue::S12
+                | EValue::S13
+                | EValue::S14
+                | EValue::S15
+                | EValue::S16
+                | EValue::S17
+                | EValue::S18
+                | EValue::S19
+                | EValue::S20
+                | EValue::S21
+        )
+    }
+
     pub fn to_point(&self) -> u8 {
         match self {
             EValue::None => 0,